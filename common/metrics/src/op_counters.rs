@@ -31,6 +31,29 @@ impl DurationHistogram {
         let v = d.as_secs() as f64 + f64::from(d.subsec_nanos()) / 1e9;
         self.histogram.observe(v);
     }
+
+    /// Number of observed durations
+    pub fn sample_count(&self) -> u64 {
+        self.histogram.get_sample_count()
+    }
+
+    /// Sum of the observed durations, in seconds
+    pub fn sample_sum(&self) -> f64 {
+        self.histogram.get_sample_sum()
+    }
+
+    /// Upper bound (in seconds) and cumulative count of every bucket
+    pub fn buckets(&self) -> Vec<(f64, u64)> {
+        let mut buckets = vec![];
+        for family in self.histogram.collect() {
+            for metric in family.get_metric() {
+                for bucket in metric.get_histogram().get_bucket() {
+                    buckets.push((bucket.get_upper_bound(), bucket.get_cumulative_count()));
+                }
+            }
+        }
+        buckets
+    }
 }
 
 #[derive(Clone)]
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::config::SafetyRulesConfig;
+use anyhow::{ensure, Result};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -12,7 +13,80 @@ pub struct ConsensusConfig {
     pub contiguous_rounds: u32,
     pub max_pruned_blocks_in_mem: usize,
     pub round_initial_timeout_ms: u64,
+    // Max per-validator jitter added to round timeouts, as a fraction of the round timeout
+    pub round_timeout_jitter_ratio: f64,
+    // Number of past rounds whose pending votes are kept, so that late votes can still complete
+    // their certificates. 0 keeps the votes of the current round only
+    pub pending_votes_round_window: u64,
+    // Number of the most recent safety rules states kept for debugging, 0 keeps none
+    pub consensus_state_history_size: usize,
+    // Number of peers block retrieval requests are sent to in parallel
+    pub block_retrieval_concurrency: usize,
+    // Timeout of the first block retrieval attempt, doubled on subsequent attempts
+    pub block_retrieval_timeout_ms: u64,
+    // Delay before retrying a block retrieval from a peer that failed it, multiplied by
+    // `block_retrieval_backoff_multiplier` on every further failure of the peer, up to
+    // `block_retrieval_backoff_max_ms`
+    pub block_retrieval_backoff_initial_ms: u64,
+    pub block_retrieval_backoff_multiplier: f64,
+    pub block_retrieval_backoff_max_ms: u64,
+    // Max number of attempts to retrieve a block before giving up
+    pub block_retrieval_max_attempts: u32,
+    // Deadline of every block retrieval of a sync up with a peer, as a ratio of the round
+    // initial timeout. The state sync and the commits of a sync up are never cancelled
+    pub sync_up_timeout_ratio: f64,
+    // Number of block retrieval requests served per second to a single peer, 0 disables the rate
+    // limiting. A syncing peer retrieves the blocks one request at a time, so the rate must leave
+    // room for catching up
+    pub block_retrieval_rate_per_sec: u64,
+    // Max number of block retrieval requests a single peer can send in a burst
+    pub block_retrieval_burst: u64,
+    // Max number of blocks served in response to a single block retrieval request
+    pub max_retrieval_blocks: u64,
+    // Delay before the response to a block retrieval request is constructed, so that the
+    // latency critical events are handled first under load. 0 serves the requests right away
+    pub block_retrieval_serve_delay_ms: u64,
+    // Number of recently served blocks kept in memory, so that repeated retrievals of the same
+    // chain (e.g. by several peers catching up) are served without block store lookups
+    pub block_retrieval_cache_size: usize,
+    // Number of uncommitted blocks on the highest certified branch above which proposals are
+    // limited to `backpressure_block_size` transactions
+    pub backpressure_pending_blocks: usize,
+    // Max number of transactions in a proposed block while under backpressure
+    pub backpressure_block_size: u64,
+    // Max number of uncommitted blocks on a branch, proposals extending a longer branch are
+    // neither executed nor voted for
+    pub max_pending_blocks: usize,
+    // Min time between two proposals of this validator, the proposals of the rounds starting
+    // sooner are held back. 0 to propose as soon as a round starts
+    pub min_proposal_interval_ms: u64,
+    // Max size in bytes of a serialized proposal message, larger proposals have their payload
+    // cut down before being broadcast
+    pub max_proposal_bytes: u64,
+    // Time to wait for the proposal of the valid proposer after receiving the one of the backup
+    // proposer, before voting for the backup proposal
+    pub backup_proposal_delay_ms: u64,
+    // Max number of rounds the node can lag behind the highest round observed from its peers
+    // while still being reported as synced
+    pub max_synced_round_gap: u64,
+    // Min time between two SyncInfos sent to the same stale peer to help it catch up, unless our
+    // state advanced in the meantime. 0 sends one on every stale message of the peer
+    pub sync_info_help_interval_ms: u64,
+    // Max number of proposals for future rounds kept until the node catches up with them
+    pub max_future_proposals: usize,
+    // Number of rounds ahead of the current one scanned for the next round this validator leads
+    pub proposal_lookahead_rounds: u64,
+    // Number of upcoming rounds whose proposers receive the votes, so that a QC can still form if
+    // the next proposer is down
+    pub vote_upcoming_leaders: u64,
+    // Window within which the rejected transactions of the blocks executed back-to-back are
+    // sent to mempool in a single notification, 0 to notify mempool of every block separately
+    pub txn_commit_coalescing_window_ms: u64,
     pub proposer_type: ConsensusProposerType,
+    pub timeout_mode: TimeoutMode,
+    // How the proposals are disseminated to the validators. The timeouts always are broadcast to
+    // all of them so that a TC can form
+    pub broadcast_strategy: BroadcastStrategy,
     pub safety_rules: SafetyRulesConfig,
 }
 
@@ -27,6 +101,35 @@ impl Default for ConsensusConfig {
             contiguous_rounds: 2,
             max_pruned_blocks_in_mem: 10000,
             round_initial_timeout_ms: 1000,
+            round_timeout_jitter_ratio: 0.1,
+            pending_votes_round_window: 0,
+            consensus_state_history_size: 0,
+            block_retrieval_concurrency: 2,
+            block_retrieval_timeout_ms: 200,
+            block_retrieval_backoff_initial_ms: 100,
+            block_retrieval_backoff_multiplier: 2.0,
+            block_retrieval_backoff_max_ms: 2000,
+            block_retrieval_max_attempts: 50,
+            sync_up_timeout_ratio: 3.0,
+            block_retrieval_rate_per_sec: 0,
+            block_retrieval_burst: 20,
+            max_retrieval_blocks: 100,
+            block_retrieval_serve_delay_ms: 0,
+            block_retrieval_cache_size: 100,
+            backpressure_pending_blocks: 20,
+            backpressure_block_size: 100,
+            max_pending_blocks: 200,
+            min_proposal_interval_ms: 0,
+            max_proposal_bytes: 8 * 1024 * 1024,
+            backup_proposal_delay_ms: 500,
+            max_synced_round_gap: 5,
+            sync_info_help_interval_ms: 0,
+            max_future_proposals: 10,
+            proposal_lookahead_rounds: 100,
+            vote_upcoming_leaders: 1,
+            txn_commit_coalescing_window_ms: 0,
+            timeout_mode: TimeoutMode::NilVote,
+            broadcast_strategy: BroadcastStrategy::Full,
             safety_rules: SafetyRulesConfig::default(),
         }
     }
@@ -36,6 +139,17 @@ impl ConsensusConfig {
     pub fn set_data_dir(&mut self, data_dir: PathBuf) {
         self.safety_rules.set_data_dir(data_dir);
     }
+
+    pub fn validate(&self) -> Result<()> {
+        // a sync up retrieves the 3-chain of the commit certificate, it should fit in a single
+        // block retrieval response
+        ensure!(
+            self.max_retrieval_blocks >= 3,
+            "max_retrieval_blocks should be at least 3, got {}",
+            self.max_retrieval_blocks
+        );
+        Ok(())
+    }
 }
 
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -47,6 +161,28 @@ pub enum ConsensusProposerType {
     RotatingProposer,
     // Committed history based proposer election
     LeaderReputation(LeaderReputationConfig),
+    // Round based proposer election weighted by voting power
+    WeightedRound,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeoutMode {
+    // Vote for a NIL block on timeout unless already voted in the round
+    NilVote,
+    // Repeat the vote of the round on timeout, or only broadcast a timeout signature if there
+    // is none
+    RepeatLastOrAbstain,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum BroadcastStrategy {
+    // Send to all the validators
+    Full,
+    // Send to `fanout` random validators. A proposal is forwarded to `fanout` random validators
+    // by every validator receiving it for the first time
+    Gossip { fanout: usize },
 }
 
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -54,3 +190,18 @@ pub struct LeaderReputationConfig {
     pub active_weights: u64,
     pub inactive_weights: u64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_max_retrieval_blocks_validation() {
+        let mut config = ConsensusConfig::default();
+        assert!(config.validate().is_ok());
+        config.max_retrieval_blocks = 3;
+        assert!(config.validate().is_ok());
+        config.max_retrieval_blocks = 2;
+        assert!(config.validate().is_err());
+    }
+}
@@ -13,10 +13,52 @@ pub struct MempoolConfig {
     pub shared_mempool_min_broadcast_recipient_count: Option<usize>,
     pub max_broadcasts_per_peer: usize,
     pub capacity: usize,
-    // max number of transactions per user in Mempool
-    pub capacity_per_user: usize,
+    // max number of outstanding transactions per account in Mempool, formerly named
+    // `capacity_per_user` which existing configs can still use
+    #[serde(alias = "capacity_per_user")]
+    pub max_txns_per_account: usize,
+    // max distance between the sequence number of an admitted transaction and the current
+    // sequence number of its account
+    pub max_sequence_gap: u64,
+    // min percentage by which a transaction update has to bump the gas price to replace
+    // the transaction already in Mempool
+    pub min_gas_price_bump_pct: u64,
+    // initial min gas price a non-governance transaction needs to be admitted into Mempool,
+    // can be raised at runtime under load
+    pub min_gas_price: u64,
+    // raise the min gas price with the occupancy of Mempool: up to `gas_floor_occupancy_threshold`
+    // of the capacity the floor is `min_gas_price`, above it the floor grows linearly up to
+    // `max_gas_floor` when Mempool is full. Recomputed on every gc. 0 disables the adjustment
+    pub max_gas_floor: u64,
+    pub gas_floor_occupancy_threshold: f64,
+    // fraction of a block reserved for governance transactions together with the pending
+    // transactions of the same account they depend on
+    pub governance_reserved_fraction: f64,
+    // max number of transactions of a single account in a block pulled by Consensus, the rest
+    // of the account's transactions is left for the following blocks
+    pub max_txns_per_account_per_block: Option<usize>,
+    // fractions of the capacity governance and regular transactions can take at most, so that
+    // one class can't crowd out the other. Within its quota, a transaction can only evict
+    // transactions of its own class
+    pub governance_capacity_fraction: f64,
+    pub regular_capacity_fraction: f64,
+    // increase of the ranking score of a transaction for every second it has been waiting in
+    // Mempool, so that low gas transactions aren't starved. 0 ranks by gas price only
+    pub ranking_aging_coefficient: f64,
+    // share the block space across the senders by weighted round-robin instead of walking the
+    // priority queue, every sender gets a share in proportion to its weight (equal by default)
+    pub fair_queuing: bool,
     pub system_transaction_timeout_secs: u64,
     pub system_transaction_gc_interval_ms: u64,
+    // how long committed transactions are remembered, so that late re-broadcasts of them are
+    // rejected right away
+    pub committed_cache_ttl_secs: u64,
+    // how long the hashes of added transactions are remembered, so that identical copies of them
+    // (e.g. gossiped back by peers) are recognized without being processed again. 0 disables
+    // the check
+    pub known_txn_hash_ttl_secs: u64,
+    // max number of transactions returned by a single timeline read, larger requests are clamped
+    pub max_timeline_read: usize,
 }
 
 impl Default for MempoolConfig {
@@ -29,9 +71,37 @@ impl Default for MempoolConfig {
             shared_mempool_min_broadcast_recipient_count: None,
             max_broadcasts_per_peer: 25,
             capacity: 1_000_000,
-            capacity_per_user: 100,
+            max_txns_per_account: 100,
+            max_sequence_gap: 100,
+            min_gas_price_bump_pct: 10,
+            min_gas_price: 0,
+            max_gas_floor: 0,
+            gas_floor_occupancy_threshold: 0.5,
+            governance_reserved_fraction: 0.1,
+            max_txns_per_account_per_block: None,
+            governance_capacity_fraction: 1.0,
+            regular_capacity_fraction: 1.0,
+            ranking_aging_coefficient: 0.0,
+            fair_queuing: false,
             system_transaction_timeout_secs: 86400,
             system_transaction_gc_interval_ms: 180_000,
+            committed_cache_ttl_secs: 100,
+            known_txn_hash_ttl_secs: 30,
+            max_timeline_read: 10_000,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capacity_per_user_alias() {
+        let config: MempoolConfig = toml::from_str("capacity_per_user = 42").unwrap();
+        assert_eq!(config.max_txns_per_account, 42);
+
+        let config: MempoolConfig = toml::from_str("max_txns_per_account = 42").unwrap();
+        assert_eq!(config.max_txns_per_account, 42);
+    }
+}
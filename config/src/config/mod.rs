@@ -225,6 +225,8 @@ impl NodeConfig {
             );
         }
 
+        config.consensus.validate()?;
+
         let mut network_ids = HashSet::new();
         let input_dir = RootPath::new(input_path);
         config.execution.load(&input_dir)?;
@@ -192,6 +192,16 @@ impl Block {
         }
     }
 
+    /// Wraps the given block data into a block without signing it. Such a block doesn't pass
+    /// `validate_signatures` and is only meant for local inspection (e.g. previewing a proposal).
+    pub fn new_unsigned_proposal(block_data: BlockData) -> Self {
+        Block {
+            id: block_data.hash(),
+            block_data,
+            signature: None,
+        }
+    }
+
     /// Verifies that the proposal and the QC are correctly signed.
     /// If this is the genesis block, we skip these checks.
     pub fn validate_signatures(&self, validator: &ValidatorVerifier) -> anyhow::Result<()> {
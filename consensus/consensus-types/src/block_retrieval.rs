@@ -48,6 +48,12 @@ pub enum BlockRetrievalStatus {
     IdNotFound,
     // Can not find enough blocks but find some.
     NotEnoughBlocks,
+    // The requester exceeded its request rate limit, the request was not served.
+    RateLimited,
+    // The requested chain exceeds the number of blocks served per request, the beginning of the
+    // chain was returned. The requester retrieves the rest of it with a request starting at the
+    // parent of the last returned block.
+    Truncated,
 }
 
 /// Carries the returned blocks and the retrieval status.
@@ -83,6 +89,13 @@ impl BlockRetrievalResponse {
             num_blocks,
             self.blocks.len(),
         );
+        ensure!(
+            self.status != BlockRetrievalStatus::Truncated
+                || (!self.blocks.is_empty() && (self.blocks.len() as u64) < num_blocks),
+            "truncated response should return between 1 and {} blocks, get {}",
+            num_blocks - 1,
+            self.blocks.len(),
+        );
         self.blocks
             .iter()
             .try_fold(block_id, |expected_id, block| {
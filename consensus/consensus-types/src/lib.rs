@@ -14,6 +14,7 @@ pub mod quorum_cert;
 pub mod sync_info;
 pub mod timeout;
 pub mod timeout_certificate;
+pub mod timeout_msg;
 pub mod vote;
 pub mod vote_data;
 pub mod vote_msg;
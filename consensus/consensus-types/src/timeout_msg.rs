@@ -0,0 +1,91 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{Author, Round},
+    sync_info::SyncInfo,
+    timeout::Timeout,
+};
+use anyhow::{ensure, Context};
+use libra_crypto::{ed25519::Ed25519Signature, hash::CryptoHash};
+use libra_types::validator_verifier::ValidatorVerifier;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// TimeoutMsg is broadcast on a local timeout by a validator that didn't vote in the round and
+/// doesn't vote for a NIL block instead. It only carries the round signature, which can be
+/// aggregated to a TimeoutCertificate.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct TimeoutMsg {
+    /// The epoch and round that timed out
+    timeout: Timeout,
+    /// The validator that timed out
+    author: Author,
+    /// Signature of the author over the timeout
+    signature: Ed25519Signature,
+    /// Sync info carries information about highest QC, TC and LedgerInfo
+    sync_info: SyncInfo,
+}
+
+impl Display for TimeoutMsg {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "TimeoutMsg: [{}, author: {}]",
+            self.timeout,
+            self.author.short_str()
+        )
+    }
+}
+
+impl TimeoutMsg {
+    pub fn new(
+        timeout: Timeout,
+        author: Author,
+        signature: Ed25519Signature,
+        sync_info: SyncInfo,
+    ) -> Self {
+        Self {
+            timeout,
+            author,
+            signature,
+            sync_info,
+        }
+    }
+
+    pub fn timeout(&self) -> &Timeout {
+        &self.timeout
+    }
+
+    pub fn author(&self) -> Author {
+        self.author
+    }
+
+    pub fn signature(&self) -> &Ed25519Signature {
+        &self.signature
+    }
+
+    /// SyncInfo of the given timeout message
+    pub fn sync_info(&self) -> &SyncInfo {
+        &self.sync_info
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.timeout.epoch()
+    }
+
+    pub fn round(&self) -> Round {
+        self.timeout.round()
+    }
+
+    pub fn verify(&self, validator: &ValidatorVerifier) -> anyhow::Result<()> {
+        ensure!(
+            self.epoch() == self.sync_info.epoch(),
+            "TimeoutMsg has different epoch"
+        );
+        // As for the votes, SyncInfo is only verified in case we need it.
+        validator
+            .verify_signature(self.author, self.timeout.hash(), &self.signature)
+            .context("Failed to verify TimeoutMsg")
+    }
+}
@@ -352,6 +352,15 @@ impl BlockStore {
         Ok(())
     }
 
+    /// Returns the number of blocks on the path from the root to the highest certified block,
+    /// i.e. the blocks that are executed and certified but not committed yet.
+    pub fn uncommitted_block_count(&self) -> usize {
+        let inner = self.inner.read().unwrap();
+        inner
+            .path_from_root(inner.highest_certified_block().id())
+            .map_or(0, |path| path.len())
+    }
+
     /// Prune the tree up to next_root_id (keep next_root_id's block).  Any branches not part of
     /// the next_root_id's tree should be removed as well.
     ///
@@ -3,10 +3,20 @@
 
 use crate::{
     block_storage::{
-        block_store::sync_manager::NeedFetchResult, BlockReader, PendingVotes, VoteReceptionResult,
+        block_store::sync_manager::{
+            discard_duplicates, retrieval_candidates, retrieval_success_rate,
+            retrieve_with_backoff, sort_by_success_rate, NeedFetchResult, RetrievalBackoff,
+        },
+        BlockReader, BlockStore, PendingVotes, VoteReceptionResult,
+    },
+    counters,
+    persistent_liveness_storage::{PersistentLivenessStorage, SyncCheckpoint},
+    test_utils::{
+        build_empty_tree, build_simple_tree, consensus_runtime, timed_block_on, MockSharedStorage,
+        MockStorage, TreeInserter,
     },
-    test_utils::{build_empty_tree, build_simple_tree, TreeInserter},
 };
+use anyhow::format_err;
 use consensus_types::{
     block::{
         block_test_utils::{
@@ -21,10 +31,16 @@ use consensus_types::{
 };
 use libra_crypto::{HashValue, PrivateKey};
 use libra_types::{
+    account_address::AccountAddress, on_chain_config::ValidatorSet,
     validator_signer::ValidatorSigner, validator_verifier::random_validator_verifier,
 };
 use proptest::prelude::*;
-use std::{cmp::min, collections::HashSet};
+use std::{
+    cmp::min,
+    collections::HashSet,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
 #[test]
 fn test_highest_block_and_quorum_cert() {
@@ -435,3 +451,195 @@ fn test_empty_reconfiguration_suffix() {
     );
     a6.verify_well_formed().unwrap_err();
 }
+
+#[test]
+fn test_retrieval_candidates_order() {
+    let peers: Vec<Author> = (0..4)
+        .map(|i| ValidatorSigner::from_int(i).author())
+        .collect();
+    // explicit candidates keep their order and come before the remaining signers
+    let candidates = vec![peers[2], peers[0]];
+    let signers = vec![peers[3], peers[0], peers[1], peers[2]];
+    assert_eq!(
+        retrieval_candidates(&candidates, signers),
+        vec![peers[2], peers[0], peers[3], peers[1]]
+    );
+    // a candidate doesn't have to be a signer
+    assert_eq!(
+        retrieval_candidates(&[peers[3]], vec![peers[1]]),
+        vec![peers[3], peers[1]]
+    );
+}
+
+#[test]
+fn test_retrieval_backoff() {
+    let backoff = RetrievalBackoff {
+        initial: Duration::from_millis(20),
+        multiplier: 2.0,
+        max: Duration::from_millis(50),
+        max_attempts: 4,
+    };
+    let schedule: Vec<_> = (0..4).map(|failures| backoff.delay(failures)).collect();
+    assert_eq!(
+        schedule,
+        vec![
+            Duration::from_millis(0),
+            Duration::from_millis(20),
+            Duration::from_millis(40),
+            Duration::from_millis(50),
+        ]
+    );
+
+    let mut runtime = consensus_runtime();
+    let peer = ValidatorSigner::from_int(0).author();
+    let block = Block::make_genesis_block();
+    let attempts = Arc::new(Mutex::new(vec![]));
+    // the peer fails twice, then succeeds
+    let result = timed_block_on(
+        &mut runtime,
+        retrieve_with_backoff(block.id(), vec![peer], 2, backoff, |_, attempt| {
+            let attempts = attempts.clone();
+            let block = block.clone();
+            async move {
+                attempts.lock().unwrap().push(Instant::now());
+                if attempt <= 2 {
+                    Err(format_err!("peer is flapping"))
+                } else {
+                    Ok(vec![block])
+                }
+            }
+        }),
+    );
+    assert_eq!(result.unwrap(), vec![block.clone()]);
+    let attempts = attempts.lock().unwrap().clone();
+    assert_eq!(attempts.len(), 3);
+    assert!(attempts[1] - attempts[0] >= schedule[1]);
+    assert!(attempts[2] - attempts[1] >= schedule[2]);
+
+    // gives up once out of attempts
+    let num_attempts = Arc::new(Mutex::new(0));
+    let result = timed_block_on(
+        &mut runtime,
+        retrieve_with_backoff(block.id(), vec![peer], 2, backoff, |_, _| {
+            let num_attempts = num_attempts.clone();
+            async move {
+                *num_attempts.lock().unwrap() += 1;
+                Err(format_err!("peer is down"))
+            }
+        }),
+    );
+    assert!(result.is_err());
+    assert_eq!(*num_attempts.lock().unwrap(), backoff.max_attempts);
+}
+
+#[test]
+fn test_retrieval_peer_results() {
+    let backoff = RetrievalBackoff {
+        initial: Duration::from_millis(1),
+        multiplier: 1.0,
+        max: Duration::from_millis(1),
+        max_attempts: 10,
+    };
+    let mut runtime = consensus_runtime();
+    let (flaky, reliable, unknown) = (
+        AccountAddress::random(),
+        AccountAddress::random(),
+        AccountAddress::random(),
+    );
+    let block = Block::make_genesis_block();
+    // the flaky peer is asked first and fails, the reliable one succeeds
+    for _ in 0..2 {
+        let result = timed_block_on(
+            &mut runtime,
+            retrieve_with_backoff(block.id(), vec![flaky, reliable], 1, backoff, |peer, _| {
+                let block = block.clone();
+                async move {
+                    if peer == flaky {
+                        Err(format_err!("timeout"))
+                    } else {
+                        Ok(vec![block])
+                    }
+                }
+            }),
+        );
+        assert_eq!(result.unwrap(), vec![block.clone()]);
+    }
+    let count = |peer: Author, result: &str| {
+        counters::BLOCK_RETRIEVAL_PEER_RESULTS
+            .with_label_values(&[&peer.short_str(), result])
+            .get()
+    };
+    assert_eq!((count(flaky, "success"), count(flaky, "failure")), (0, 2));
+    assert_eq!(
+        (count(reliable, "success"), count(reliable, "failure")),
+        (2, 0)
+    );
+    assert_eq!(retrieval_success_rate(unknown), 0.5);
+    assert_eq!(retrieval_success_rate(reliable), 0.75);
+
+    let mut peers = vec![flaky, unknown, reliable];
+    sort_by_success_rate(&mut peers);
+    assert_eq!(peers, vec![reliable, unknown, flaky]);
+}
+
+#[test]
+fn test_checkpointed_blocks() {
+    let (blocks, _) = build_simple_tree();
+    let (genesis, a1, a2, a3) = (&blocks[0], &blocks[1], &blocks[2], &blocks[3]);
+    let inserter = TreeInserter::default();
+    let commit_cert = inserter.create_qc_for_block(a3, Some(a1.block_info()));
+    let storage = MockStorage::new(Arc::new(MockSharedStorage::new(ValidatorSet::empty())));
+    let chain = vec![a3.block().clone(), a2.block().clone(), a1.block().clone()];
+
+    // first attempt: nothing was retrieved yet, the whole chain is retrieved
+    assert!(BlockStore::checkpointed_blocks(&commit_cert, &storage)
+        .unwrap()
+        .is_empty());
+
+    // the sync is interrupted after persisting the retrieved chain
+    storage.save_tree(chain.clone(), vec![]).unwrap();
+    let checkpoint = SyncCheckpoint {
+        epoch: commit_cert.commit_info().epoch(),
+        highest_block_id: a3.id(),
+    };
+    storage.save_sync_checkpoint(&checkpoint).unwrap();
+
+    // second attempt to the same target: nothing left to retrieve
+    assert_eq!(
+        BlockStore::checkpointed_blocks(&commit_cert, &storage).unwrap(),
+        chain
+    );
+
+    // a different target doesn't reuse the checkpoint
+    let other_cert = inserter.create_qc_for_block(a2, Some(genesis.block_info()));
+    assert!(BlockStore::checkpointed_blocks(&other_cert, &storage)
+        .unwrap()
+        .is_empty());
+
+    // a checkpoint from another epoch is stale
+    storage
+        .save_sync_checkpoint(&SyncCheckpoint {
+            epoch: checkpoint.epoch + 1,
+            ..checkpoint
+        })
+        .unwrap();
+    assert!(BlockStore::checkpointed_blocks(&commit_cert, &storage)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_discard_duplicates() {
+    let (blocks, _) = build_simple_tree();
+    let (a1, a2) = (blocks[1].block().clone(), blocks[2].block().clone());
+    let duplicates = counters::DUPLICATE_RETRIEVAL_COUNT.get();
+
+    // the same block delivered twice is only kept once
+    assert_eq!(
+        discard_duplicates(vec![a2.clone(), a1.clone(), a2.clone()]),
+        vec![a2.clone(), a1.clone()]
+    );
+    // the blocks of an earlier retrieval are kept, they might still need to be inserted
+    assert_eq!(discard_duplicates(vec![a1.clone()]), vec![a1]);
+    assert_eq!(counters::DUPLICATE_RETRIEVAL_COUNT.get(), duplicates + 1);
+}
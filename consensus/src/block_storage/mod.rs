@@ -12,9 +12,14 @@ mod block_store;
 mod block_tree;
 mod pending_votes;
 
-pub use block_store::{sync_manager::BlockRetriever, BlockStore};
+pub use block_store::{
+    sync_manager::{BlockRetriever, RetrievalBackoff},
+    BlockStore,
+};
 use consensus_types::sync_info::SyncInfo;
 pub use pending_votes::PendingVotes;
+#[cfg(test)]
+pub use pending_votes::VoteProgress;
 
 /// Result of the vote processing. The failure case (Verification error) is returned
 /// as the Error part of the result.
@@ -35,6 +40,8 @@ pub enum VoteReceptionResult {
     ErrorAddingVote(VerifyError),
     /// The vote is not for the current round.
     UnexpectedRound(u64, u64),
+    /// The vote is for a past round whose pending votes were already pruned.
+    PrunedRound(u64),
 }
 
 pub trait BlockReader: Send + Sync {
@@ -5,10 +5,12 @@ use crate::block_storage::VoteReceptionResult;
 use consensus_types::{
     common::{Author, Round},
     quorum_cert::QuorumCert,
+    timeout::Timeout,
     timeout_certificate::TimeoutCertificate,
     vote::Vote,
+    vote_data::VoteData,
 };
-use libra_crypto::{hash::CryptoHash, HashValue};
+use libra_crypto::{ed25519::Ed25519Signature, hash::CryptoHash, HashValue};
 use libra_logger::prelude::*;
 use libra_types::{
     ledger_info::LedgerInfoWithSignatures,
@@ -30,6 +32,20 @@ struct LastVoteInfo {
     is_timeout: bool, // true if a vote includes a round signature that can be aggregated to TC
 }
 
+/// Progress of the vote aggregation for a block.
+#[cfg(test)]
+#[derive(Debug, PartialEq)]
+pub struct VoteProgress {
+    /// Voting power of the votes for the block
+    pub voting_power: u64,
+    /// Voting power still missing to form a QuorumCert
+    pub remaining_power: u64,
+    /// Voting power of the timeout signatures for the block's round
+    pub timeout_voting_power: u64,
+    /// Voting power still missing to form a TimeoutCertificate
+    pub timeout_remaining_power: u64,
+}
+
 /// Last pending votes of the authors. Should be cleared upon reconfiguration.
 pub struct PendingVotes {
     /// `li_digest_to_votes` might keep multiple LedgerInfos per proposed block in order
@@ -38,8 +54,8 @@ pub struct PendingVotes {
     /// LedgerInfo digest covers the potential commit ids, as well as the vote information
     /// (including the 3-chain of a voted proposal).
     /// Thus, the structure of `li_digest_to_votes` is as follows:
-    /// HashMap<ledger_info_digest, LedgerInfoWithSignatures>
-    li_digest_to_votes: HashMap<HashValue, LedgerInfoWithSignatures>,
+    /// HashMap<ledger_info_digest, (VoteData, LedgerInfoWithSignatures)>
+    li_digest_to_votes: HashMap<HashValue, (VoteData, LedgerInfoWithSignatures)>,
     /// Tracks all the signatures of the votes for the given round. In case we succeed to
     /// aggregate 2f+1 signatures for the same round a TimeoutCertificate is formed.
     /// Note that QuorumCert has higher priority than TimeoutCertificate (in case 2f+1 votes are
@@ -91,8 +107,11 @@ impl PendingVotes {
         // Note that the digest covers the ledger info information, which is also indirectly
         // covering vote data hash (in its `consensus_data_hash` field).
         let li_digest = vote.ledger_info().hash();
-        let li_with_sig = self.li_digest_to_votes.entry(li_digest).or_insert_with(|| {
-            LedgerInfoWithSignatures::new(vote.ledger_info().clone(), BTreeMap::new())
+        let (_, li_with_sig) = self.li_digest_to_votes.entry(li_digest).or_insert_with(|| {
+            (
+                vote.vote_data().clone(),
+                LedgerInfoWithSignatures::new(vote.ledger_info().clone(), BTreeMap::new()),
+            )
         });
         li_with_sig.add_signature(vote.author(), vote.signature().clone());
 
@@ -111,6 +130,32 @@ impl PendingVotes {
         }
     }
 
+    /// Insert a round signature sent on its own (without a vote) and return the new
+    /// TimeoutCertificate in case it completes one.
+    pub fn insert_timeout(
+        &mut self,
+        timeout: &Timeout,
+        author: Author,
+        signature: Ed25519Signature,
+        validator_verifier: &ValidatorVerifier,
+    ) -> VoteReceptionResult {
+        let tc = self
+            .round_to_tc
+            .entry(timeout.round())
+            .or_insert_with(|| TimeoutCertificate::new(timeout.clone()));
+        tc.add_signature(author, signature);
+        match validator_verifier.check_voting_power(tc.signatures().keys()) {
+            Ok(_) => VoteReceptionResult::NewTimeoutCertificate(Arc::new(tc.clone())),
+            Err(VerifyError::TooLittleVotingPower { voting_power, .. }) => {
+                VoteReceptionResult::VoteAdded(voting_power)
+            }
+            Err(error) => {
+                error!("MUST_FIX: timeout received could not be added: {}", error);
+                VoteReceptionResult::ErrorAddingVote(error)
+            }
+        }
+    }
+
     /// In case a timeout certificate is formed (there are 2f+1 votes in the same round) return the
     /// new TimeoutCertificate, otherwise, return None.
     fn aggregate_tc(
@@ -119,21 +164,51 @@ impl PendingVotes {
         validator_verifier: &ValidatorVerifier,
     ) -> Option<VoteReceptionResult> {
         let timeout_signature = vote.timeout_signature().cloned()?;
-        let timeout = vote.timeout();
-        let tc = self
-            .round_to_tc
-            .entry(timeout.round())
-            .or_insert_with(|| TimeoutCertificate::new(timeout));
-        tc.add_signature(vote.author(), timeout_signature);
-        match validator_verifier.check_voting_power(tc.signatures().keys()) {
-            Ok(_) => Some(VoteReceptionResult::NewTimeoutCertificate(Arc::new(
-                tc.clone(),
-            ))),
-            Err(VerifyError::TooLittleVotingPower { .. }) => None,
+        match self.insert_timeout(
+            &vote.timeout(),
+            vote.author(),
+            timeout_signature,
+            validator_verifier,
+        ) {
+            tc @ VoteReceptionResult::NewTimeoutCertificate(_) => Some(tc),
+            VoteReceptionResult::VoteAdded(_) => None,
             _ => panic!("Unexpected verification error, vote = {}", vote),
         }
     }
 
+    /// Reports the voting power gathered for the given block by the pending votes, and by the
+    /// timeout signatures of its round. Returns None if there are no pending votes for the block.
+    /// In case the votes for the block carry different LedgerInfos, the one with the most voting
+    /// power is reported.
+    #[cfg(test)]
+    pub fn vote_progress(
+        &self,
+        block_id: HashValue,
+        validator_verifier: &ValidatorVerifier,
+    ) -> Option<VoteProgress> {
+        let (round, voting_power_for_block) = self
+            .li_digest_to_votes
+            .values()
+            .filter(|(vote_data, _)| vote_data.proposed().id() == block_id)
+            .map(|(vote_data, li_with_sig)| {
+                (
+                    vote_data.proposed().round(),
+                    voting_power(validator_verifier, li_with_sig.signatures().keys()),
+                )
+            })
+            .max_by_key(|(_, power)| *power)?;
+        let timeout_voting_power = self.round_to_tc.get(&round).map_or(0, |tc| {
+            voting_power(validator_verifier, tc.signatures().keys())
+        });
+        let quorum_voting_power = validator_verifier.quorum_voting_power();
+        Some(VoteProgress {
+            voting_power: voting_power_for_block,
+            remaining_power: quorum_voting_power.saturating_sub(voting_power_for_block),
+            timeout_voting_power,
+            timeout_remaining_power: quorum_voting_power.saturating_sub(timeout_voting_power),
+        })
+    }
+
     /// If this is the first vote from Author, add it to map. If Author has
     /// already voted on same block then return DuplicateVote error. If Author has already voted
     /// on some other result, prune the last vote and insert new one in map.
@@ -168,7 +243,8 @@ impl PendingVotes {
         }
 
         // Prune last pending vote from the pending votes.
-        if let Some(li_pending_votes) = self.li_digest_to_votes.get_mut(&last_voted_info.li_digest)
+        if let Some((_, li_pending_votes)) =
+            self.li_digest_to_votes.get_mut(&last_voted_info.li_digest)
         {
             // Removing signature from last voted block
             li_pending_votes.remove_signature(author);
@@ -202,12 +278,22 @@ impl PendingVotes {
     }
 }
 
+#[cfg(test)]
+fn voting_power<'a>(
+    validator_verifier: &ValidatorVerifier,
+    authors: impl Iterator<Item = &'a Author>,
+) -> u64 {
+    authors
+        .filter_map(|author| validator_verifier.get_voting_power(author))
+        .sum()
+}
+
 impl fmt::Display for PendingVotes {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let (votes, timeout) = (
             self.li_digest_to_votes
                 .iter()
-                .map(|(hash, li)| (hash, li.signatures().keys().collect::<Vec<_>>()))
+                .map(|(hash, (_, li))| (hash, li.signatures().keys().collect::<Vec<_>>()))
                 .collect::<BTreeMap<_, _>>(),
             self.round_to_tc
                 .iter()
@@ -1,7 +1,10 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::block_storage::{pending_votes::PendingVotes, VoteReceptionResult};
+use crate::block_storage::{
+    pending_votes::{PendingVotes, VoteProgress},
+    VoteReceptionResult,
+};
 use consensus_types::{common::Round, vote::Vote, vote_data::VoteData};
 use libra_crypto::HashValue;
 use libra_types::{
@@ -249,3 +252,40 @@ fn test_tc_aggregation_keep_last_only() {
         }
     };
 }
+
+#[test]
+/// Verify that the voting power gathered for a block is reported along with the remaining power
+fn test_vote_progress() {
+    let (signers, validator) = random_validator_verifier(4, Some(3), false);
+    let mut pending_votes = PendingVotes::new();
+
+    let li = random_ledger_info();
+    let vote_data = random_vote_data(1);
+    let block_id = vote_data.proposed().id();
+    assert_eq!(pending_votes.vote_progress(block_id, &validator), None);
+
+    let vote_author_0 = Vote::new(
+        vote_data.clone(),
+        signers[0].author(),
+        li.clone(),
+        &signers[0],
+    );
+    pending_votes.insert_vote(&vote_author_0, &validator);
+    let mut vote_author_1 = Vote::new(vote_data, signers[1].author(), li, &signers[1]);
+    vote_author_1.add_timeout_signature(vote_author_1.timeout().sign(&signers[1]));
+    pending_votes.insert_vote(&vote_author_1, &validator);
+
+    assert_eq!(
+        pending_votes.vote_progress(block_id, &validator),
+        Some(VoteProgress {
+            voting_power: 2,
+            remaining_power: 1,
+            timeout_voting_power: 1,
+            timeout_remaining_power: 2,
+        })
+    );
+    assert_eq!(
+        pending_votes.vote_progress(HashValue::random(), &validator),
+        None
+    );
+}
@@ -5,7 +5,7 @@ use crate::{
     block_storage::{BlockReader, BlockStore},
     counters,
     network::NetworkSender,
-    persistent_liveness_storage::{PersistentLivenessStorage, RecoveryData},
+    persistent_liveness_storage::{PersistentLivenessStorage, RecoveryData, SyncCheckpoint},
     state_replication::StateComputer,
 };
 use anyhow::{bail, format_err};
@@ -16,16 +16,25 @@ use consensus_types::{
     quorum_cert::QuorumCert,
     sync_info::SyncInfo,
 };
+use futures::{
+    stream::{FuturesUnordered, StreamExt},
+    Future,
+};
+use libra_config::config::ConsensusConfig;
+use libra_crypto::HashValue;
 use libra_logger::prelude::*;
-use libra_types::{account_address::AccountAddress, epoch_change::EpochChangeProof};
+use libra_types::epoch_change::EpochChangeProof;
 use mirai_annotations::checked_precondition;
-use rand::{prelude::*, Rng};
+use rand::prelude::*;
 use std::{
     clone::Clone,
+    cmp::Ordering,
+    collections::{HashSet, VecDeque},
     sync::Arc,
     time::{Duration, Instant},
 };
 use termion::color::*;
+use tokio::time::{delay_for, timeout};
 
 #[derive(Debug, PartialEq)]
 /// Whether we need to do block retrieval if we want to insert a Quorum Cert.
@@ -140,6 +149,7 @@ impl BlockStore {
             retrieve_qc = block.quorum_cert().clone();
             pending.push(block);
         }
+        let mut pending = discard_duplicates(pending);
         // insert the qc <- block pair
         while let Some(block) = pending.pop() {
             let block_qc = block.quorum_cert().clone();
@@ -201,9 +211,29 @@ impl BlockStore {
             highest_commit_cert.commit_info(),
         );
 
-        let blocks = retriever
-            .retrieve_block_for_qc(&highest_commit_cert, 3)
-            .await?;
+        // Resume from the blocks retrieved by an interrupted sync, if any
+        let mut blocks = Self::checkpointed_blocks(highest_commit_cert, storage.as_ref())?;
+        if blocks.len() < 3 {
+            storage.save_sync_checkpoint(&SyncCheckpoint {
+                epoch: highest_commit_cert.commit_info().epoch(),
+                highest_block_id: highest_commit_cert.certified_block().id(),
+            })?;
+        } else {
+            debug!("All the blocks to sync to were retrieved before, skipping retrieval");
+        }
+        // Every block is persisted with its QC as soon as it's retrieved, so that a sync
+        // interrupted by a crash resumes from the last one
+        while blocks.len() < 3 {
+            let retrieve_qc = blocks
+                .last()
+                .map_or(highest_commit_cert, |block| block.quorum_cert())
+                .clone();
+            let mut retrieved = retriever.retrieve_block_for_qc(&retrieve_qc, 1).await?;
+            // retrieve_block_for_qc guarantees that retrieved has exactly 1 element
+            let block = retrieved.remove(0);
+            storage.save_tree(vec![block.clone()], vec![retrieve_qc])?;
+            blocks.push(block);
+        }
         assert_eq!(
             blocks.last().expect("should have 3-chain").id(),
             highest_commit_cert.commit_info().id(),
@@ -228,118 +258,353 @@ impl BlockStore {
             .sync_to(highest_commit_cert.ledger_info().clone())
             .await?;
         counters::STATE_SYNC_DURATION_S.observe_duration(pre_sync_instance.elapsed());
+        storage.delete_sync_checkpoint()?;
         let recovery_data = storage
             .start()
             .expect_recovery_data("Failed to construct recovery data after fast forward sync");
 
         Ok(recovery_data)
     }
+
+    /// Returns the blocks of the chain certified by `highest_commit_cert` (starting from the
+    /// highest one) that were retrieved and persisted by an unfinished fast forward sync to the
+    /// same block. The checkpoint is ignored if the target has changed epoch since.
+    pub fn checkpointed_blocks(
+        highest_commit_cert: &QuorumCert,
+        storage: &dyn PersistentLivenessStorage,
+    ) -> anyhow::Result<Vec<Block>> {
+        let mut blocks = vec![];
+        let checkpoint = match storage.retrieve_sync_checkpoint()? {
+            Some(checkpoint) => checkpoint,
+            None => return Ok(blocks),
+        };
+        if checkpoint.epoch != highest_commit_cert.commit_info().epoch()
+            || checkpoint.highest_block_id != highest_commit_cert.certified_block().id()
+        {
+            return Ok(blocks);
+        }
+        let mut block_id = checkpoint.highest_block_id;
+        while blocks.len() < 3 {
+            match storage.retrieve_block(block_id)? {
+                Some(block) => {
+                    block_id = block.parent_id();
+                    blocks.push(block);
+                }
+                None => break,
+            }
+        }
+        Ok(blocks)
+    }
 }
 
 /// BlockRetriever is used internally to retrieve blocks
 pub struct BlockRetriever {
     network: NetworkSender,
     preferred_peer: Author,
+    candidates: Vec<Author>,
+    concurrency: usize,
+    initial_timeout: Duration,
+    backoff: RetrievalBackoff,
+    // Bound of every `retrieve_block_for_qc` call, over all its attempts
+    deadline: Option<Duration>,
 }
 
 impl BlockRetriever {
-    pub fn new(network: NetworkSender, preferred_peer: Author) -> Self {
+    /// Creates a retriever that tries `candidates` in order before falling back to the signers
+    /// of the quorum certificate. Up to `concurrency` requests are kept in flight at once, each
+    /// of them bounded by a per-attempt timeout starting at `initial_timeout`. Peers that fail
+    /// are retried according to `backoff`.
+    pub fn new(
+        network: NetworkSender,
+        candidates: Vec<Author>,
+        concurrency: usize,
+        initial_timeout: Duration,
+        backoff: RetrievalBackoff,
+    ) -> Self {
+        assert!(
+            !candidates.is_empty(),
+            "BlockRetriever requires at least one candidate peer"
+        );
         Self {
             network,
-            preferred_peer,
+            preferred_peer: candidates[0],
+            candidates,
+            concurrency: concurrency.max(1),
+            initial_timeout,
+            backoff,
+            deadline: None,
         }
     }
+
+    /// Bounds every retrieval of a chain of blocks by `deadline`, over all its attempts. Only
+    /// the network requests are bounded, never the state sync or the commits that follow them.
+    pub fn with_deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
     /// Retrieve chain of n blocks for given QC
     ///
     /// Returns Result with Vec that has a guaranteed size of num_blocks
     /// This guarantee is based on BlockRetrievalResponse::verify that ensures that number of
-    /// blocks in response is equal to number of blocks requested.  This method will
-    /// continue until the quorum certificate members all fail to return the missing chain.
+    /// blocks in response is equal to number of blocks requested, or that a truncated response
+    /// makes progress (see `BlockRetrievalStatus::Truncated`).  This method will
+    /// continue until it runs out of attempts to retrieve the missing chain.
     ///
-    /// The first attempt of block retrieval will always be sent to preferred_peer to allow the
-    /// leader to drive quorum certificate creation The other peers from the quorum certificate
-    /// will be randomly tried next.  If all members of the quorum certificate are exhausted, an
-    /// error is returned
+    /// The preferred peer is tried first, to allow the leader to drive quorum certificate
+    /// creation. The other candidates and the peers from the quorum certificate follow, ordered
+    /// by their historical success rate (see `retrieval_success_rate`). See
+    /// `retrieve_with_backoff` for how the failed attempts are retried.
     async fn retrieve_block_for_qc<'a>(
         &'a mut self,
         qc: &'a QuorumCert,
         num_blocks: u64,
     ) -> anyhow::Result<Vec<Block>> {
         let block_id = qc.certified_block().id();
-        let mut peers: Vec<&AccountAddress> = qc.ledger_info().signatures().keys().collect();
-        let mut attempt = 0_u32;
-        loop {
-            if peers.is_empty() {
-                bail!(
-                    "Failed to fetch block {} in {} attempts: no more peers available",
+        let mut signers: Vec<Author> = qc.ledger_info().signatures().keys().cloned().collect();
+        signers.shuffle(&mut thread_rng());
+        let mut peers = retrieval_candidates(&self.candidates, signers);
+        if peers.len() > 2 {
+            sort_by_success_rate(&mut peers[1..]);
+        }
+        let network = self.network.clone();
+        let initial_timeout = self.initial_timeout;
+        let retrieval = retrieve_with_backoff(
+            block_id,
+            peers,
+            self.concurrency,
+            self.backoff,
+            |peer, attempt| {
+                let mut network = network.clone();
+                let timeout = retrieval_timeout(initial_timeout, attempt);
+                async move {
+                    // A peer serving fewer blocks per request than needed truncates the chain,
+                    // the rest of it is requested from the same peer starting where it stopped
+                    let mut blocks: Vec<Block> = vec![];
+                    let mut next_id = block_id;
+                    loop {
+                        let response = network
+                            .request_block(
+                                BlockRetrievalRequest::new(
+                                    next_id,
+                                    num_blocks - blocks.len() as u64,
+                                ),
+                                peer,
+                                timeout,
+                            )
+                            .await?;
+                        match response.status() {
+                            BlockRetrievalStatus::Succeeded => {
+                                blocks.extend(response.blocks().iter().cloned());
+                                return Ok(blocks);
+                            }
+                            BlockRetrievalStatus::Truncated => {
+                                blocks.extend(response.blocks().iter().cloned());
+                                next_id = blocks
+                                    .last()
+                                    .expect("truncated responses are not empty")
+                                    .parent_id();
+                            }
+                            status => return Err(format_err!("{:?}", status)),
+                        }
+                    }
+                }
+            },
+        );
+        let deadline = match self.deadline {
+            Some(deadline) => deadline,
+            None => return retrieval.await,
+        };
+        match timeout(deadline, retrieval).await {
+            Ok(result) => result,
+            Err(_) => {
+                counters::SYNC_UP_TIMEOUT_COUNT.inc();
+                warn!(
+                    "Retrieval of block {} from peer {} timed out after {:?}",
                     block_id,
-                    attempt
+                    self.preferred_peer.short_str(),
+                    deadline
                 );
+                bail!(
+                    "Retrieval of block {} from peer {} timed out after {:?}",
+                    block_id,
+                    self.preferred_peer.short_str(),
+                    deadline
+                )
             }
-            let peer = self.pick_peer(attempt, &mut peers);
-            attempt += 1;
+        }
+    }
+}
+
+/// Backoff of the block retrieval attempts against a peer that failed before
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RetrievalBackoff {
+    pub initial: Duration,
+    pub multiplier: f64,
+    pub max: Duration,
+    // Max number of attempts over all the peers
+    pub max_attempts: u32,
+}
 
+impl RetrievalBackoff {
+    pub fn from_config(config: &ConsensusConfig) -> Self {
+        Self {
+            initial: Duration::from_millis(config.block_retrieval_backoff_initial_ms),
+            multiplier: config.block_retrieval_backoff_multiplier,
+            max: Duration::from_millis(config.block_retrieval_backoff_max_ms),
+            max_attempts: config.block_retrieval_max_attempts,
+        }
+    }
+
+    /// Returns the delay before the next attempt against a peer that failed `failures` times,
+    /// there's no delay for a fresh peer.
+    pub fn delay(&self, failures: u32) -> Duration {
+        if failures == 0 {
+            return Duration::from_secs(0);
+        }
+        let delay = self.initial.as_nanos() as f64 * self.multiplier.powi(failures as i32 - 1);
+        if delay < self.max.as_nanos() as f64 {
+            Duration::from_nanos(delay as u64)
+        } else {
+            self.max
+        }
+    }
+}
+
+/// Requests the chain starting at `block_id` from the given peers until one of them succeeds.
+/// Up to `concurrency` requests are in flight at any time and the first successful response
+/// wins. A peer that fails is queued again behind the other peers and is retried after the
+/// backoff delay of its number of failures. Gives up after `backoff.max_attempts` attempts.
+pub async fn retrieve_with_backoff<F, Fut>(
+    block_id: HashValue,
+    peers: Vec<Author>,
+    concurrency: usize,
+    backoff: RetrievalBackoff,
+    mut request: F,
+) -> anyhow::Result<Vec<Block>>
+where
+    F: FnMut(Author, u32) -> Fut,
+    Fut: Future<Output = anyhow::Result<Vec<Block>>>,
+{
+    let mut peers: VecDeque<(Author, u32)> = peers.into_iter().map(|peer| (peer, 0)).collect();
+    let mut in_flight = FuturesUnordered::new();
+    let mut attempt = 0_u32;
+    loop {
+        while in_flight.len() < concurrency.max(1) && attempt < backoff.max_attempts {
+            let (peer, failures) = match peers.pop_front() {
+                Some(peer) => peer,
+                None => break,
+            };
+            attempt += 1;
+            let delay = backoff.delay(failures);
             debug!(
-                "Fetching {} from {}, attempt {}",
+                "Fetching {} from {}, attempt {}, delayed by {:?}",
                 block_id,
                 peer.short_str(),
-                attempt
+                attempt,
+                delay
             );
-            let response = self
-                .network
-                .request_block(
-                    BlockRetrievalRequest::new(block_id, num_blocks),
-                    peer,
-                    retrieval_timeout(attempt),
-                )
-                .await;
-            match response.and_then(|result| {
-                if result.status() == BlockRetrievalStatus::Succeeded {
-                    Ok(result.blocks().clone())
-                } else {
-                    Err(format_err!("{:?}", result.status()))
+            let response = request(peer, attempt);
+            in_flight.push(async move {
+                if failures > 0 {
+                    delay_for(delay).await;
                 }
-            }) {
-                result @ Ok(_) => return result,
-                Err(e) => warn!(
-                    "Failed to fetch block {} from {}: {:?}, trying another peer",
+                (peer, failures, response.await)
+            });
+        }
+        let (peer, failures, response) = match in_flight.next().await {
+            Some(result) => result,
+            None => bail!(
+                "Failed to fetch block {}: gave up after {} attempts",
+                block_id,
+                attempt
+            ),
+        };
+        record_retrieval_result(peer, response.is_ok());
+        match response {
+            result @ Ok(_) => return result,
+            Err(e) => {
+                warn!(
+                    "Failed to fetch block {} from {}: {:?}, trying again",
                     block_id,
                     peer.short_str(),
                     e,
-                ),
+                );
+                peers.push_back((peer, failures + 1));
             }
         }
     }
+}
 
-    fn pick_peer(&self, attempt: u32, peers: &mut Vec<&AccountAddress>) -> AccountAddress {
-        assert!(!peers.is_empty(), "pick_peer on empty peer list");
+/// Returns the order in which peers are asked for blocks: the explicit candidates first (in the
+/// given order), followed by the remaining signers that are not candidates already.
+pub fn retrieval_candidates(candidates: &[Author], signers: Vec<Author>) -> Vec<Author> {
+    let mut peers = vec![];
+    for peer in candidates.iter().cloned().chain(signers) {
+        if !peers.contains(&peer) {
+            peers.push(peer);
+        }
+    }
+    peers
+}
 
-        if attempt == 0 {
-            // remove preferred_peer if its in list of peers
-            // (strictly speaking it is not required to be there)
-            for i in 0..peers.len() {
-                if *peers[i] == self.preferred_peer {
-                    peers.remove(i);
-                    break;
-                }
+/// Drops the blocks that appear more than once in `blocks`, e.g. because a peer answered the
+/// continuation of a truncated request with blocks it already sent. Only the exact duplicates are
+/// dropped: the blocks that aren't in the block store yet are always inserted.
+pub fn discard_duplicates(blocks: Vec<Block>) -> Vec<Block> {
+    let mut retrieved = HashSet::new();
+    blocks
+        .into_iter()
+        .filter(|block| {
+            let new = retrieved.insert(block.id());
+            if !new {
+                counters::DUPLICATE_RETRIEVAL_COUNT.inc();
             }
-            return self.preferred_peer;
-        }
+            new
+        })
+        .collect()
+}
+
+fn record_retrieval_result(peer: Author, success: bool) {
+    let result = if success { "success" } else { "failure" };
+    counters::BLOCK_RETRIEVAL_PEER_RESULTS
+        .with_label_values(&[&peer.short_str(), result])
+        .inc();
+}
+
+/// Returns the ratio of the successful block retrievals from the peer so far. The ratio is
+/// smoothed so that a peer with no history ranks between the reliable and the unreliable ones.
+pub fn retrieval_success_rate(peer: Author) -> f64 {
+    let label = peer.short_str();
+    let successes = counters::BLOCK_RETRIEVAL_PEER_RESULTS
+        .with_label_values(&[&label, "success"])
+        .get() as f64;
+    let failures = counters::BLOCK_RETRIEVAL_PEER_RESULTS
+        .with_label_values(&[&label, "failure"])
+        .get() as f64;
+    (successes + 1.0) / (successes + failures + 2.0)
+}
 
-        let peer_idx = thread_rng().gen_range(0, peers.len());
-        *peers.remove(peer_idx)
+/// Orders the peers by decreasing historical success rate, the order of the peers with the same
+/// rate is kept.
+pub fn sort_by_success_rate(peers: &mut [Author]) {
+    let mut rated: Vec<_> = peers
+        .iter()
+        .map(|peer| (retrieval_success_rate(*peer), *peer))
+        .collect();
+    rated.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(Ordering::Equal));
+    for (peer, (_, rated_peer)) in peers.iter_mut().zip(rated) {
+        *peer = rated_peer;
     }
 }
 
-// Max timeout is 16s=RETRIEVAL_INITIAL_TIMEOUT*(2^RETRIEVAL_MAX_EXP)
-const RETRIEVAL_INITIAL_TIMEOUT: Duration = Duration::from_millis(200);
+// Max timeout is initial_timeout*(2^RETRIEVAL_MAX_EXP)
 const RETRIEVAL_MAX_EXP: u32 = 4;
 
 /// Returns exponentially increasing timeout with
-/// limit of RETRIEVAL_INITIAL_TIMEOUT*(2^RETRIEVAL_MAX_EXP)
-#[allow(clippy::trivially_copy_pass_by_ref)]
-fn retrieval_timeout(attempt: u32) -> Duration {
+/// limit of initial_timeout*(2^RETRIEVAL_MAX_EXP)
+fn retrieval_timeout(initial_timeout: Duration, attempt: u32) -> Duration {
     assert!(attempt > 0, "retrieval_timeout attempt can't be 0");
     let exp = RETRIEVAL_MAX_EXP.min(attempt - 1); // [0..RETRIEVAL_MAX_EXP]
-    RETRIEVAL_INITIAL_TIMEOUT * 2_u32.pow(exp)
+    initial_timeout * 2_u32.pow(exp)
 }
@@ -7,6 +7,7 @@ use crate::{
     network::NetworkTask,
     network_interface::{ConsensusNetworkEvents, ConsensusNetworkSender},
     persistent_liveness_storage::StorageWriteProxy,
+    round_manager::RoundManagerHooks,
     state_computer::ExecutionProxy,
     txn_manager::MempoolProxy,
     util::time_service::ClockTimeService,
@@ -19,7 +20,7 @@ use libra_logger::prelude::*;
 use libra_mempool::ConsensusRequest;
 use libra_types::on_chain_config::OnChainConfigPayload;
 use state_synchronizer::StateSyncClient;
-use std::{boxed::Box, sync::Arc};
+use std::{boxed::Box, sync::Arc, time::Duration};
 use storage_interface::DbReader;
 use tokio::runtime::{self, Runtime};
 
@@ -32,6 +33,7 @@ pub fn start_consensus(
     consensus_to_mempool_sender: mpsc::Sender<ConsensusRequest>,
     libra_db: Arc<dyn DbReader>,
     reconfig_events: libra_channel::Receiver<(), OnChainConfigPayload>,
+    round_manager_hooks: RoundManagerHooks,
 ) -> Runtime {
     let runtime = runtime::Builder::new()
         .thread_name("consensus-")
@@ -40,7 +42,10 @@ pub fn start_consensus(
         .build()
         .expect("Failed to create Tokio runtime!");
     let storage = Arc::new(StorageWriteProxy::new(node_config, libra_db));
-    let txn_manager = Box::new(MempoolProxy::new(consensus_to_mempool_sender));
+    let txn_manager = Box::new(MempoolProxy::new(
+        consensus_to_mempool_sender,
+        Duration::from_millis(node_config.consensus.txn_commit_coalescing_window_ms),
+    ));
     let execution_correctness_manager = ExecutionCorrectnessManager::new(node_config);
     let state_computer = Arc::new(ExecutionProxy::new(
         execution_correctness_manager.client(),
@@ -49,6 +54,8 @@ pub fn start_consensus(
     let time_service = Arc::new(ClockTimeService::new(runtime.handle().clone()));
 
     let (timeout_sender, timeout_receiver) = channel::new(1_024, &counters::PENDING_ROUND_TIMEOUTS);
+    let (backup_proposal_sender, backup_proposal_receiver) =
+        channel::new(1_024, &counters::PENDING_BACKUP_PROPOSAL_TIMEOUTS);
     let (self_sender, self_receiver) = channel::new(1_024, &counters::PENDING_SELF_MESSAGES);
 
     let epoch_mgr = EpochManager::new(
@@ -57,15 +64,22 @@ pub fn start_consensus(
         self_sender,
         network_sender,
         timeout_sender,
+        backup_proposal_sender,
         txn_manager,
         state_computer,
         storage,
+        round_manager_hooks,
     );
 
     let (network_task, network_receiver) = NetworkTask::new(network_events, self_receiver);
 
     runtime.spawn(network_task.start());
-    runtime.spawn(epoch_mgr.start(timeout_receiver, network_receiver, reconfig_events));
+    runtime.spawn(epoch_mgr.start(
+        timeout_receiver,
+        backup_proposal_receiver,
+        network_receiver,
+        reconfig_events,
+    ));
 
     debug!("Consensus started.");
     runtime
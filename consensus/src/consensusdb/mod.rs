@@ -158,6 +158,32 @@ impl ConsensusDB {
         self.commit(batch)
     }
 
+    pub fn save_sync_checkpoint(&self, checkpoint: Vec<u8>) -> Result<()> {
+        let mut batch = SchemaBatch::new();
+        batch.put::<SingleEntrySchema>(&SingleEntryKey::SyncCheckpoint, &checkpoint)?;
+        self.commit(batch)
+    }
+
+    /// Get serialized fast forward sync checkpoint (if available)
+    pub fn get_sync_checkpoint(&self) -> Result<Option<Vec<u8>>> {
+        self.db
+            .get::<SingleEntrySchema>(&SingleEntryKey::SyncCheckpoint)
+    }
+
+    pub fn delete_sync_checkpoint(&self) -> Result<()> {
+        let mut batch = SchemaBatch::new();
+        batch.delete::<SingleEntrySchema>(&SingleEntryKey::SyncCheckpoint)?;
+        self.commit(batch)
+    }
+
+    /// Get a single consensus block.
+    pub fn get_block(&self, block_id: HashValue) -> Result<Option<Block>> {
+        Ok(self
+            .db
+            .get::<BlockSchema>(&block_id)?
+            .map(|block| block.borrow_into_block().clone()))
+    }
+
     /// Get all consensus blocks.
     fn get_blocks(&self) -> Result<HashMap<HashValue, Block>> {
         let mut iter = self.db.iter::<BlockSchema>(ReadOptions::default())?;
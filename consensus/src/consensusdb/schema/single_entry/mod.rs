@@ -35,6 +35,8 @@ pub enum SingleEntryKey {
     HighestTimeoutCertificate = 0,
     // Used to store the last vote
     LastVoteMsg = 1,
+    // Used to store the progress of an unfinished fast forward sync
+    SyncCheckpoint = 2,
 }
 
 impl KeyCodec<SingleEntrySchema> for SingleEntryKey {
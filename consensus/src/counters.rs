@@ -2,8 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use libra_metrics::{
-    register_histogram, register_int_counter, register_int_counter_vec, register_int_gauge,
-    DurationHistogram, Histogram, IntCounter, IntCounterVec, IntGauge,
+    register_histogram, register_histogram_vec, register_int_counter, register_int_counter_vec,
+    register_int_gauge, DurationHistogram, Histogram, HistogramVec, IntCounter, IntCounterVec,
+    IntGauge,
 };
 use once_cell::sync::Lazy;
 
@@ -75,6 +76,15 @@ pub static COMMITTED_BLOCKS_COUNT: Lazy<IntCounter> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Count of the commit notifications dropped because the subscriber was full or gone.
+pub static COMMIT_NOTIFICATIONS_DROPPED_COUNT: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "libra_consensus_commit_notifications_dropped_count",
+        "Count of the commit notifications dropped because the subscriber was full or gone."
+    )
+    .unwrap()
+});
+
 /// Count of the committed transactions since last restart.
 pub static COMMITTED_TXNS_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
     register_int_counter_vec!(
@@ -117,6 +127,17 @@ pub static CONSENSUS_CHANNEL_MSGS: Lazy<IntCounterVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Count of the block retrievals per target peer and result (success or failure, the timeouts
+/// being counted as failures)
+pub static BLOCK_RETRIEVAL_PEER_RESULTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "libra_consensus_block_retrieval_peer_results_count",
+        "Count of the block retrievals per target peer and result",
+        &["peer", "result"]
+    )
+    .unwrap()
+});
+
 /// Counters(queued,dequeued,dropped) related to block retrieval channel
 pub static BLOCK_RETRIEVAL_CHANNEL_MSGS: Lazy<IntCounterVec> = Lazy::new(|| {
     register_int_counter_vec!(
@@ -137,6 +158,24 @@ pub static PROPOSALS_COUNT: Lazy<IntCounter> = Lazy::new(|| {
     register_int_counter!("libra_consensus_proposals_count", "Count of the block proposals sent by this validator since last restart (both primary and secondary)").unwrap()
 });
 
+/// Count of the proposals a validator didn't vote for because of its local vote policy
+pub static VOTES_ABSTAINED_COUNT: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "libra_consensus_votes_abstained_count",
+        "Count of the proposals a validator didn't vote for because of its local vote policy"
+    )
+    .unwrap()
+});
+
+/// Count the number of votes for nil blocks received since last restart.
+pub static NIL_VOTE_RECEIVED_COUNT: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "libra_consensus_nil_vote_received_count",
+        "Count the number of votes for nil blocks received since last restart."
+    )
+    .unwrap()
+});
+
 /// Count the number of times a validator voted for a nil block since last restart.
 pub static VOTE_NIL_COUNT: Lazy<IntCounter> = Lazy::new(|| {
     register_int_counter!(
@@ -205,6 +244,26 @@ pub static BLOCK_RETRIEVAL_COUNT: Lazy<IntCounter> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Count of the proposals rejected because their author isn't a valid proposer for their round,
+/// labeled by whether the proposal round was the current one (current_round or future_round)
+pub static INVALID_PROPOSER_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "libra_consensus_invalid_proposer_count",
+        "Count of the proposals rejected because their author isn't a valid proposer for their round",
+        &["round"]
+    )
+    .unwrap()
+});
+
+/// Count of the retrieved blocks discarded because they were delivered more than once
+pub static DUPLICATE_RETRIEVAL_COUNT: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "libra_consensus_duplicate_retrieval_count",
+        "Count of the retrieved blocks discarded because they were delivered more than once"
+    )
+    .unwrap()
+});
+
 /// Histogram of block retrieval duration.
 pub static BLOCK_RETRIEVAL_DURATION_S: Lazy<DurationHistogram> = Lazy::new(|| {
     DurationHistogram::new(
@@ -227,6 +286,33 @@ pub static STATE_SYNC_DURATION_S: Lazy<DurationHistogram> = Lazy::new(|| {
     )
 });
 
+/// Count of the proposers caught sending different proposals for the same round.
+pub static PROPOSER_EQUIVOCATION_COUNT: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "libra_consensus_proposer_equivocation_count",
+        "Count of the proposers caught sending different proposals for the same round."
+    )
+    .unwrap()
+});
+
+/// Counts the number of times a block retrieval of a sync up hasn't completed before its deadline.
+pub static SYNC_UP_TIMEOUT_COUNT: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "libra_consensus_sync_up_timeout_count",
+        "Counts the number of times a block retrieval of a sync up hasn't completed before its deadline."
+    )
+    .unwrap()
+});
+
+/// Counts the number of sync info messages to stale peers held back as one was sent recently.
+pub static SYNC_INFO_MSGS_SUPPRESSED_COUNT: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "libra_consensus_sync_info_msg_suppressed_count",
+        "Counts the number of sync info messages to stale peers held back as one was sent recently."
+    )
+    .unwrap()
+});
+
 /// Counts the number of times the sync info message has been set since last restart.
 pub static SYNC_INFO_MSGS_SENT_COUNT: Lazy<IntCounter> = Lazy::new(|| {
     register_int_counter!(
@@ -295,6 +381,82 @@ pub static BLOCK_EXECUTION_DURATION_S: Lazy<DurationHistogram> = Lazy::new(|| {
     )
 });
 
+/// Count of the block retrieval requests rejected because the requester exceeded its rate limit
+pub static BLOCK_RETRIEVAL_RATE_LIMITED_COUNT: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "libra_consensus_block_retrieval_rate_limited_count",
+        "Count of the block retrieval requests rejected because the requester exceeded its rate limit"
+    )
+    .unwrap()
+});
+
+/// Count of the blocks served to block retrieval requests from the cache of recently served blocks
+pub static BLOCK_RETRIEVAL_CACHE_HIT_COUNT: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "libra_consensus_block_retrieval_cache_hit_count",
+        "Count of the blocks served to block retrieval requests from the cache of recently served blocks"
+    )
+    .unwrap()
+});
+
+/// Count of the future round proposals dropped because the buffer is full
+pub static FUTURE_PROPOSALS_DROPPED_COUNT: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "libra_consensus_future_proposals_dropped_count",
+        "Count of the future round proposals dropped because the buffer is full"
+    )
+    .unwrap()
+});
+
+/// Count of the proposals limited in size because of too many uncommitted blocks
+pub static BACKPRESSURE_PROPOSALS_COUNT: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "libra_consensus_backpressure_proposals_count",
+        "Count of the proposals limited in size because of too many uncommitted blocks"
+    )
+    .unwrap()
+});
+
+/// Count of the proposals whose payload was cut down to fit the max proposal size
+pub static OVERSIZED_PROPOSALS_COUNT: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "libra_consensus_oversized_proposals_count",
+        "Count of the proposals whose payload was cut down to fit the max proposal size"
+    )
+    .unwrap()
+});
+
+/// Count of the backup proposals processed because the proposal of the valid proposer was missing
+pub static BACKUP_PROPOSALS_COUNT: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "libra_consensus_backup_proposals_count",
+        "Count of the backup proposals processed because the proposal of the valid proposer was missing"
+    )
+    .unwrap()
+});
+
+/// Histogram of the time it takes to execute a block before voting on it, labeled by the block
+/// type (nil or proposal).
+pub static BLOCK_EXECUTION_S: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "libra_consensus_block_execution_s",
+        "Histogram of the time it takes to execute a block before voting on it",
+        &["block_type"]
+    )
+    .unwrap()
+});
+
+/// Histogram of the time it takes to notify the transaction manager about an executed block,
+/// labeled by the block type (nil or proposal).
+pub static TXN_MANAGER_COMMIT_S: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "libra_consensus_txn_manager_commit_s",
+        "Histogram of the time it takes to notify the transaction manager about an executed block",
+        &["block_type"]
+    )
+    .unwrap()
+});
+
 // TODO Consider reintroducing this counter
 // pub static UNWRAPPED_PROPOSAL_SIZE_BYTES: Lazy<Histogram> = Lazy::new(|| {
 //     register_histogram!(
@@ -412,6 +574,15 @@ pub static PENDING_SELF_MESSAGES: Lazy<IntGauge> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Count of the pending outbound backup proposal timeouts
+pub static PENDING_BACKUP_PROPOSAL_TIMEOUTS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "libra_consensus_pending_backup_proposal_timeouts",
+        "Count of the pending outbound backup proposal timeouts"
+    )
+    .unwrap()
+});
+
 /// Count of the pending outbound round timeouts
 pub static PENDING_ROUND_TIMEOUTS: Lazy<IntGauge> = Lazy::new(|| {
     register_int_gauge!(
@@ -10,11 +10,14 @@ use crate::{
         proposer_election::ProposerElection,
         rotating_proposer_election::{choose_leader, RotatingProposer},
         round_state::{ExponentialTimeInterval, RoundState},
+        weighted_round_proposer_election::WeightedRoundProposerElection,
     },
     network::{IncomingBlockRetrievalRequest, NetworkReceivers, NetworkSender},
     network_interface::{ConsensusMsg, ConsensusNetworkSender},
     persistent_liveness_storage::{LedgerRecoveryData, PersistentLivenessStorage, RecoveryData},
-    round_manager::{RecoveryManager, RoundManager, UnverifiedEvent, VerifiedEvent},
+    round_manager::{
+        RecoveryManager, RoundManager, RoundManagerHooks, UnverifiedEvent, VerifiedEvent,
+    },
     state_replication::{StateComputer, TxnManager},
     util::time_service::TimeService,
 };
@@ -75,10 +78,12 @@ pub struct EpochManager {
     self_sender: channel::Sender<anyhow::Result<Event<ConsensusMsg>>>,
     network_sender: ConsensusNetworkSender,
     timeout_sender: channel::Sender<Round>,
+    backup_proposal_sender: channel::Sender<Round>,
     txn_manager: Box<dyn TxnManager>,
     state_computer: Arc<dyn StateComputer>,
     storage: Arc<dyn PersistentLivenessStorage>,
     safety_rules_manager: SafetyRulesManager,
+    round_manager_hooks: RoundManagerHooks,
     processor: Option<RoundProcessor>,
 }
 
@@ -89,9 +94,11 @@ impl EpochManager {
         self_sender: channel::Sender<anyhow::Result<Event<ConsensusMsg>>>,
         network_sender: ConsensusNetworkSender,
         timeout_sender: channel::Sender<Round>,
+        backup_proposal_sender: channel::Sender<Round>,
         txn_manager: Box<dyn TxnManager>,
         state_computer: Arc<dyn StateComputer>,
         storage: Arc<dyn PersistentLivenessStorage>,
+        round_manager_hooks: RoundManagerHooks,
     ) -> Self {
         let author = config::peer_id(node_config.validator_network.as_ref().unwrap());
         let config = node_config.consensus.clone();
@@ -103,10 +110,12 @@ impl EpochManager {
             self_sender,
             network_sender,
             timeout_sender,
+            backup_proposal_sender,
             txn_manager,
             state_computer,
             storage,
             safety_rules_manager,
+            round_manager_hooks,
             processor: None,
         }
     }
@@ -139,6 +148,8 @@ impl EpochManager {
             6,
         ));
         RoundState::new(time_interval, time_service, timeout_sender)
+            .with_timeout_jitter(self.author, self.config.round_timeout_jitter_ratio)
+            .with_pending_votes_window(self.config.pending_votes_round_window)
     }
 
     /// Create a proposer election handler based on proposers
@@ -174,6 +185,9 @@ impl EpochManager {
                 ));
                 Box::new(LeaderReputation::new(proposers, backend, heuristic))
             }
+            ConsensusProposerType::WeightedRound => {
+                Box::new(WeightedRoundProposerElection::new(&epoch_state.verifier))
+            }
         }
     }
 
@@ -251,7 +265,9 @@ impl EpochManager {
 
     async fn start_round_manager(&mut self, recovery_data: RecoveryData, epoch_state: EpochState) {
         // Release the previous RoundManager, especially the SafetyRule client
-        self.processor = None;
+        if let Some(RoundProcessor::Normal(mut processor)) = self.processor.take() {
+            processor.shutdown().await;
+        }
         counters::EPOCH.set(epoch_state.epoch as i64);
         counters::CURRENT_EPOCH_VALIDATORS.set(epoch_state.verifier.len() as i64);
         counters::CURRENT_EPOCH_QUORUM_SIZE.set(epoch_state.verifier.quorum_voting_power() as i64);
@@ -321,6 +337,9 @@ impl EpochManager {
             self.txn_manager.clone(),
             self.storage.clone(),
             self.time_service.clone(),
+            self.backup_proposal_sender.clone(),
+            self.config.clone(),
+            self.round_manager_hooks.clone(),
         );
         processor.start(last_vote).await;
         self.processor = Some(RoundProcessor::Normal(processor));
@@ -348,6 +367,7 @@ impl EpochManager {
             self.storage.clone(),
             self.state_computer.clone(),
             ledger_recovery_data.commit_round(),
+            self.config.clone(),
         )));
         info!("SyncProcessor started");
     }
@@ -378,9 +398,15 @@ impl EpochManager {
         consensus_msg: ConsensusMsg,
     ) -> anyhow::Result<()> {
         if let Some(event) = self.process_epoch(peer_id, consensus_msg).await? {
-            let verified_event = event
-                .verify(&self.epoch_state().verifier)
-                .context("[EpochManager] Verify event")?;
+            let verified_event = match event.verify(&self.epoch_state().verifier) {
+                Ok(verified_event) => verified_event,
+                Err(e) => {
+                    if let Some(RoundProcessor::Normal(p)) = self.processor.as_ref() {
+                        p.report_invalid_peer(peer_id, "invalid consensus message");
+                    }
+                    return Err(e.context("[EpochManager] Verify event"));
+                }
+            };
             self.process_event(peer_id, verified_event).await?;
         }
         Ok(())
@@ -392,7 +418,10 @@ impl EpochManager {
         msg: ConsensusMsg,
     ) -> anyhow::Result<Option<UnverifiedEvent>> {
         match msg {
-            ConsensusMsg::ProposalMsg(_) | ConsensusMsg::SyncInfo(_) | ConsensusMsg::VoteMsg(_) => {
+            ConsensusMsg::ProposalMsg(_)
+            | ConsensusMsg::SyncInfo(_)
+            | ConsensusMsg::VoteMsg(_)
+            | ConsensusMsg::TimeoutMsg(_) => {
                 let event: UnverifiedEvent = msg.into();
                 if event.epoch() == self.epoch() {
                     return Ok(Some(event));
@@ -432,6 +461,7 @@ impl EpochManager {
                 let recovery_data = match event {
                     VerifiedEvent::ProposalMsg(proposal) => p.process_proposal_msg(*proposal).await,
                     VerifiedEvent::VoteMsg(vote) => p.process_vote(*vote).await,
+                    VerifiedEvent::TimeoutMsg(timeout) => p.process_timeout_msg(*timeout).await,
                     _ => Err(anyhow!("Unexpected VerifiedEvent during startup")),
                 }?;
                 let epoch_state = p.epoch_state().clone();
@@ -439,13 +469,19 @@ impl EpochManager {
                 self.start_round_manager(recovery_data, epoch_state).await;
                 Ok(())
             }
-            RoundProcessor::Normal(p) => match event {
-                VerifiedEvent::ProposalMsg(proposal) => p.process_proposal_msg(*proposal).await,
-                VerifiedEvent::VoteMsg(vote) => p.process_vote(*vote).await,
-                VerifiedEvent::SyncInfo(sync_info) => {
-                    p.process_sync_info_msg(*sync_info, peer_id).await
-                }
-            },
+            RoundProcessor::Normal(p) => {
+                let result = match event {
+                    VerifiedEvent::ProposalMsg(proposal) => p.process_proposal_msg(*proposal).await,
+                    VerifiedEvent::VoteMsg(vote) => p.process_vote(*vote).await,
+                    VerifiedEvent::SyncInfo(sync_info) => {
+                        p.process_sync_info_msg(*sync_info, peer_id).await
+                    }
+                    VerifiedEvent::TimeoutMsg(timeout) => p.process_timeout_msg(*timeout).await,
+                };
+                // The certificates carried by the event might have advanced the round
+                p.process_future_proposals().await;
+                result
+            }
         }
     }
 
@@ -472,9 +508,17 @@ impl EpochManager {
         }
     }
 
+    pub async fn process_backup_proposal_timeout(&mut self, round: u64) -> anyhow::Result<()> {
+        match self.processor_mut() {
+            RoundProcessor::Normal(p) => p.process_backup_proposal_timeout(round).await,
+            _ => unreachable!("RoundManager not started yet"),
+        }
+    }
+
     pub async fn start(
         mut self,
         mut round_timeout_sender_rx: channel::Receiver<Round>,
+        mut backup_proposal_timeout_rx: channel::Receiver<Round>,
         mut network_receivers: NetworkReceivers,
         mut reconfig_events: libra_channel::Receiver<(), OnChainConfigPayload>,
     ) {
@@ -503,6 +547,10 @@ impl EpochManager {
                     idle_duration = pre_select_instant.elapsed();
                     self.process_local_timeout(round).await
                 }
+                round = backup_proposal_timeout_rx.select_next_some() => {
+                    idle_duration = pre_select_instant.elapsed();
+                    self.process_backup_proposal_timeout(round).await
+                }
             };
             if let Err(e) = result {
                 error!("{:?}", e);
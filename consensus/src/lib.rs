@@ -38,4 +38,7 @@ pub mod network_interface;
 
 #[cfg(feature = "fuzzing")]
 pub use round_manager::round_manager_fuzzing;
+pub use round_manager::{
+    CertObserver, CommittedBlockInfo, PayloadFilter, PeerScoreSink, RoundManagerHooks, VotePolicy,
+};
 pub use util::config_subscription::gen_consensus_reconfig_subscription;
@@ -6,6 +6,7 @@ pub(crate) mod proposal_generator;
 pub(crate) mod proposer_election;
 pub(crate) mod rotating_proposer_election;
 pub(crate) mod round_state;
+pub(crate) mod weighted_round_proposer_election;
 
 #[cfg(test)]
 mod leader_reputation_test;
@@ -13,3 +14,5 @@ mod leader_reputation_test;
 mod rotating_proposer_test;
 #[cfg(test)]
 mod round_state_test;
+#[cfg(test)]
+mod weighted_round_proposer_test;
@@ -16,6 +16,7 @@ use consensus_types::{
 };
 use libra_logger::prelude::*;
 use std::{
+    cmp::min,
     sync::{Arc, Mutex},
     time::{Duration, Instant},
 };
@@ -104,6 +105,18 @@ impl ProposalGenerator {
         &mut self,
         round: Round,
         round_deadline: Instant,
+    ) -> anyhow::Result<BlockData> {
+        self.generate_proposal_with_max_block_size(round, round_deadline, self.max_block_size)
+            .await
+    }
+
+    /// Same as `generate_proposal`, but pulls at most `max_block_size` transactions (still capped
+    /// by the configured max block size). Used to propose smaller blocks under backpressure.
+    pub async fn generate_proposal_with_max_block_size(
+        &mut self,
+        round: Round,
+        round_deadline: Instant,
+        max_block_size: u64,
     ) -> anyhow::Result<BlockData> {
         {
             let mut last_round_generated = self.last_round_generated.lock().unwrap();
@@ -114,6 +127,30 @@ impl ProposalGenerator {
             }
         }
 
+        self.generate_block_data(round, round_deadline, max_block_size)
+            .await
+    }
+
+    /// Generates the block data that would be proposed for the given round without recording the
+    /// round as proposed, so it can be called repeatedly for the same round. The result must not
+    /// be signed or broadcast.
+    #[cfg(test)]
+    pub async fn preview_proposal(
+        &mut self,
+        round: Round,
+        round_deadline: Instant,
+        max_block_size: u64,
+    ) -> anyhow::Result<BlockData> {
+        self.generate_block_data(round, round_deadline, max_block_size)
+            .await
+    }
+
+    async fn generate_block_data(
+        &mut self,
+        round: Round,
+        round_deadline: Instant,
+        max_block_size: u64,
+    ) -> anyhow::Result<BlockData> {
         let hqc = self.ensure_highest_quorum_cert(round)?;
 
         if hqc.certified_block().has_reconfiguration() {
@@ -205,7 +242,7 @@ impl ProposalGenerator {
 
         let txns = self
             .txn_manager
-            .pull_txns(self.max_block_size, exclude_payload)
+            .pull_txns(min(max_block_size, self.max_block_size), exclude_payload)
             .await
             .context("Fail to retrieve txn")?;
 
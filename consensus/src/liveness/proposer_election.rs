@@ -21,12 +21,31 @@ pub trait ProposerElection {
     /// used by e.g., voters for choosing the destinations for sending their votes to).
     fn get_valid_proposer(&self, round: Round) -> Author;
 
+    /// Return the backup proposer for a given round (if any): its proposal is voted for in case
+    /// the proposal of the valid proposer doesn't show up shortly.
+    fn get_backup_proposer(&self, _round: Round) -> Option<Author> {
+        None
+    }
+
+    /// Return if a given author is either the valid or the backup proposer of the round.
+    fn is_candidate_proposer(&self, author: Author, round: Round) -> bool {
+        self.is_valid_proposer(author, round) || self.get_backup_proposer(round) == Some(author)
+    }
+
     /// Return if a given proposed block is valid.
     fn is_valid_proposal(&self, block: &Block) -> bool {
         block.author().map_or(false, |author| {
             self.is_valid_proposer(author, block.round())
         })
     }
+
+    /// Return if a given proposed block comes from the backup proposer of its round.
+    fn is_backup_proposal(&self, block: &Block) -> bool {
+        block.author().map_or(false, |author| {
+            !self.is_valid_proposer(author, block.round())
+                && self.get_backup_proposer(block.round()) == Some(author)
+        })
+    }
 }
 
 // next continuously mutates a state and returns a u64-index
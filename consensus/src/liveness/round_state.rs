@@ -1,15 +1,25 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+#[cfg(test)]
+use crate::block_storage::VoteProgress;
 use crate::{
     block_storage::{PendingVotes, VoteReceptionResult},
     counters,
+    liveness::proposer_election::next,
     util::time_service::{SendTask, TimeService},
 };
-use consensus_types::{common::Round, sync_info::SyncInfo, vote::Vote};
+use consensus_types::{
+    common::{Author, Round},
+    sync_info::SyncInfo,
+    timeout::Timeout,
+    vote::Vote,
+};
+use libra_crypto::{ed25519::Ed25519Signature, HashValue};
 use libra_logger::prelude::*;
 use libra_types::validator_verifier::ValidatorVerifier;
 use std::{
+    collections::BTreeMap,
     fmt,
     sync::Arc,
     time::{Duration, Instant},
@@ -144,14 +154,26 @@ pub struct RoundState {
     // The deadline for the next local timeout event. It is reset every time a new round start, or
     // a previous deadline expires.
     current_round_deadline: Instant,
+    // The same deadline according to the time service
+    current_round_deadline_timestamp: Duration,
     // Service for timer
     time_service: Arc<dyn TimeService>,
     // To send local timeout events to the subscriber (e.g., SMR)
     timeout_sender: channel::Sender<Round>,
     // Votes received fot the current round.
     pending_votes: PendingVotes,
+    // Votes received for the past rounds within `pending_votes_window` of the current one, late
+    // votes for them can still complete a certificate.
+    past_pending_votes: BTreeMap<Round, PendingVotes>,
+    // Number of past rounds whose pending votes are kept.
+    pending_votes_window: Round,
     // Vote sent locally for the current round.
     vote_sent: Option<Vote>,
+    // Author used to derive a deterministic per-validator jitter added to the round timeout,
+    // so that validators sharing the same base timeout don't time out simultaneously.
+    jitter_author: Option<Author>,
+    // Upper bound of the jitter as a fraction of the round timeout.
+    max_jitter_ratio: f64,
 }
 
 // this is required by structured log
@@ -193,13 +215,37 @@ impl RoundState {
             highest_committed_round: 0,
             current_round: 0,
             current_round_deadline: Instant::now(),
+            current_round_deadline_timestamp: time_service.get_current_timestamp(),
             time_service,
             timeout_sender,
             pending_votes: PendingVotes::new(),
+            past_pending_votes: BTreeMap::new(),
+            pending_votes_window: 0,
             vote_sent: None,
+            jitter_author: None,
+            max_jitter_ratio: 0.0,
         }
     }
 
+    /// Add a jitter of up to `max_jitter_ratio` of the round timeout to every round deadline.
+    /// The jitter is a deterministic function of the author and the round.
+    pub fn with_timeout_jitter(mut self, author: Author, max_jitter_ratio: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&max_jitter_ratio),
+            "max_jitter_ratio should be within [0, 1]"
+        );
+        self.jitter_author = Some(author);
+        self.max_jitter_ratio = max_jitter_ratio;
+        self
+    }
+
+    /// Keep collecting the votes of the past `window` rounds, the older ones are pruned whenever
+    /// a new round starts.
+    pub fn with_pending_votes_window(mut self, window: Round) -> Self {
+        self.pending_votes_window = window;
+        self
+    }
+
     /// Return the current round.
     pub fn current_round(&self) -> Round {
         self.current_round
@@ -210,6 +256,12 @@ impl RoundState {
         self.current_round_deadline
     }
 
+    /// Returns deadline for current round according to the time service
+    #[cfg(test)]
+    pub fn current_round_deadline_timestamp(&self) -> Duration {
+        self.current_round_deadline_timestamp
+    }
+
     /// In case the local timeout corresponds to the current round, reset the timeout and
     /// return true. Otherwise ignore and return false.
     pub fn process_local_timeout(&mut self, round: Round) -> bool {
@@ -231,8 +283,14 @@ impl RoundState {
         let new_round = sync_info.highest_round() + 1;
         if new_round > self.current_round {
             // Start a new round.
+            let pending_votes = std::mem::replace(&mut self.pending_votes, PendingVotes::new());
+            if self.pending_votes_window > 0 {
+                self.past_pending_votes
+                    .insert(self.current_round, pending_votes);
+            }
             self.current_round = new_round;
-            self.pending_votes = PendingVotes::new();
+            let oldest_round = new_round.saturating_sub(self.pending_votes_window);
+            self.past_pending_votes = self.past_pending_votes.split_off(&oldest_round);
             self.vote_sent = None;
             let timeout = self.setup_timeout();
             // The new round reason is QCReady in case both QC and TC are equal
@@ -257,16 +315,53 @@ impl RoundState {
         vote: &Vote,
         verifier: &ValidatorVerifier,
     ) -> VoteReceptionResult {
-        if vote.vote_data().proposed().round() == self.current_round {
+        let round = vote.vote_data().proposed().round();
+        if round == self.current_round {
             self.pending_votes.insert_vote(vote, verifier)
+        } else if round > self.current_round {
+            VoteReceptionResult::UnexpectedRound(round, self.current_round)
+        } else {
+            match self.past_pending_votes.get_mut(&round) {
+                Some(pending_votes) => pending_votes.insert_vote(vote, verifier),
+                None => VoteReceptionResult::PrunedRound(round),
+            }
+        }
+    }
+
+    /// Returns the rounds whose votes are currently collected, in ascending order.
+    #[cfg(test)]
+    pub fn pending_vote_rounds(&self) -> Vec<Round> {
+        self.past_pending_votes
+            .keys()
+            .cloned()
+            .chain(std::iter::once(self.current_round))
+            .collect()
+    }
+
+    pub fn insert_timeout(
+        &mut self,
+        timeout: &Timeout,
+        author: Author,
+        signature: Ed25519Signature,
+        verifier: &ValidatorVerifier,
+    ) -> VoteReceptionResult {
+        if timeout.round() == self.current_round {
+            self.pending_votes
+                .insert_timeout(timeout, author, signature, verifier)
         } else {
-            VoteReceptionResult::UnexpectedRound(
-                vote.vote_data().proposed().round(),
-                self.current_round,
-            )
+            VoteReceptionResult::UnexpectedRound(timeout.round(), self.current_round)
         }
     }
 
+    #[cfg(test)]
+    pub fn vote_progress(
+        &self,
+        block_id: HashValue,
+        verifier: &ValidatorVerifier,
+    ) -> Option<VoteProgress> {
+        self.pending_votes.vote_progress(block_id, verifier)
+    }
+
     pub fn record_vote(&mut self, vote: Vote) {
         if vote.vote_data().proposed().round() == self.current_round {
             self.vote_sent = Some(vote);
@@ -304,9 +399,10 @@ impl RoundState {
                 self.current_round - self.highest_committed_round - 3
             }
         } as usize;
-        let timeout = self
+        let base_timeout = self
             .time_interval
             .get_round_duration(round_index_after_committed_round);
+        let timeout = base_timeout + self.timeout_jitter(base_timeout);
         let now = Instant::now();
         debug!(
             "{:?} passed since the previous deadline.",
@@ -315,6 +411,19 @@ impl RoundState {
         );
         debug!("Set round deadline to {:?} from now", timeout);
         self.current_round_deadline = now + timeout;
+        self.current_round_deadline_timestamp = self.time_service.get_current_timestamp() + timeout;
         timeout
     }
+
+    /// Deterministic jitter for the current round, within [0, max_jitter_ratio * base_timeout]
+    fn timeout_jitter(&self, base_timeout: Duration) -> Duration {
+        let author = match self.jitter_author {
+            Some(author) => author,
+            None => return Duration::from_secs(0),
+        };
+        let mut state = author.to_vec();
+        state.extend_from_slice(&self.current_round.to_le_bytes());
+        let fraction = next(&mut state) as f64 / std::u64::MAX as f64;
+        base_timeout.mul_f64(self.max_jitter_ratio * fraction)
+    }
 }
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    block_storage::VoteReceptionResult,
     liveness::round_state::{
         ExponentialTimeInterval, NewRoundEvent, NewRoundReason, RoundState, RoundTimeInterval,
     },
@@ -10,13 +11,15 @@ use crate::{
 
 use consensus_types::{
     common::Round, quorum_cert::QuorumCert, sync_info::SyncInfo, timeout::Timeout,
-    timeout_certificate::TimeoutCertificate, vote_data::VoteData,
+    timeout_certificate::TimeoutCertificate, vote::Vote, vote_data::VoteData,
 };
 use futures::StreamExt;
 use libra_crypto::HashValue;
 use libra_types::{
+    account_address::AccountAddress,
     block_info::BlockInfo,
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    validator_verifier::random_validator_verifier,
 };
 use std::{collections::BTreeMap, sync::Arc, time::Duration};
 
@@ -78,6 +81,76 @@ fn test_round_event_generation() {
     );
 }
 
+#[test]
+fn test_timeout_jitter() {
+    let base_timeout = Duration::from_millis(1000);
+    let max_jitter_ratio = 0.1;
+    let round_timeout = |author| {
+        let time_interval = Box::new(ExponentialTimeInterval::fixed(base_timeout));
+        let (timeout_tx, _) = channel::new_test(1_024);
+        let mut round_state = RoundState::new(
+            time_interval,
+            Arc::new(SimulatedTimeService::new()),
+            timeout_tx,
+        )
+        .with_timeout_jitter(author, max_jitter_ratio);
+        round_state
+            .process_certificates(generate_sync_info(Some(1), None, None))
+            .unwrap()
+            .timeout
+    };
+    let author_a = AccountAddress::new([1; AccountAddress::LENGTH]);
+    let author_b = AccountAddress::new([2; AccountAddress::LENGTH]);
+
+    let timeout_a = round_timeout(author_a);
+    let timeout_b = round_timeout(author_b);
+    // the jitter is reproducible for a given author and round
+    assert_eq!(timeout_a, round_timeout(author_a));
+    assert_ne!(timeout_a, timeout_b);
+    for timeout in &[timeout_a, timeout_b] {
+        assert!(*timeout >= base_timeout);
+        assert!(*timeout <= base_timeout.mul_f64(1.0 + max_jitter_ratio));
+    }
+}
+
+#[test]
+fn test_pending_votes_pruned() {
+    let (round_state, _) = make_round_state();
+    let mut round_state = round_state.with_pending_votes_window(2);
+    let (signers, verifier) = random_validator_verifier(4, None, false);
+    let vote_for_round = |round| {
+        let proposed = BlockInfo::new(1, round, HashValue::random(), HashValue::zero(), 0, 0, None);
+        Vote::new(
+            VoteData::new(proposed, BlockInfo::empty()),
+            signers[0].author(),
+            LedgerInfo::new(BlockInfo::empty(), HashValue::zero()),
+            &signers[0],
+        )
+    };
+
+    round_state.process_certificates(generate_sync_info(Some(0), None, None));
+    assert!(matches!(
+        round_state.insert_vote(&vote_for_round(1), &verifier),
+        VoteReceptionResult::VoteAdded(1)
+    ));
+    // the votes of the past rounds within the window are kept
+    round_state.process_certificates(generate_sync_info(Some(1), None, None));
+    round_state.process_certificates(generate_sync_info(Some(2), None, None));
+    assert_eq!(round_state.pending_vote_rounds(), vec![1, 2, 3]);
+
+    round_state.process_certificates(generate_sync_info(Some(3), None, None));
+    assert_eq!(round_state.pending_vote_rounds(), vec![2, 3, 4]);
+    assert!(matches!(
+        round_state.insert_vote(&vote_for_round(1), &verifier),
+        VoteReceptionResult::PrunedRound(1)
+    ));
+    // a late vote for a round within the window is still collected
+    assert!(matches!(
+        round_state.insert_vote(&vote_for_round(2), &verifier),
+        VoteReceptionResult::VoteAdded(1)
+    ));
+}
+
 fn make_round_state() -> (RoundState, channel::Receiver<Round>) {
     let time_interval = Box::new(ExponentialTimeInterval::fixed(Duration::from_millis(2)));
     let simulated_time = SimulatedTimeService::auto_advance_until(Duration::from_millis(4));
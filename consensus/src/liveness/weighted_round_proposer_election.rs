@@ -0,0 +1,67 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::liveness::proposer_election::{next, ProposerElection};
+use consensus_types::common::{Author, Round};
+use libra_types::validator_verifier::ValidatorVerifier;
+use std::cmp::Ordering;
+
+/// The weighted round proposer maps a round to an author with probability proportional to the
+/// author's voting power. The round number is used as the seed of the selection, so all honest
+/// replicas agree on the proposer of each round.
+pub struct WeightedRoundProposerElection {
+    // Ordering of proposers (all honest replicas must agree on this)
+    proposers: Vec<Author>,
+    // Cumulative voting power of proposers[0..=i] at index i
+    cumulative_weights: Vec<u64>,
+}
+
+impl WeightedRoundProposerElection {
+    pub fn new(verifier: &ValidatorVerifier) -> Self {
+        let mut proposers = vec![];
+        let mut cumulative_weights = vec![];
+        let mut total_weight = 0;
+        for author in verifier.get_ordered_account_addresses_iter() {
+            let voting_power = verifier
+                .get_voting_power(&author)
+                .expect("Author from the verifier must have voting power");
+            // Validators without voting power are never chosen as proposers
+            if voting_power == 0 {
+                continue;
+            }
+            total_weight += voting_power;
+            proposers.push(author);
+            cumulative_weights.push(total_weight);
+        }
+        assert!(
+            total_weight > 0,
+            "Weighted proposer election requires a positive total voting power"
+        );
+        Self {
+            proposers,
+            cumulative_weights,
+        }
+    }
+}
+
+impl ProposerElection for WeightedRoundProposerElection {
+    fn get_valid_proposer(&self, round: Round) -> Author {
+        let total_weight = *self
+            .cumulative_weights
+            .last()
+            .expect("There is at least one proposer");
+        let mut state = round.to_le_bytes().to_vec();
+        let chosen_weight = next(&mut state) % total_weight;
+        let chosen_index = self
+            .cumulative_weights
+            .binary_search_by(|w| {
+                if *w <= chosen_weight {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                }
+            })
+            .unwrap_err();
+        self.proposers[chosen_index]
+    }
+}
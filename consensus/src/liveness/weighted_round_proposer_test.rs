@@ -0,0 +1,80 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::liveness::{
+    proposer_election::ProposerElection,
+    weighted_round_proposer_election::WeightedRoundProposerElection,
+};
+use consensus_types::block::{block_test_utils::certificate_for_genesis, Block};
+use libra_types::{
+    validator_signer::ValidatorSigner,
+    validator_verifier::{ValidatorConsensusInfo, ValidatorVerifier},
+};
+use std::collections::{BTreeMap, HashMap};
+
+fn create_verifier(signers: &[ValidatorSigner], weights: &[u64]) -> ValidatorVerifier {
+    let address_to_validator_info: BTreeMap<_, _> = signers
+        .iter()
+        .zip(weights)
+        .map(|(signer, weight)| {
+            (
+                signer.author(),
+                ValidatorConsensusInfo::new(signer.public_key(), *weight),
+            )
+        })
+        .collect();
+    ValidatorVerifier::new(address_to_validator_info)
+}
+
+#[test]
+fn test_weighted_round_proposer_consistency() {
+    let signers: Vec<_> = (0..4u8).map(|i| ValidatorSigner::random([i; 32])).collect();
+    let verifier = create_verifier(&signers, &[1, 2, 3, 4]);
+    let pe = WeightedRoundProposerElection::new(&verifier);
+    let another_pe = WeightedRoundProposerElection::new(&verifier);
+    let quorum_cert = certificate_for_genesis();
+
+    for round in 1..100 {
+        let proposer = pe.get_valid_proposer(round);
+        // Every replica elects the same proposer
+        assert_eq!(another_pe.get_valid_proposer(round), proposer);
+        for signer in &signers {
+            let proposal = Block::new_proposal(vec![], round, 1, quorum_cert.clone(), signer);
+            assert_eq!(
+                pe.is_valid_proposer(signer.author(), round),
+                signer.author() == proposer
+            );
+            assert_eq!(pe.is_valid_proposal(&proposal), signer.author() == proposer);
+        }
+    }
+}
+
+#[test]
+fn test_weighted_round_proposer_frequency() {
+    let weights = [1, 2, 3, 4, 0];
+    let signers: Vec<_> = (0..weights.len() as u8)
+        .map(|i| ValidatorSigner::random([i; 32]))
+        .collect();
+    let verifier = create_verifier(&signers, &weights);
+    let pe = WeightedRoundProposerElection::new(&verifier);
+
+    let num_rounds = 40_000;
+    let mut frequency = HashMap::new();
+    for round in 0..num_rounds {
+        *frequency.entry(pe.get_valid_proposer(round)).or_insert(0) += 1;
+    }
+
+    let total_weight: u64 = weights.iter().sum();
+    for (signer, weight) in signers.iter().zip(weights.iter()) {
+        let expected = num_rounds * weight / total_weight;
+        let actual = *frequency.get(&signer.author()).unwrap_or(&0);
+        // allow 10% deviation from the expected frequency
+        assert!(
+            actual * 10 >= expected * 9 && actual * 10 <= expected * 11,
+            "author with weight {} chosen {} times, expected {}",
+            weight,
+            actual,
+            expected
+        );
+    }
+}
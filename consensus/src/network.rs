@@ -13,6 +13,7 @@ use consensus_types::{
     common::Author,
     proposal_msg::ProposalMsg,
     sync_info::SyncInfo,
+    timeout_msg::TimeoutMsg,
     vote_msg::VoteMsg,
 };
 use futures::{channel::oneshot, stream::select, SinkExt, Stream, StreamExt, TryStreamExt};
@@ -33,6 +34,7 @@ use std::{
 /// for carrying the response
 #[derive(Debug)]
 pub struct IncomingBlockRetrievalRequest {
+    pub requester: Author,
     pub req: BlockRetrievalRequest,
     pub response_sender: oneshot::Sender<Result<Bytes, RpcError>>,
 }
@@ -124,6 +126,12 @@ impl NetworkSender {
         self.broadcast(msg).await
     }
 
+    /// Sends the given proposal to the chosen recipients only, see `broadcast_proposal`.
+    pub async fn send_proposal(&self, proposal: ProposalMsg, recipients: Vec<Author>) {
+        let msg = ConsensusMsg::ProposalMsg(Box::new(proposal));
+        self.send(msg, recipients).await
+    }
+
     async fn broadcast(&mut self, msg: ConsensusMsg) {
         // Directly send the message to ourself without going through network.
         let self_msg = Event::Message((self.author, msg.clone()));
@@ -154,19 +162,23 @@ impl NetworkSender {
     /// out. It does not give indication about when the message is delivered to the recipients,
     /// as well as there is no indication about the network failures.
     pub async fn send_vote(&self, vote_msg: VoteMsg, recipients: Vec<Author>) {
+        let msg = ConsensusMsg::VoteMsg(Box::new(vote_msg));
+        self.send(msg, recipients).await
+    }
+
+    async fn send(&self, msg: ConsensusMsg, recipients: Vec<Author>) {
         let mut network_sender = self.network_sender.clone();
         let mut self_sender = self.self_sender.clone();
-        let msg = ConsensusMsg::VoteMsg(Box::new(vote_msg));
         for peer in recipients {
             if self.author == peer {
                 let self_msg = Event::Message((self.author, msg.clone()));
                 if let Err(err) = self_sender.send(Ok(self_msg)).await {
-                    error!("Error delivering a self vote: {:?}", err);
+                    error!("Error delivering a self message: {:?}", err);
                 }
                 continue;
             }
             if let Err(e) = network_sender.send_to(peer, msg.clone()) {
-                error!("Failed to send a vote to peer {:?}: {:?}", peer, e);
+                error!("Failed to send a message to peer {:?}: {:?}", peer, e);
             }
         }
     }
@@ -177,6 +189,12 @@ impl NetworkSender {
         self.broadcast(msg).await
     }
 
+    /// Broadcasts timeout message to all validators
+    pub async fn broadcast_timeout(&mut self, timeout_msg: TimeoutMsg) {
+        let msg = ConsensusMsg::TimeoutMsg(Box::new(timeout_msg));
+        self.broadcast(msg).await
+    }
+
     /// Sends the given sync info to the given author.
     /// The future is fulfilled as soon as the message is added to the internal network channel
     /// (does not indicate whether the message is delivered or sent out).
@@ -265,6 +283,7 @@ impl NetworkTask {
                     ConsensusMsg::BlockRetrievalRequest(request) => {
                         debug!("Received block retrieval request {}", request);
                         let req_with_callback = IncomingBlockRetrievalRequest {
+                            requester: peer_id,
                             req: *request,
                             response_sender: callback,
                         };
@@ -10,6 +10,7 @@ use consensus_types::{
     epoch_retrieval::EpochRetrievalRequest,
     proposal_msg::ProposalMsg,
     sync_info::SyncInfo,
+    timeout_msg::TimeoutMsg,
     vote_msg::VoteMsg,
 };
 use libra_types::{epoch_change::EpochChangeProof, PeerId};
@@ -46,6 +47,8 @@ pub enum ConsensusMsg {
     /// VoteMsg is the struct that is ultimately sent by the voter in response for receiving a
     /// proposal.
     VoteMsg(Box<VoteMsg>),
+    /// TimeoutMsg carries the round signature of a validator that timed out without voting.
+    TimeoutMsg(Box<TimeoutMsg>),
 }
 
 /// The interface from Network to Consensus layer.
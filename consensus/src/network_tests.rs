@@ -312,13 +312,13 @@ impl NetworkPlayground {
         matches!(&msg.1, ConsensusMsg::VoteMsg(_))
     }
 
-    /// Returns true for vote messages that carry round signatures only.
+    /// Returns true for vote messages that carry round signatures and timeout messages only.
     pub fn timeout_votes_only(msg: &(Author, ConsensusMsg)) -> bool {
         matches!(
             &msg.1,
             // Timeout votes carry non-empty round signatures.
             ConsensusMsg::VoteMsg(vote_msg) if vote_msg.vote().timeout_signature().is_some()
-        )
+        ) || matches!(&msg.1, ConsensusMsg::TimeoutMsg(_))
     }
 
     /// Returns true for sync info messages only.
@@ -15,6 +15,7 @@ use libra_types::{
     block_info::Round, epoch_change::EpochChangeProof, ledger_info::LedgerInfo,
     transaction::Version,
 };
+use serde::{Deserialize, Serialize};
 use std::{cmp::max, collections::HashSet, sync::Arc};
 use storage_interface::DbReader;
 
@@ -49,6 +50,26 @@ pub trait PersistentLivenessStorage: Send + Sync {
 
     /// Returns a handle of the libradb.
     fn libra_db(&self) -> Arc<dyn DbReader>;
+
+    /// Persist the progress of a fast forward sync.
+    fn save_sync_checkpoint(&self, checkpoint: &SyncCheckpoint) -> Result<()>;
+
+    /// Retrieve the progress of an unfinished fast forward sync, if any.
+    fn retrieve_sync_checkpoint(&self) -> Result<Option<SyncCheckpoint>>;
+
+    /// Delete the progress of a fast forward sync once it's done.
+    fn delete_sync_checkpoint(&self) -> Result<()>;
+
+    /// Retrieve a persisted block by id.
+    fn retrieve_block(&self, block_id: HashValue) -> Result<Option<Block>>;
+}
+
+/// Progress marker of a fast forward sync: the chain of blocks ending at `highest_block_id`
+/// has been retrieved and persisted, but the node might not have finished syncing to it.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SyncCheckpoint {
+    pub epoch: u64,
+    pub highest_block_id: HashValue,
 }
 
 #[derive(Clone)]
@@ -412,4 +433,25 @@ impl PersistentLivenessStorage for StorageWriteProxy {
     fn libra_db(&self) -> Arc<dyn DbReader> {
         self.libra_db.clone()
     }
+
+    fn save_sync_checkpoint(&self, checkpoint: &SyncCheckpoint) -> Result<()> {
+        self.db.save_sync_checkpoint(lcs::to_bytes(checkpoint)?)
+    }
+
+    fn retrieve_sync_checkpoint(&self) -> Result<Option<SyncCheckpoint>> {
+        self.db
+            .get_sync_checkpoint()?
+            .map(|bytes| {
+                lcs::from_bytes(&bytes[..]).context("unable to deserialize sync checkpoint")
+            })
+            .transpose()
+    }
+
+    fn delete_sync_checkpoint(&self) -> Result<()> {
+        self.db.delete_sync_checkpoint()
+    }
+
+    fn retrieve_block(&self, block_id: HashValue) -> Result<Option<Block>> {
+        self.db.get_block(block_id)
+    }
 }
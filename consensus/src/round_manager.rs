@@ -1,37 +1,60 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{sync::Arc, time::Duration};
+use std::{
+    collections::{btree_map::Entry, BTreeMap, HashMap, HashSet, VecDeque},
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
-use anyhow::{ensure, Context, Result};
+use anyhow::{bail, ensure, Context, Result};
 use termion::color::*;
+use thiserror::Error;
 
 use consensus_types::{
     block::Block,
+    block_data::BlockData,
     block_retrieval::{BlockRetrievalResponse, BlockRetrievalStatus},
     common::{Author, Round},
     proposal_msg::ProposalMsg,
     quorum_cert::QuorumCert,
     sync_info::SyncInfo,
+    timeout::Timeout,
     timeout_certificate::TimeoutCertificate,
+    timeout_msg::TimeoutMsg,
     vote::Vote,
     vote_msg::VoteMsg,
     vote_proposal::VoteProposal,
 };
 use debug_interface::prelude::*;
-use libra_crypto::hash::TransactionAccumulatorHasher;
-use libra_logger::prelude::*;
+use executor_types::StateComputeResult;
+use futures::channel::mpsc;
+use libra_config::config::{BroadcastStrategy, ConsensusConfig, TimeoutMode};
+use libra_crypto::{hash::TransactionAccumulatorHasher, HashValue};
+use libra_logger::{prelude::*, StructuredLogEntry};
+#[cfg(test)]
+use libra_metrics::DurationHistogram;
 use libra_security_logger::{security_log, SecurityEvent};
 use libra_types::{
-    epoch_state::EpochState, proof::AccumulatorExtensionProof,
-    validator_verifier::ValidatorVerifier,
+    account_address::AccountAddress, epoch_state::EpochState,
+    ledger_info::LedgerInfoWithSignatures, proof::AccumulatorExtensionProof,
+    transaction::SignedTransaction, validator_verifier::ValidatorVerifier,
 };
+use rand::seq::SliceRandom;
 #[cfg(test)]
+use rayon::prelude::*;
 use safety_rules::ConsensusState;
 use safety_rules::TSafetyRules;
+#[cfg(test)]
+use serde::{Deserialize, Serialize};
 
+#[cfg(test)]
+use crate::block_storage::VoteProgress;
 use crate::{
-    block_storage::{BlockReader, BlockRetriever, BlockStore, VoteReceptionResult},
+    block_storage::{
+        BlockReader, BlockRetriever, BlockStore, RetrievalBackoff, VoteReceptionResult,
+    },
     counters,
     liveness::{
         proposal_generator::ProposalGenerator,
@@ -42,8 +65,13 @@ use crate::{
     network_interface::ConsensusMsg,
     persistent_liveness_storage::{PersistentLivenessStorage, RecoveryData},
     state_replication::{StateComputer, TxnManager},
-    util::time_service::{
-        duration_since_epoch, wait_if_possible, TimeService, WaitingError, WaitingSuccess,
+    util::{
+        lru_cache::LruCache,
+        rate_limiter::RateLimiter,
+        time_service::{
+            duration_since_epoch, wait_if_possible, SendTask, TimeService, WaitingError,
+            WaitingSuccess,
+        },
     },
 };
 
@@ -51,6 +79,7 @@ pub enum UnverifiedEvent {
     ProposalMsg(Box<ProposalMsg>),
     VoteMsg(Box<VoteMsg>),
     SyncInfo(Box<SyncInfo>),
+    TimeoutMsg(Box<TimeoutMsg>),
 }
 
 impl UnverifiedEvent {
@@ -68,14 +97,32 @@ impl UnverifiedEvent {
                 s.verify(validator)?;
                 VerifiedEvent::SyncInfo(s)
             }
+            UnverifiedEvent::TimeoutMsg(t) => {
+                t.verify(validator)?;
+                VerifiedEvent::TimeoutMsg(t)
+            }
         })
     }
 
+    /// Verifies the events in parallel on the rayon thread pool, the results are returned in the
+    /// order of the input events.
+    #[cfg(test)]
+    pub fn verify_batch(
+        events: Vec<UnverifiedEvent>,
+        validator: &ValidatorVerifier,
+    ) -> Vec<Result<VerifiedEvent>> {
+        events
+            .into_par_iter()
+            .map(|event| event.verify(validator))
+            .collect()
+    }
+
     pub fn epoch(&self) -> u64 {
         match self {
             UnverifiedEvent::ProposalMsg(p) => p.epoch(),
             UnverifiedEvent::VoteMsg(v) => v.epoch(),
             UnverifiedEvent::SyncInfo(s) => s.epoch(),
+            UnverifiedEvent::TimeoutMsg(t) => t.epoch(),
         }
     }
 }
@@ -86,6 +133,7 @@ impl From<ConsensusMsg> for UnverifiedEvent {
             ConsensusMsg::ProposalMsg(m) => UnverifiedEvent::ProposalMsg(m),
             ConsensusMsg::VoteMsg(m) => UnverifiedEvent::VoteMsg(m),
             ConsensusMsg::SyncInfo(m) => UnverifiedEvent::SyncInfo(m),
+            ConsensusMsg::TimeoutMsg(m) => UnverifiedEvent::TimeoutMsg(m),
             _ => unreachable!("Unexpected conversion"),
         }
     }
@@ -95,6 +143,7 @@ pub enum VerifiedEvent {
     ProposalMsg(Box<ProposalMsg>),
     VoteMsg(Box<VoteMsg>),
     SyncInfo(Box<SyncInfo>),
+    TimeoutMsg(Box<TimeoutMsg>),
 }
 
 #[cfg(test)]
@@ -113,6 +162,7 @@ pub struct RecoveryManager {
     storage: Arc<dyn PersistentLivenessStorage>,
     state_computer: Arc<dyn StateComputer>,
     last_committed_round: Round,
+    config: ConsensusConfig,
 }
 
 impl RecoveryManager {
@@ -122,6 +172,7 @@ impl RecoveryManager {
         storage: Arc<dyn PersistentLivenessStorage>,
         state_computer: Arc<dyn StateComputer>,
         last_committed_round: Round,
+        config: ConsensusConfig,
     ) -> Self {
         RecoveryManager {
             epoch_state,
@@ -129,6 +180,7 @@ impl RecoveryManager {
             storage,
             state_computer,
             last_committed_round,
+            config,
         }
     }
 
@@ -138,33 +190,56 @@ impl RecoveryManager {
     ) -> Result<RecoveryData> {
         let author = proposal_msg.proposer();
         let sync_info = proposal_msg.sync_info();
-        self.sync_up(&sync_info, author).await
+        Ok(self.sync_up(&sync_info, author).await?)
     }
 
     pub async fn process_vote(&mut self, vote_msg: VoteMsg) -> Result<RecoveryData> {
         let author = vote_msg.vote().author();
         let sync_info = vote_msg.sync_info();
-        self.sync_up(&sync_info, author).await
+        Ok(self.sync_up(&sync_info, author).await?)
     }
 
-    async fn sync_up(&mut self, sync_info: &SyncInfo, peer: Author) -> Result<RecoveryData> {
-        sync_info.verify(&self.epoch_state.verifier)?;
-        ensure!(
-            sync_info.highest_round() > self.last_committed_round,
-            "[RecoveryManager] Received sync info has lower round number than committed block"
-        );
-        ensure!(
-            sync_info.epoch() == self.epoch_state.epoch,
-            "[RecoveryManager] Received sync info is in different epoch than committed block"
+    pub async fn process_timeout_msg(&mut self, timeout_msg: TimeoutMsg) -> Result<RecoveryData> {
+        let author = timeout_msg.author();
+        let sync_info = timeout_msg.sync_info();
+        Ok(self.sync_up(&sync_info, author).await?)
+    }
+
+    async fn sync_up(
+        &mut self,
+        sync_info: &SyncInfo,
+        peer: Author,
+    ) -> Result<RecoveryData, SyncUpError> {
+        sync_info
+            .verify(&self.epoch_state.verifier)
+            .map_err(SyncUpError::VerificationFailed)?;
+        if sync_info.highest_round() <= self.last_committed_round {
+            return Err(SyncUpError::StaleSyncInfo {
+                round: sync_info.highest_round(),
+                committed_round: self.last_committed_round,
+            });
+        }
+        if sync_info.epoch() != self.epoch_state.epoch {
+            return Err(SyncUpError::EpochMismatch {
+                local: self.epoch_state.epoch,
+                remote: sync_info.epoch(),
+            });
+        }
+        let mut retriever = BlockRetriever::new(
+            self.network.clone(),
+            vec![peer],
+            self.config.block_retrieval_concurrency,
+            Duration::from_millis(self.config.block_retrieval_timeout_ms),
+            RetrievalBackoff::from_config(&self.config),
         );
-        let mut retriever = BlockRetriever::new(self.network.clone(), peer);
         let recovery_data = BlockStore::fast_forward_sync(
             &sync_info.highest_commit_cert(),
             &mut retriever,
             self.storage.clone(),
             self.state_computer.clone(),
         )
-        .await?;
+        .await
+        .map_err(SyncUpError::RetrievalFailed)?;
 
         Ok(recovery_data)
     }
@@ -174,6 +249,140 @@ impl RecoveryManager {
     }
 }
 
+/// Name of the structured log event emitted on every round transition.
+pub const NEW_ROUND_LOG_EVENT: &str = "consensus.new_round";
+
+/// Returned by the event processing functions of a RoundManager that was shut down at the end of
+/// its epoch.
+#[derive(Debug, PartialEq, Eq, Error)]
+#[error("[RoundManager] Epoch {0} ended, the round manager is shut down")]
+pub struct EpochEnded(pub u64);
+
+/// Returned by `sync_up` when the local state can't be brought up to a peer's SyncInfo, so that
+/// the callers can tell a misbehaving peer from a transient failure.
+#[derive(Debug, Error)]
+pub enum SyncUpError {
+    /// The SyncInfo carries certificates that don't verify against the validator set.
+    #[error("[RoundManager] SyncInfo verification failed: {0:#}")]
+    VerificationFailed(anyhow::Error),
+    /// The missing blocks couldn't be retrieved from the peer or processed in time.
+    #[error("[RoundManager] Failed to retrieve the blocks of SyncInfo: {0:#}")]
+    RetrievalFailed(anyhow::Error),
+    /// The SyncInfo is from another epoch.
+    #[error("[RoundManager] SyncInfo is in epoch {remote}, local epoch is {local}")]
+    EpochMismatch { local: u64, remote: u64 },
+    /// The newer certificates of the SyncInfo are not above the committed round.
+    #[error(
+        "[RoundManager] SyncInfo round {round} is not above committed round {committed_round}"
+    )]
+    StaleSyncInfo {
+        round: Round,
+        committed_round: Round,
+    },
+    /// The certificates of the SyncInfo couldn't be applied to the local state.
+    #[error("[RoundManager] Failed to process the certificates of SyncInfo: {0:#}")]
+    ProcessingFailed(anyhow::Error),
+}
+
+/// Notification sent to the (optional) commit subscriber of RoundManager whenever the highest
+/// commit certificate advances.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommittedBlockInfo {
+    pub block_id: HashValue,
+    pub round: Round,
+    pub ledger_info: LedgerInfoWithSignatures,
+}
+
+/// Point-in-time copy of a duration histogram.
+#[cfg(test)]
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct HistogramSnapshot {
+    /// Number of observed durations.
+    pub sample_count: u64,
+    /// Sum of the observed durations, in seconds.
+    pub sample_sum: f64,
+    /// Upper bound (in seconds) and cumulative count of every bucket.
+    pub buckets: Vec<(f64, u64)>,
+}
+
+#[cfg(test)]
+impl From<&DurationHistogram> for HistogramSnapshot {
+    fn from(histogram: &DurationHistogram) -> Self {
+        Self {
+            sample_count: histogram.sample_count(),
+            sample_sum: histogram.sample_sum(),
+            buckets: histogram.buckets(),
+        }
+    }
+}
+
+/// Point-in-time copy of the round latency histograms, for offline analysis.
+#[cfg(test)]
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct RoundTimingSnapshot {
+    /// Time from the creation of a block until it's received, see `CREATION_TO_RECEIVAL_S`.
+    pub creation_to_receival: HistogramSnapshot,
+    /// Time from the creation of a block until it gathers a QC, see `CREATION_TO_QC_S`.
+    pub creation_to_qc: HistogramSnapshot,
+    /// Time waited before voting, see `VOTE_SUCCESS_WAIT_S`.
+    pub vote_success_wait: HistogramSnapshot,
+}
+
+/// Observer of the certificates aggregated by RoundManager, e.g. for recording them in research
+/// tooling. The callbacks are invoked synchronously on the consensus path and must be cheap.
+/// A panicking observer is caught and logged, it doesn't affect consensus.
+pub trait CertObserver: Send + Sync {
+    /// Called with a newly aggregated QC and the author of the vote that completed it.
+    fn on_qc(&self, qc: &QuorumCert, author: Author);
+
+    /// Called with a newly aggregated TC.
+    fn on_tc(&self, tc: &TimeoutCertificate);
+}
+
+/// Local policy of the proposals a validator is willing to vote for, e.g. to refuse blocks with
+/// transactions on a denylist. It's consulted after a proposal is executed and can only make the
+/// validator abstain, it doesn't affect the safety rules. NIL blocks are always voted for so that
+/// timeouts keep working.
+pub trait VotePolicy: Send + Sync {
+    /// Returns false to abstain from voting for the executed block.
+    fn should_vote(&self, block: &Block, compute_result: &StateComputeResult) -> bool;
+}
+
+/// Local policy of the transactions a validator is willing to propose, e.g. to leave out the
+/// transactions to denylisted recipients. The transactions it rejects are removed from the
+/// generated proposals before they are signed, possibly leaving an empty proposal.
+pub trait PayloadFilter: Send + Sync {
+    /// Returns false to leave the transaction out of the proposal.
+    fn keep(&self, txn: &SignedTransaction) -> bool;
+}
+
+/// Receives the signals about the peers' behavior observed by consensus, e.g. to feed them into
+/// a peer reputation. The scoring policy is up to the implementation, the callbacks are invoked
+/// synchronously on the consensus path and must be cheap.
+pub trait PeerScoreSink: Send + Sync {
+    /// Called when a message from the peer fails verification.
+    fn report_invalid(&self, peer: Author, reason: &str);
+
+    /// Called when the certificates received from the peer were successfully synced up to.
+    fn report_useful(&self, peer: Author);
+}
+
+/// The optional hooks of RoundManager. They're set up once by the node and shared by the
+/// RoundManagers of all the epochs.
+#[derive(Clone, Default)]
+pub struct RoundManagerHooks {
+    /// Notified whenever the highest commit certificate advances.
+    pub commit_notifier: Option<mpsc::Sender<CommittedBlockInfo>>,
+    /// Observes the aggregated QCs and TCs.
+    pub cert_observer: Option<Arc<dyn CertObserver>>,
+    /// Lets the validator abstain from voting for some proposals.
+    pub vote_policy: Option<Arc<dyn VotePolicy>>,
+    /// Receives the signals about the peers' behavior.
+    pub peer_score_sink: Option<Arc<dyn PeerScoreSink>>,
+    /// Leaves some transactions out of the generated proposals.
+    pub payload_filter: Option<Arc<dyn PayloadFilter>>,
+}
+
 /// Consensus SMR is working in an event based fashion: RoundManager is responsible for
 /// processing the individual events (e.g., process_new_round, process_proposal, process_vote,
 /// etc.). It is exposing the async processing functions for each event type.
@@ -190,6 +399,44 @@ pub struct RoundManager {
     txn_manager: Box<dyn TxnManager>,
     storage: Arc<dyn PersistentLivenessStorage>,
     time_service: Arc<dyn TimeService>,
+    // Notified of the rounds whose backup proposal can be voted for, unless the proposal of the
+    // valid proposer shows up in the meantime
+    backup_proposal_sender: channel::Sender<Round>,
+    config: ConsensusConfig,
+    commit_notifier: Option<mpsc::Sender<CommittedBlockInfo>>,
+    last_notified_commit_round: Round,
+    cert_observer: Option<Arc<dyn CertObserver>>,
+    vote_policy: Option<Arc<dyn VotePolicy>>,
+    peer_score_sink: Option<Arc<dyn PeerScoreSink>>,
+    payload_filter: Option<Arc<dyn PayloadFilter>>,
+    // The proposals received from the valid proposers of the current and future rounds, there
+    // can be a primary and a backup proposer per round
+    proposals_seen: BTreeMap<(Round, Author), HashValue>,
+    // The proposals of the current and future rounds already gossiped further
+    proposals_forwarded: BTreeMap<Round, HashSet<HashValue>>,
+    // The valid proposals of the current round received from either the valid or the backup
+    // proposer, the one to vote for is selected among them
+    candidate_proposals: BTreeMap<Round, Vec<Block>>,
+    // The proposals for future rounds that we couldn't catch up to, re-examined once the
+    // current round reaches them
+    future_proposals: BTreeMap<Round, ProposalMsg>,
+    // Limits the rate of block retrieval requests served to each peer, if enabled
+    block_retrieval_limiter: Option<RateLimiter<Author>>,
+    // The recently served blocks, the ones below the root are dropped as they're pruned
+    block_retrieval_cache: Arc<Mutex<LruCache<HashValue, Block>>>,
+    // Set once the epoch ended, no more events are processed
+    shut_down: bool,
+    // The highest round of the verified SyncInfos received from peers
+    highest_observed_round: Round,
+    // Cleared to keep voting without proposing, e.g. during maintenance
+    proposing_enabled: bool,
+    // When the last proposal of this validator was broadcast, according to the time service
+    last_proposal_time: Option<Duration>,
+    // The most recent safety rules states, up to `consensus_state_history_size`
+    consensus_states: VecDeque<ConsensusState>,
+    // The last SyncInfo sent to help each stale peer, and when it was sent according to the
+    // time service
+    sync_info_sent: HashMap<Author, (Duration, SyncInfo)>,
 }
 
 impl RoundManager {
@@ -204,10 +451,25 @@ impl RoundManager {
         txn_manager: Box<dyn TxnManager>,
         storage: Arc<dyn PersistentLivenessStorage>,
         time_service: Arc<dyn TimeService>,
+        backup_proposal_sender: channel::Sender<Round>,
+        config: ConsensusConfig,
+        hooks: RoundManagerHooks,
     ) -> Self {
         counters::BLOCK_RETRIEVAL_COUNT.get();
         counters::STATE_SYNC_COUNT.get();
 
+        let last_notified_commit_round = block_store.highest_commit_cert().commit_info().round();
+        let block_retrieval_limiter = if config.block_retrieval_rate_per_sec > 0 {
+            Some(RateLimiter::new(
+                config.block_retrieval_rate_per_sec,
+                config.block_retrieval_burst,
+            ))
+        } else {
+            None
+        };
+        let block_retrieval_cache =
+            Arc::new(Mutex::new(LruCache::new(config.block_retrieval_cache_size)));
+
         Self {
             epoch_state,
             block_store,
@@ -219,11 +481,50 @@ impl RoundManager {
             network,
             storage,
             time_service,
+            backup_proposal_sender,
+            config,
+            commit_notifier: hooks.commit_notifier,
+            last_notified_commit_round,
+            cert_observer: hooks.cert_observer,
+            vote_policy: hooks.vote_policy,
+            peer_score_sink: hooks.peer_score_sink,
+            payload_filter: hooks.payload_filter,
+            proposals_seen: BTreeMap::new(),
+            proposals_forwarded: BTreeMap::new(),
+            candidate_proposals: BTreeMap::new(),
+            future_proposals: BTreeMap::new(),
+            block_retrieval_limiter,
+            block_retrieval_cache,
+            shut_down: false,
+            highest_observed_round: 0,
+            proposing_enabled: true,
+            last_proposal_time: None,
+            consensus_states: VecDeque::new(),
+            sync_info_sent: HashMap::new(),
         }
     }
 
+    fn ensure_not_shut_down(&self) -> anyhow::Result<()> {
+        if self.shut_down {
+            return Err(EpochEnded(self.epoch_state.epoch).into());
+        }
+        Ok(())
+    }
+
+    /// Each retrieval of the returned retriever is bounded by a deadline derived from the round
+    /// timeout, so that a slow or malicious peer can't block the event processing.
     fn create_block_retriever(&self, author: Author) -> BlockRetriever {
-        BlockRetriever::new(self.network.clone(), author)
+        BlockRetriever::new(
+            self.network.clone(),
+            vec![author],
+            self.config.block_retrieval_concurrency,
+            Duration::from_millis(self.config.block_retrieval_timeout_ms),
+            RetrievalBackoff::from_config(&self.config),
+        )
+        .with_deadline(
+            Duration::from_millis(self.config.round_initial_timeout_ms)
+                .mul_f64(self.config.sync_up_timeout_ratio),
+        )
     }
 
     /// Leader:
@@ -240,6 +541,7 @@ impl RoundManager {
     /// Do nothing
     async fn process_new_round_event(&mut self, new_round_event: NewRoundEvent) {
         debug!("Processing {}", new_round_event);
+        send_struct_log!(self.new_round_log_entry(&new_round_event));
         counters::CURRENT_ROUND.set(new_round_event.round as i64);
         counters::ROUND_TIMEOUT_MS.set(new_round_event.timeout.as_millis() as i64);
         match new_round_event.reason {
@@ -252,10 +554,18 @@ impl RoundManager {
         };
         if !self
             .proposer_election
-            .is_valid_proposer(self.proposal_generator.author(), new_round_event.round)
+            .is_candidate_proposer(self.proposal_generator.author(), new_round_event.round)
         {
             return;
         }
+        if !self.proposing_enabled {
+            debug!(
+                "Proposing is disabled, not proposing for round {}",
+                new_round_event.round
+            );
+            return;
+        }
+        let round = new_round_event.round;
         let proposal_msg = match self.generate_proposal(new_round_event).await {
             Ok(x) => x,
             Err(e) => {
@@ -263,24 +573,142 @@ impl RoundManager {
                 return;
             }
         };
+        if !self.wait_min_proposal_interval(round).await {
+            return;
+        }
         let mut network = self.network.clone();
-        network.broadcast_proposal(proposal_msg).await;
+        match self.config.broadcast_strategy {
+            BroadcastStrategy::Gossip { fanout } => {
+                let mut recipients = self.gossip_peers(fanout);
+                recipients.push(self.proposal_generator.author());
+                network.send_proposal(proposal_msg, recipients).await;
+            }
+            BroadcastStrategy::Full => network.broadcast_proposal(proposal_msg).await,
+        }
         counters::PROPOSALS_COUNT.inc();
     }
 
+    /// Returns false if the same SyncInfo was sent to help the peer less than
+    /// `sync_info_help_interval_ms` ago, otherwise records that it's sent now.
+    fn should_help_peer(&mut self, author: Author, local_sync_info: &SyncInfo) -> bool {
+        if self.config.sync_info_help_interval_ms == 0 {
+            return true;
+        }
+        let interval = Duration::from_millis(self.config.sync_info_help_interval_ms);
+        let now = self.time_service.get_current_timestamp();
+        if let Some((sent_at, sent_sync_info)) = self.sync_info_sent.get(&author) {
+            let elapsed = now.checked_sub(*sent_at).unwrap_or_default();
+            if elapsed < interval && !local_sync_info.has_newer_certificates(sent_sync_info) {
+                return false;
+            }
+        }
+        self.sync_info_sent
+            .insert(author, (now, local_sync_info.clone()));
+        true
+    }
+
+    /// Holds back the proposal of the given round until `min_proposal_interval_ms` passed since
+    /// the previous one. Returns false if the round is over by then and the proposal is stale.
+    async fn wait_min_proposal_interval(&mut self, round: Round) -> bool {
+        let min_interval = Duration::from_millis(self.config.min_proposal_interval_ms);
+        if let Some(last_proposal_time) = self.last_proposal_time {
+            let elapsed = self
+                .time_service
+                .get_current_timestamp()
+                .checked_sub(last_proposal_time)
+                .unwrap_or_default();
+            if elapsed < min_interval {
+                debug!(
+                    "Delaying the proposal for round {} by {:?}",
+                    round,
+                    min_interval - elapsed
+                );
+                self.time_service.sleep(min_interval - elapsed).await;
+                if self.round_state.current_round() != round {
+                    debug!("Round {} is over, dropping its proposal", round);
+                    return false;
+                }
+            }
+        }
+        self.last_proposal_time = Some(self.time_service.get_current_timestamp());
+        true
+    }
+
+    /// Returns `fanout` validators picked at random among the others.
+    fn gossip_peers(&self, fanout: usize) -> Vec<Author> {
+        let author = self.proposal_generator.author();
+        let peers: Vec<_> = self
+            .epoch_state
+            .verifier
+            .get_ordered_account_addresses_iter()
+            .filter(|peer| *peer != author)
+            .collect();
+        peers
+            .choose_multiple(&mut rand::thread_rng(), fanout)
+            .cloned()
+            .collect()
+    }
+
+    /// Forwards a proposal received for the first time to `fanout` random validators.
+    async fn forward_proposal(&mut self, proposal_msg: &ProposalMsg, fanout: usize) {
+        self.proposals_forwarded = self
+            .proposals_forwarded
+            .split_off(&self.round_state.current_round());
+        if !self
+            .proposals_forwarded
+            .entry(proposal_msg.round())
+            .or_default()
+            .insert(proposal_msg.proposal().id())
+        {
+            return;
+        }
+        self.network
+            .send_proposal(proposal_msg.clone(), self.gossip_peers(fanout))
+            .await;
+    }
+
+    /// Machine-parseable counterpart of the new round debug log.
+    fn new_round_log_entry(&self, new_round_event: &NewRoundEvent) -> StructuredLogEntry {
+        StructuredLogEntry::new_named(NEW_ROUND_LOG_EVENT)
+            .data("epoch", self.epoch_state.epoch)
+            .data("round", new_round_event.round)
+            .data("reason", format!("{:?}", new_round_event.reason))
+            .data(
+                "proposer",
+                self.proposer_election
+                    .get_valid_proposer(new_round_event.round)
+                    .to_string(),
+            )
+            .data("timeout_ms", new_round_event.timeout.as_millis() as u64)
+    }
+
     async fn generate_proposal(
         &mut self,
         new_round_event: NewRoundEvent,
     ) -> anyhow::Result<ProposalMsg> {
+        // Propose smaller blocks if execution and commit fall behind
+        let max_block_size = if self.under_backpressure() {
+            counters::BACKPRESSURE_PROPOSALS_COUNT.inc();
+            debug!(
+                "{} uncommitted blocks, limiting proposal to {} txns",
+                self.block_store.uncommitted_block_count(),
+                self.config.backpressure_block_size
+            );
+            self.config.backpressure_block_size
+        } else {
+            self.config.max_block_size
+        };
         // Proposal generator will ensure that at most one proposal is generated per round
         let proposal = self
             .proposal_generator
-            .generate_proposal(
+            .generate_proposal_with_max_block_size(
                 new_round_event.round,
                 self.round_state.current_round_deadline(),
+                max_block_size,
             )
             .await?;
-        let signed_proposal = self.safety_rules.sign_proposal(proposal)?;
+        let proposal = self.filter_payload(proposal);
+        let signed_proposal = self.sign_proposal_within_size_limit(proposal)?;
         self.txn_manager.trace_transactions(&signed_proposal);
         trace_edge!("parent_proposal", {"block", signed_proposal.parent_id()}, {"block", signed_proposal.id()});
         trace_event!("round_manager::generate_proposal", {"block", signed_proposal.id()});
@@ -292,11 +720,182 @@ impl RoundManager {
         ))
     }
 
+    /// Removes the transactions rejected by the payload filter, if any, from the proposal.
+    fn filter_payload(&self, proposal: BlockData) -> BlockData {
+        let (filter, payload) = match (self.payload_filter.as_ref(), proposal.payload()) {
+            (Some(filter), Some(payload)) => (filter, payload),
+            _ => return proposal,
+        };
+        let filtered: Vec<_> = payload
+            .iter()
+            .filter(|txn| filter.keep(txn))
+            .cloned()
+            .collect();
+        if filtered.len() == payload.len() {
+            return proposal;
+        }
+        debug!(
+            "[RoundManager] Payload filter removed {} of the {} txns proposed for round {}",
+            payload.len() - filtered.len(),
+            payload.len(),
+            proposal.round()
+        );
+        BlockData::new_proposal(
+            filtered,
+            self.proposal_generator.author(),
+            proposal.round(),
+            proposal.timestamp_usecs(),
+            proposal.quorum_cert().clone(),
+        )
+    }
+
+    /// Signs the proposal, halving its payload until the serialized proposal message fits in
+    /// `max_proposal_bytes`. An empty block is signed as is, whatever its size.
+    fn sign_proposal_within_size_limit(&mut self, mut proposal: BlockData) -> Result<Block> {
+        let sync_info = self.block_store.sync_info();
+        loop {
+            let signed_proposal = self.safety_rules.sign_proposal(proposal.clone())?;
+            let proposal_bytes = lcs::to_bytes(&ProposalMsg::new(
+                signed_proposal.clone(),
+                sync_info.clone(),
+            ))?
+            .len() as u64;
+            let payload = match proposal.payload() {
+                Some(payload) if proposal_bytes > self.config.max_proposal_bytes => payload,
+                _ => return Ok(signed_proposal),
+            };
+            if payload.is_empty() {
+                return Ok(signed_proposal);
+            }
+            counters::OVERSIZED_PROPOSALS_COUNT.inc();
+            error!(
+                "[RoundManager] Proposal for round {} is {} bytes, over the limit of {} bytes, dropping {} of its {} txns",
+                proposal.round(),
+                proposal_bytes,
+                self.config.max_proposal_bytes,
+                payload.len() - payload.len() / 2,
+                payload.len()
+            );
+            // keeping a prefix of the payload preserves the sequence number order of each sender
+            proposal = BlockData::new_proposal(
+                payload[..payload.len() / 2].to_vec(),
+                self.proposal_generator.author(),
+                proposal.round(),
+                proposal.timestamp_usecs(),
+                proposal.quorum_cert().clone(),
+            );
+        }
+    }
+
+    /// Returns the block this node would propose for the given round, without signing,
+    /// tracing or broadcasting it. Neither the round state nor the proposal generator's record
+    /// of proposed rounds is updated, so the preview can be repeated.
+    #[cfg(test)]
+    pub async fn preview_proposal(&mut self, round: Round) -> anyhow::Result<Block> {
+        let max_block_size = if self.under_backpressure() {
+            self.config.backpressure_block_size
+        } else {
+            self.config.max_block_size
+        };
+        let block_data = self
+            .proposal_generator
+            .preview_proposal(
+                round,
+                self.round_state.current_round_deadline(),
+                max_block_size,
+            )
+            .await?;
+        Ok(Block::new_unsigned_proposal(
+            self.filter_payload(block_data),
+        ))
+    }
+
+    fn under_backpressure(&self) -> bool {
+        self.block_store.uncommitted_block_count() > self.config.backpressure_pending_blocks
+    }
+
     /// Process a ProposalMsg, pre_process would bring all the dependencies and filter out invalid
     /// proposal, process_proposed_block would execute and decide whether to vote for it.
+    /// The proposal of the valid proposer is processed right away, the one of the backup proposer
+    /// only once `backup_proposal_delay_ms` passed without the valid proposer showing up.
     pub async fn process_proposal_msg(&mut self, proposal_msg: ProposalMsg) -> anyhow::Result<()> {
-        let block = self.pre_process_proposal(proposal_msg).await?;
-        self.process_proposed_block(block).await
+        self.ensure_not_shut_down()?;
+        let block = match self.pre_process_proposal(proposal_msg).await? {
+            Some(block) => block,
+            None => return Ok(()),
+        };
+        if !self.add_candidate_proposal(&block) {
+            return self.process_proposed_block(block).await;
+        }
+        debug!(
+            "[RoundManager] Received backup proposal {}, waiting for the valid proposer",
+            block
+        );
+        Ok(())
+    }
+
+    /// Keeps the proposal among the candidates of its round and returns if it's a backup
+    /// proposal. The wake up for voting for the backup proposal is scheduled when the first
+    /// backup proposal of the round is received.
+    fn add_candidate_proposal(&mut self, block: &Block) -> bool {
+        let round = block.round();
+        self.candidate_proposals = self.candidate_proposals.split_off(&round);
+        let is_backup = self.proposer_election.is_backup_proposal(block);
+        let proposer_election = &self.proposer_election;
+        let candidates = self.candidate_proposals.entry(round).or_default();
+        let first_backup = is_backup
+            && !candidates
+                .iter()
+                .any(|b| proposer_election.is_backup_proposal(b));
+        if !candidates.iter().any(|b| b.id() == block.id()) {
+            candidates.push(block.clone());
+        }
+        if first_backup {
+            self.time_service.run_after(
+                Duration::from_millis(self.config.backup_proposal_delay_ms),
+                SendTask::make(self.backup_proposal_sender.clone(), round),
+            );
+        }
+        is_backup
+    }
+
+    /// Selects the proposal to vote for among the candidates of the round: the proposal of the
+    /// valid proposer if any, otherwise the one of the backup proposer. Ties (in case of an
+    /// equivocation) are broken by the block id, so that the validators having received the same
+    /// proposals select the same one.
+    fn select_candidate_proposal(&self, round: Round) -> Option<&Block> {
+        self.candidate_proposals.get(&round).and_then(|candidates| {
+            candidates
+                .iter()
+                .min_by_key(|b| (self.proposer_election.is_backup_proposal(b), b.id()))
+        })
+    }
+
+    /// Votes for the backup proposal of the round in case the proposal of the valid proposer
+    /// wasn't received. Triggered `backup_proposal_delay_ms` after the first backup proposal of the
+    /// round was received.
+    pub async fn process_backup_proposal_timeout(&mut self, round: Round) -> anyhow::Result<()> {
+        self.ensure_not_shut_down()?;
+        ensure!(
+            round == self.round_state.current_round(),
+            "[RoundManager] Backup proposal timeout of round {} is stale, current round is {}",
+            round,
+            self.round_state.current_round()
+        );
+        let proposal = match self.select_candidate_proposal(round) {
+            Some(proposal) if self.proposer_election.is_backup_proposal(proposal) => {
+                proposal.clone()
+            }
+            _ => {
+                debug!(
+                    "[RoundManager] Proposal of the valid proposer of round {} received, ignore the backup",
+                    round
+                );
+                return Ok(());
+            }
+        };
+        counters::BACKUP_PROPOSALS_COUNT.inc();
+        self.process_proposed_block(proposal).await
     }
 
     /// The function is responsible for processing the incoming proposals and the Quorum
@@ -305,8 +904,18 @@ impl RoundManager {
     /// and fetch all the blocks from the committed state to the HQC
     /// 2. forwarding the proposals to the ProposerElection queue,
     /// which is going to eventually trigger one winning proposal per round
-    async fn pre_process_proposal(&mut self, proposal_msg: ProposalMsg) -> Result<Block> {
+    /// Returns None for our own proposal looped back to us once it's already processed.
+    async fn pre_process_proposal(&mut self, proposal_msg: ProposalMsg) -> Result<Option<Block>> {
         trace_event!("round_manager::pre_process_proposal", {"block", proposal_msg.proposal().id()});
+        if proposal_msg.proposer() == self.proposal_generator.author()
+            && self.block_store.block_exists(proposal_msg.proposal().id())
+        {
+            debug!(
+                "[RoundManager] Ignore our own proposal {} that is already processed",
+                proposal_msg.proposal()
+            );
+            return Ok(None);
+        }
         // RoundState is going to be updated with all the proposal certificates later,
         // but it's known that the round_state's round is not going to decrease so we can already
         // filter out the proposals from old rounds.
@@ -317,46 +926,175 @@ impl RoundManager {
             proposal_msg.round(),
             current_round,
         );
+        let valid_proposer = self
+            .proposer_election
+            .is_valid_proposal(proposal_msg.proposal())
+            || self
+                .proposer_election
+                .is_backup_proposal(proposal_msg.proposal());
+        if !valid_proposer {
+            // frequent proposals for the current round might mean a disagreement on the
+            // proposer election, e.g. due to clock or view differences
+            let round_matched = if proposal_msg.round() == current_round {
+                "current_round"
+            } else {
+                "future_round"
+            };
+            counters::INVALID_PROPOSER_COUNT
+                .with_label_values(&[round_matched])
+                .inc();
+            warn!(
+                "[RoundManager] Proposal from invalid proposer {} for round {} ({})",
+                proposal_msg.proposer(),
+                proposal_msg.round(),
+                round_matched,
+            );
+        }
         ensure!(
-            self.proposer_election
-                .is_valid_proposal(proposal_msg.proposal()),
+            valid_proposer,
             "[RoundManager] Proposer {} for block {} is not a valid proposer for this round",
             proposal_msg.proposer(),
             proposal_msg.proposal()
         );
-        self.sync_up(proposal_msg.sync_info(), proposal_msg.proposer(), true)
+        self.detect_equivocation(proposal_msg.proposal());
+        if let BroadcastStrategy::Gossip { fanout } = self.config.broadcast_strategy {
+            self.forward_proposal(&proposal_msg, fanout).await;
+        }
+        if let Err(e) = self
+            .sync_up(proposal_msg.sync_info(), proposal_msg.proposer(), true)
             .await
-            .context("[RoundManager] Process proposal")?;
+        {
+            // The round might still catch up with the proposal through other certificates.
+            if proposal_msg.round() > self.round_state.current_round() {
+                self.buffer_future_proposal(proposal_msg);
+            }
+            return Err(e).context("[RoundManager] Process proposal");
+        }
 
         // round_state may catch up with the SyncInfo, check again
         let current_round = self.round_state.current_round();
+        if proposal_msg.round() > current_round {
+            let round = proposal_msg.round();
+            self.buffer_future_proposal(proposal_msg);
+            bail!(
+                "[RoundManager] Proposal round {} is ahead of current round {} after sync, buffered",
+                round,
+                current_round
+            );
+        }
         ensure!(
             proposal_msg.round() == current_round,
             "[RoundManager] Proposal round doesn't match current round after sync"
         );
-        Ok(proposal_msg.take_proposal())
+        Ok(Some(proposal_msg.take_proposal()))
+    }
+
+    /// Keeps a proposal for a future round until the current round catches up with it.
+    /// In case the buffer is full, the proposals of the lowest rounds are preferred.
+    fn buffer_future_proposal(&mut self, proposal_msg: ProposalMsg) {
+        self.future_proposals = self
+            .future_proposals
+            .split_off(&self.round_state.current_round());
+        let round = proposal_msg.round();
+        if self.future_proposals.contains_key(&round) {
+            return;
+        }
+        if self.future_proposals.len() >= self.config.max_future_proposals {
+            match self.future_proposals.keys().next_back() {
+                Some(&highest_round) if highest_round > round => {
+                    self.future_proposals.remove(&highest_round);
+                }
+                _ => {
+                    counters::FUTURE_PROPOSALS_DROPPED_COUNT.inc();
+                    return;
+                }
+            }
+        }
+        self.future_proposals.insert(round, proposal_msg);
+    }
+
+    /// Re-examines the buffered proposal of the current round (if any) and evicts the ones of the
+    /// older rounds. Supposed to be called after processing an event that might have advanced
+    /// the round.
+    pub async fn process_future_proposals(&mut self) {
+        if self.shut_down {
+            return;
+        }
+        self.future_proposals = self
+            .future_proposals
+            .split_off(&self.round_state.current_round());
+        if let Some(proposal_msg) = self
+            .future_proposals
+            .remove(&self.round_state.current_round())
+        {
+            debug!("Re-examining buffered {}", proposal_msg);
+            if let Err(e) = self.process_proposal_msg(proposal_msg).await {
+                warn!(
+                    "[RoundManager] Failed to process buffered proposal: {:?}",
+                    e
+                );
+            }
+        }
+    }
+
+    /// Reports a proposer that sends two different proposals for the same round.
+    /// It only serves as an audit trail and doesn't affect the processing of the proposal.
+    fn detect_equivocation(&mut self, proposal: &Block) {
+        let author = match proposal.author() {
+            Some(author) => author,
+            None => return,
+        };
+        // Proposals of the old rounds are not going to be processed anyway.
+        self.proposals_seen = self.proposals_seen.split_off(&(
+            self.round_state.current_round(),
+            AccountAddress::new([0; AccountAddress::LENGTH]),
+        ));
+        match self.proposals_seen.entry((proposal.round(), author)) {
+            Entry::Vacant(entry) => {
+                entry.insert(proposal.id());
+            }
+            Entry::Occupied(entry) => {
+                let prev_id = *entry.get();
+                if prev_id != proposal.id() {
+                    counters::PROPOSER_EQUIVOCATION_COUNT.inc();
+                    security_log(SecurityEvent::InvalidConsensusProposal)
+                        .error("Proposer equivocation")
+                        .data(author)
+                        .data(proposal.round())
+                        .data(prev_id)
+                        .data(proposal.id())
+                        .log();
+                }
+            }
+        }
     }
 
     /// The function makes sure that it brings the missing dependencies from the QC and LedgerInfo
     /// of the given sync info and update the round_state with the certificates if succeed.
     /// Returns Error in case sync mgr failed to bring the missing dependencies.
     /// We'll try to help the remote if the SyncInfo lags behind and the flag is set.
+    /// Sync ups run one at a time as they borrow the RoundManager mutably, so the missing blocks
+    /// are never retrieved twice by concurrent calls: the next call finds them in the block store.
     pub async fn sync_up(
         &mut self,
         sync_info: &SyncInfo,
         author: Author,
         help_remote: bool,
-    ) -> anyhow::Result<()> {
+    ) -> Result<(), SyncUpError> {
         let local_sync_info = self.block_store.sync_info();
         if help_remote && local_sync_info.has_newer_certificates(&sync_info) {
-            counters::SYNC_INFO_MSGS_SENT_COUNT.inc();
-            debug!(
-                "Peer {} has stale state {}, send it back {}",
-                author.short_str(),
-                sync_info,
-                local_sync_info,
-            );
-            self.network.send_sync_info(local_sync_info.clone(), author);
+            if self.should_help_peer(author, &local_sync_info) {
+                counters::SYNC_INFO_MSGS_SENT_COUNT.inc();
+                debug!(
+                    "Peer {} has stale state {}, send it back {}",
+                    author.short_str(),
+                    sync_info,
+                    local_sync_info,
+                );
+                self.network.send_sync_info(local_sync_info.clone(), author);
+            } else {
+                counters::SYNC_INFO_MSGS_SUPPRESSED_COUNT.inc();
+            }
         }
         if sync_info.has_newer_certificates(&local_sync_info) {
             debug!(
@@ -365,6 +1103,14 @@ impl RoundManager {
                 author.short_str(),
                 sync_info
             );
+            // A SyncInfo of another epoch can't be verified against our validator set, it's not
+            // a sign of a misbehaving peer.
+            if sync_info.epoch() != self.epoch_state.epoch {
+                return Err(SyncUpError::EpochMismatch {
+                    local: self.epoch_state.epoch,
+                    remote: sync_info.epoch(),
+                });
+            }
             // Some information in SyncInfo is ahead of what we have locally.
             // First verify the SyncInfo (didn't verify it in the yet).
             sync_info
@@ -374,28 +1120,89 @@ impl RoundManager {
                         .error(&e)
                         .data(&sync_info)
                         .log();
-                    e
+                    self.report_invalid_peer(author, "invalid sync info");
+                    SyncUpError::VerificationFailed(e)
                 })?;
+            self.highest_observed_round =
+                self.highest_observed_round.max(sync_info.highest_round());
+            // e.g. a TC newer than ours, but for a round that's already committed
+            let committed_round = self.block_store.root().round();
+            if sync_info.highest_round() <= committed_round {
+                return Err(SyncUpError::StaleSyncInfo {
+                    round: sync_info.highest_round(),
+                    committed_round,
+                });
+            }
+            if sync_info.highest_certified_round() <= local_sync_info.highest_certified_round()
+                && sync_info.highest_commit_round() <= local_sync_info.highest_commit_round()
+            {
+                // Only the TC is newer, there are no missing blocks to retrieve.
+                if let Some(tc) = sync_info.highest_timeout_certificate() {
+                    self.block_store
+                        .insert_timeout_certificate(Arc::new(tc.clone()))
+                        .map_err(SyncUpError::ProcessingFailed)?;
+                }
+                return self
+                    .process_certificates()
+                    .await
+                    .map_err(SyncUpError::ProcessingFailed);
+            }
+            // The block retrievals are bounded by a deadline (see `create_block_retriever`),
+            // the state sync and the commits are never cancelled.
             self.block_store
                 .add_certs(&sync_info, self.create_block_retriever(author))
                 .await
                 .map_err(|e| {
                     warn!("Fail to sync up to {}: {:?}", sync_info, e);
-                    e
+                    SyncUpError::RetrievalFailed(e)
                 })?;
+            if let Some(sink) = self.peer_score_sink.as_ref() {
+                sink.report_useful(author);
+            }
 
             // Update safety rules and round_state and potentially start a new round.
-            self.process_certificates().await?;
+            self.process_certificates()
+                .await
+                .map_err(SyncUpError::ProcessingFailed)?;
         }
         Ok(())
     }
 
+    /// Returns true if `sync_up` to the SyncInfo would bring certificates newer than the local
+    /// ones. Read only, the SyncInfo isn't verified.
+    #[cfg(test)]
+    pub fn would_advance(&self, sync_info: &SyncInfo) -> bool {
+        sync_info.has_newer_certificates(&self.block_store.sync_info())
+    }
+
+    /// Returns the round the node would be in after `sync_up` to the SyncInfo succeeds. Read
+    /// only, the SyncInfo isn't verified.
+    #[cfg(test)]
+    pub fn projected_round(&self, sync_info: &SyncInfo) -> Round {
+        let current_round = self.round_state.current_round();
+        if self.would_advance(sync_info) {
+            current_round.max(sync_info.highest_round() + 1)
+        } else {
+            current_round
+        }
+    }
+
+    /// Returns true if the node participates in consensus, false while it's still catching up:
+    /// the highest round of its SyncInfo must be within `max_synced_round_gap` rounds of the
+    /// highest round observed from peers.
+    #[cfg(test)]
+    pub fn is_synced(&self) -> bool {
+        self.block_store.sync_info().highest_round() + self.config.max_synced_round_gap
+            >= self.highest_observed_round
+    }
+
     /// Process the SyncInfo sent by peers to catch up to latest state.
     pub async fn process_sync_info_msg(
         &mut self,
         sync_info: SyncInfo,
         peer: Author,
     ) -> anyhow::Result<()> {
+        self.ensure_not_shut_down()?;
         debug!("Received a sync info msg: {}", sync_info);
         counters::SYNC_INFO_MSGS_RECEIVED_COUNT.inc();
         // To avoid a ping-pong cycle between two peers that move forward together.
@@ -411,14 +1218,46 @@ impl RoundManager {
     /// 2) In case a validator didn't vote yet but has a secondary proposal, it executes this
     /// proposal and votes.
     /// 3) If neither primary nor secondary proposals are available, vote for a NIL block.
+    /// In the `RepeatLastOrAbstain` timeout mode a validator that didn't vote in this round
+    /// broadcasts a TimeoutMsg carrying only its round signature instead of voting for a NIL block.
     pub async fn process_local_timeout(&mut self, round: Round) -> anyhow::Result<()> {
+        self.ensure_not_shut_down()?;
         ensure!(
             self.round_state.process_local_timeout(round),
             "[RoundManager] local timeout is stale"
         );
+        self.timeout_round(round).await
+    }
+
+    /// Time out the current round right away, without waiting for the local timeout to fire and
+    /// without rescheduling it.
+    #[cfg(test)]
+    pub async fn force_timeout(&mut self, round: Round) -> anyhow::Result<()> {
+        self.ensure_not_shut_down()?;
+        ensure!(
+            round == self.round_state.current_round(),
+            "[RoundManager] forced timeout of round {} while in round {}",
+            round,
+            self.round_state.current_round()
+        );
+        self.timeout_round(round).await
+    }
+
+    /// Repeats the vote of the timed out round or produces a backup one and broadcasts it.
+    async fn timeout_round(&mut self, round: Round) -> anyhow::Result<()> {
+        // Don't hold back the notifications of the committed blocks while there's no progress
+        if let Err(e) = self.txn_manager.flush().await {
+            error!(
+                "[RoundManager] Failed to notify mempool of rejected txns: {:?}",
+                e
+            );
+        }
 
         let (use_last_vote, mut timeout_vote) = match self.round_state.vote_sent() {
             Some(vote) if vote.vote_data().proposed().round() == round => (true, vote),
+            _ if self.config.timeout_mode == TimeoutMode::RepeatLastOrAbstain => {
+                return self.broadcast_timeout(round).await;
+            }
             _ => {
                 // Didn't vote in this round yet, generate a backup vote
                 let nil_block = self.proposal_generator.generate_nil_block(round)?;
@@ -451,12 +1290,42 @@ impl RoundManager {
         Ok(())
     }
 
+    /// Sign the timeout of the given round and broadcast it without voting.
+    async fn broadcast_timeout(&mut self, round: Round) -> anyhow::Result<()> {
+        warn!(
+            "Round {} timed out: didn't vote at this round, expected round proposer was {:?}, broadcasting the timeout to all replicas",
+            round,
+            self.proposer_election.get_valid_proposer(round),
+        );
+        let timeout = Timeout::new(self.epoch_state.epoch, round);
+        let signature = self
+            .safety_rules
+            .sign_timeout(&timeout)
+            .context("[RoundManager] SafetyRules signs timeout")?;
+        let timeout_msg = TimeoutMsg::new(
+            timeout,
+            self.proposal_generator.author(),
+            signature,
+            self.block_store.sync_info(),
+        );
+        self.network.broadcast_timeout(timeout_msg).await;
+        Ok(())
+    }
+
     /// This function is called only after all the dependencies of the given QC have been retrieved.
     async fn process_certificates(&mut self) -> anyhow::Result<()> {
         let sync_info = self.block_store.sync_info();
         self.safety_rules.update(sync_info.highest_quorum_cert())?;
         let consensus_state = self.safety_rules.consensus_state()?;
         counters::PREFERRED_BLOCK_ROUND.set(consensus_state.preferred_round() as i64);
+        self.record_consensus_state(consensus_state);
+        self.notify_commit();
+        // evict the blocks pruned by a commit: only the root and its descendants remain
+        let block_store = &self.block_store;
+        self.block_retrieval_cache
+            .lock()
+            .unwrap()
+            .retain(|id, _| block_store.path_from_root(*id).is_some());
 
         if let Some(new_round_event) = self.round_state.process_certificates(sync_info) {
             self.process_new_round_event(new_round_event).await;
@@ -464,6 +1333,29 @@ impl RoundManager {
         Ok(())
     }
 
+    /// Notifies the commit subscriber (if any) in case the highest commit cert has advanced since
+    /// the last notification. The notification is dropped rather than stalling consensus if the
+    /// subscriber can't keep up.
+    fn notify_commit(&mut self) {
+        let commit_cert = self.block_store.highest_commit_cert();
+        let commit_info = commit_cert.commit_info();
+        if commit_info.round() <= self.last_notified_commit_round {
+            return;
+        }
+        self.last_notified_commit_round = commit_info.round();
+        if let Some(commit_notifier) = self.commit_notifier.as_mut() {
+            let committed_block = CommittedBlockInfo {
+                block_id: commit_info.id(),
+                round: commit_info.round(),
+                ledger_info: commit_cert.ledger_info().clone(),
+            };
+            if let Err(e) = commit_notifier.try_send(committed_block) {
+                counters::COMMIT_NOTIFICATIONS_DROPPED_COUNT.inc();
+                warn!("Failed to send commit notification: {:?}", e);
+            }
+        }
+    }
+
     /// This function processes a proposal that was chosen as a representative of its round:
     /// 1. Add it to a block store.
     /// 2. Try to vote for it following the safety rules.
@@ -480,19 +1372,46 @@ impl RoundManager {
 
         let proposal_round = proposal.round();
 
+        // Only proposals are bounded: the NIL blocks of the timeouts must still be voted for so
+        // that a TC can form while the commits are stalled.
+        let pending_blocks = self
+            .block_store
+            .path_from_root(proposal.parent_id())
+            .map_or(0, |path| path.len());
+        ensure!(
+            pending_blocks < self.config.max_pending_blocks,
+            "[RoundManager] Not voting for {}: it extends {} uncommitted blocks",
+            proposal,
+            pending_blocks,
+        );
+
         let vote = self
             .execute_and_vote(proposal)
             .await
             .context("[RoundManager] Process proposal")?;
 
-        let recipients = self
+        let mut recipients = vec![];
+        for offset in 1..=self.config.vote_upcoming_leaders.max(1) {
+            let leader = self
+                .proposer_election
+                .get_valid_proposer(proposal_round + offset);
+            if !recipients.contains(&leader) {
+                recipients.push(leader);
+            }
+        }
+        if let Some(backup) = self
             .proposer_election
-            .get_valid_proposer(proposal_round + 1);
+            .get_backup_proposer(proposal_round + 1)
+        {
+            if !recipients.contains(&backup) {
+                recipients.push(backup);
+            }
+        }
         debug!("{}Voted: {} {}", Fg(Green), Fg(Reset), vote);
 
         self.round_state.record_vote(vote.clone());
         let vote_msg = VoteMsg::new(vote, self.block_store.sync_info());
-        self.network.send_vote(vote_msg, vec![recipients]).await;
+        self.network.send_vote(vote_msg, recipients).await;
         Ok(())
     }
 
@@ -565,15 +1484,26 @@ impl RoundManager {
     /// * save the updated state to consensus DB
     /// * return a VoteMsg with the LedgerInfo to be committed in case the vote gathers QC.
     ///
-    /// This function assumes that it might be called from different tasks concurrently.
+    /// The block is executed synchronously while holding the RoundManager exclusively, so the
+    /// executions of racing proposals never overlap and don't need a concurrency limit.
     async fn execute_and_vote(&mut self, proposed_block: Block) -> anyhow::Result<Vote> {
         trace_code_block!("round_manager::execute_and_vote", {"block", proposed_block.id()});
+        let block_type = if proposed_block.is_nil_block() {
+            "nil"
+        } else {
+            "proposal"
+        };
+        let execution_start = Instant::now();
         let executed_block = self
             .block_store
             .execute_and_insert_block(proposed_block)
             .context("[RoundManager] Failed to execute_and_insert the block")?;
+        counters::BLOCK_EXECUTION_S
+            .with_label_values(&[block_type])
+            .observe(execution_start.elapsed().as_secs_f64());
         // notify mempool about failed txn
         let compute_result = executed_block.compute_result();
+        let commit_start = Instant::now();
         if let Err(e) = self
             .txn_manager
             .commit(executed_block.block(), compute_result)
@@ -584,6 +1514,9 @@ impl RoundManager {
                 e
             );
         }
+        counters::TXN_MANAGER_COMMIT_S
+            .with_label_values(&[block_type])
+            .observe(commit_start.elapsed().as_secs_f64());
         let block = executed_block.block();
 
         // Checking round_state round again, because multiple proposed_block can now race
@@ -603,6 +1536,16 @@ impl RoundManager {
             self.round_state.current_round()
         );
 
+        if let Some(vote_policy) = &self.vote_policy {
+            if !block.is_nil_block() && !vote_policy.should_vote(block, compute_result) {
+                counters::VOTES_ABSTAINED_COUNT.inc();
+                bail!(
+                    "[RoundManager] Abstain from voting for {} because of the local vote policy",
+                    block
+                );
+            }
+        }
+
         let parent_block = self
             .block_store
             .get_block(executed_block.parent_id())
@@ -636,6 +1579,7 @@ impl RoundManager {
 
         let consensus_state = self.safety_rules.consensus_state()?;
         counters::LAST_VOTE_ROUND.set(consensus_state.last_voted_round() as i64);
+        self.record_consensus_state(consensus_state);
         self.storage
             .save_vote(&vote)
             .context("[RoundManager] Fail to persist last vote")?;
@@ -646,9 +1590,12 @@ impl RoundManager {
     /// Upon new vote:
     /// 1. Filter out votes for rounds that should not be processed by this validator (to avoid
     /// potential attacks).
-    /// 2. Add the vote to the store and check whether it finishes a QC.
-    /// 3. Once the QC successfully formed, notify the RoundState.
+    /// 2. Sync up to the SyncInfo of the vote, unless the voted block is already present and the
+    /// SyncInfo isn't newer than ours.
+    /// 3. Add the vote to the store and check whether it finishes a QC.
+    /// 4. Once the QC successfully formed, notify the RoundState.
     pub async fn process_vote(&mut self, vote_msg: VoteMsg) -> anyhow::Result<()> {
+        self.ensure_not_shut_down()?;
         trace_code_block!("round_manager::process_vote", {"block", vote_msg.proposed_block_id()});
         // Check whether this validator is a valid recipient of the vote.
         if !vote_msg.vote().is_timeout() {
@@ -656,20 +1603,118 @@ impl RoundManager {
             let next_round = vote_msg.vote().vote_data().proposed().round() + 1;
             ensure!(
                 self.proposer_election
-                    .is_valid_proposer(self.proposal_generator.author(), next_round),
+                    .is_candidate_proposer(self.proposal_generator.author(), next_round),
                 "[RoundManager] Received {}, but I am not a valid proposer for round {}, ignore.",
                 vote_msg,
                 next_round
             );
         }
-        self.sync_up(vote_msg.sync_info(), vote_msg.vote().author(), true)
-            .await
-            .context("[RoundManager] Stop processing vote")?;
+        // Fast path for the common case of the votes for a block we already have, e.g. our own
+        // proposal: there's nothing to sync up to, the voter itself catches up with the
+        // SyncInfo of the next proposal.
+        let is_fast_path = self.block_store.block_exists(vote_msg.proposed_block_id())
+            && !vote_msg
+                .sync_info()
+                .has_newer_certificates(&self.block_store.sync_info());
+        if !is_fast_path {
+            self.sync_up(vote_msg.sync_info(), vote_msg.vote().author(), true)
+                .await
+                .context("[RoundManager] Stop processing vote")?;
+        }
         self.add_vote(vote_msg.vote())
             .await
             .context("[RoundManager] Add a new vote")
     }
 
+    /// Process a batch of votes, e.g., the ones re-broadcast by the peers after a restart.
+    /// Votes for blocks that already have a QC are dropped before any further processing, the
+    /// rest is grouped by the voted block. A single sync up is performed for the highest SyncInfo
+    /// carried in the batch before all the votes are added to the pending votes.
+    #[cfg(test)]
+    pub async fn process_votes(&mut self, vote_msgs: Vec<VoteMsg>) -> anyhow::Result<()> {
+        self.ensure_not_shut_down()?;
+        let mut votes_by_block: BTreeMap<(Round, HashValue), Vec<VoteMsg>> = BTreeMap::new();
+        for vote_msg in vote_msgs {
+            let proposed = vote_msg.vote().vote_data().proposed();
+            if self
+                .block_store
+                .get_quorum_cert_for_block(proposed.id())
+                .is_some()
+            {
+                continue;
+            }
+            if !vote_msg.vote().is_timeout() {
+                let next_round = proposed.round() + 1;
+                if !self
+                    .proposer_election
+                    .is_candidate_proposer(self.proposal_generator.author(), next_round)
+                {
+                    warn!(
+                        "[RoundManager] Received {}, but I am not a valid proposer for round {}, ignore.",
+                        vote_msg, next_round
+                    );
+                    continue;
+                }
+            }
+            votes_by_block
+                .entry((proposed.round(), proposed.id()))
+                .or_default()
+                .push(vote_msg);
+        }
+
+        let highest_sync_vote = match votes_by_block.values().flatten().max_by_key(|vote_msg| {
+            (
+                vote_msg.sync_info().highest_round(),
+                vote_msg.sync_info().highest_commit_round(),
+            )
+        }) {
+            Some(vote_msg) => vote_msg,
+            None => return Ok(()),
+        };
+        self.sync_up(
+            highest_sync_vote.sync_info(),
+            highest_sync_vote.vote().author(),
+            true,
+        )
+        .await
+        .context("[RoundManager] Stop processing votes")?;
+
+        for vote_msg in votes_by_block.values().flatten() {
+            if let Err(e) = self.add_vote(vote_msg.vote()).await {
+                warn!("[RoundManager] Failed to add vote {}: {:?}", vote_msg, e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Reports how much voting power the pending votes for the given block of the current round
+    /// have gathered and how much is still needed for a QC, as well as the progress of the
+    /// timeout signatures aggregation of its round.
+    #[cfg(test)]
+    pub fn vote_progress(&self, block_id: HashValue) -> Option<VoteProgress> {
+        self.round_state
+            .vote_progress(block_id, &self.epoch_state.verifier)
+    }
+
+    /// Upon new timeout message sync up with its author and add the round signature to the
+    /// pending votes, a new round starts in case it completes a TC.
+    pub async fn process_timeout_msg(&mut self, timeout_msg: TimeoutMsg) -> anyhow::Result<()> {
+        self.ensure_not_shut_down()?;
+        self.sync_up(timeout_msg.sync_info(), timeout_msg.author(), true)
+            .await
+            .context("[RoundManager] Stop processing timeout msg")?;
+        debug!("Add timeout: {}", timeout_msg);
+        match self.round_state.insert_timeout(
+            timeout_msg.timeout(),
+            timeout_msg.author(),
+            timeout_msg.signature().clone(),
+            &self.epoch_state.verifier,
+        ) {
+            VoteReceptionResult::NewTimeoutCertificate(tc) => self.new_tc_aggregated(tc).await,
+            _ => Ok(()),
+        }
+    }
+
     /// Add a vote to the pending votes.
     /// If a new QC / TC is formed then
     /// 1) fetch missing dependencies if required, and then
@@ -685,6 +1730,14 @@ impl RoundManager {
         {
             return Ok(());
         }
+        // The vote only carries the block id, NIL blocks are recognized if we have them locally
+        if self
+            .block_store
+            .get_block(block_id)
+            .map_or(false, |block| block.is_nil_block())
+        {
+            counters::NIL_VOTE_RECEIVED_COUNT.inc();
+        }
         // Add the vote and check whether it completes a new QC or a TC
         match self
             .round_state
@@ -712,6 +1765,7 @@ impl RoundManager {
         qc: Arc<QuorumCert>,
         preferred_peer: Author,
     ) -> anyhow::Result<()> {
+        self.notify_cert_observer("on_qc", |observer| observer.on_qc(&qc, preferred_peer));
         self.block_store
             .insert_quorum_cert(&qc, &mut self.create_block_retriever(preferred_peer))
             .await
@@ -720,6 +1774,7 @@ impl RoundManager {
     }
 
     async fn new_tc_aggregated(&mut self, tc: Arc<TimeoutCertificate>) -> anyhow::Result<()> {
+        self.notify_cert_observer("on_tc", |observer| observer.on_tc(&tc));
         self.block_store
             .insert_timeout_certificate(tc.clone())
             .context("[RoundManager] Failed to process a newly aggregated TC")?;
@@ -728,42 +1783,94 @@ impl RoundManager {
         self.process_certificates().await
     }
 
+    /// Reports the peer whose message failed verification to the peer score sink, if any.
+    pub fn report_invalid_peer(&self, peer: Author, reason: &str) {
+        if let Some(sink) = self.peer_score_sink.as_ref() {
+            sink.report_invalid(peer, reason);
+        }
+    }
+
+    fn notify_cert_observer(&self, callback: &str, f: impl FnOnce(&dyn CertObserver)) {
+        if let Some(observer) = self.cert_observer.as_ref() {
+            if panic::catch_unwind(AssertUnwindSafe(|| f(observer.as_ref()))).is_err() {
+                error!("CertObserver panicked in {}", callback);
+            }
+        }
+    }
+
     /// Retrieve a n chained blocks from the block store starting from
     /// an initial parent id, returning with <n (as many as possible) if
-    /// id or its ancestors can not be found.
-    ///
-    /// The current version of the function is not really async, but keeping it this way for
-    /// future possible changes.
+    /// id or its ancestors can not be found. If rate limiting is enabled, the requests from a peer
+    /// exceeding its rate limit are answered with `RateLimited` without being served.
+    /// With a nonzero `block_retrieval_serve_delay_ms` the response is constructed and sent in
+    /// the background after the delay, without holding up the processing of the other events.
     pub async fn process_block_retrieval(
-        &self,
+        &mut self,
         request: IncomingBlockRetrievalRequest,
     ) -> anyhow::Result<()> {
-        let mut blocks = vec![];
-        let mut status = BlockRetrievalStatus::Succeeded;
-        let mut id = request.req.block_id();
-        while (blocks.len() as u64) < request.req.num_blocks() {
-            if let Some(executed_block) = self.block_store.get_block(id) {
-                id = executed_block.parent_id();
-                blocks.push(executed_block.block().clone());
-            } else {
-                status = BlockRetrievalStatus::NotEnoughBlocks;
-                break;
-            }
+        let now = self.time_service.get_current_timestamp();
+        let rate_limited = match self.block_retrieval_limiter.as_mut() {
+            Some(limiter) => !limiter.try_acquire(request.requester, now),
+            None => false,
+        };
+        if rate_limited {
+            counters::BLOCK_RETRIEVAL_RATE_LIMITED_COUNT.inc();
+            warn!(
+                "Block retrieval request from {} is rate limited",
+                request.requester.short_str()
+            );
+            let response = BlockRetrievalResponse::new(BlockRetrievalStatus::RateLimited, vec![]);
+            return send_block_retrieval_response(response, request);
         }
 
-        if blocks.is_empty() {
-            status = BlockRetrievalStatus::IdNotFound;
+        let max_retrieval_blocks = self.config.max_retrieval_blocks;
+        let serve_delay = Duration::from_millis(self.config.block_retrieval_serve_delay_ms);
+        if serve_delay == Duration::from_secs(0) {
+            let response = block_retrieval_response(
+                &self.block_store,
+                &self.block_retrieval_cache,
+                &request,
+                max_retrieval_blocks,
+            );
+            return send_block_retrieval_response(response, request);
         }
+        let block_store = self.block_store.clone();
+        let cache = self.block_retrieval_cache.clone();
+        let sleep = self.time_service.sleep(serve_delay);
+        tokio::spawn(async move {
+            sleep.await;
+            let response =
+                block_retrieval_response(&block_store, &cache, &request, max_retrieval_blocks);
+            if let Err(e) = send_block_retrieval_response(response, request) {
+                warn!("{:?}", e);
+            }
+        });
+        Ok(())
+    }
 
-        let response = Box::new(BlockRetrievalResponse::new(status, blocks));
-        lcs::to_bytes(&ConsensusMsg::BlockRetrievalResponse(response))
-            .and_then(|bytes| {
-                request
-                    .response_sender
-                    .send(Ok(bytes.into()))
-                    .map_err(|e| lcs::Error::Custom(format!("{:?}", e)))
-            })
-            .context("[RoundManager] Failed to process block retrieval")
+    /// Stops processing the events of the ended epoch, the subsequent `process_*` calls fail with
+    /// `EpochEnded`. Mempool is notified of the rejected transactions still held back and the last
+    /// vote is persisted.
+    pub async fn shutdown(&mut self) {
+        if self.shut_down {
+            return;
+        }
+        self.shut_down = true;
+        if let Err(e) = self.txn_manager.flush().await {
+            error!(
+                "[RoundManager] Failed to notify mempool of rejected txns: {:?}",
+                e
+            );
+        }
+        if let Some(vote) = self.round_state.vote_sent() {
+            if let Err(e) = self.storage.save_vote(&vote) {
+                error!("[RoundManager] Failed to save the last vote: {:?}", e);
+            }
+        }
+        info!(
+            "[RoundManager] Shut down at the end of epoch {}",
+            self.epoch_state.epoch
+        );
     }
 
     /// To jump start new round with the current certificates we have.
@@ -778,6 +1885,35 @@ impl RoundManager {
         self.process_new_round_event(new_round_event).await;
     }
 
+    /// Returns the current values of the round latency histograms. The histograms are process
+    /// wide, they aren't reset at the start of an epoch.
+    #[cfg(test)]
+    pub fn timing_snapshot(&self) -> RoundTimingSnapshot {
+        RoundTimingSnapshot {
+            creation_to_receival: (&*counters::CREATION_TO_RECEIVAL_S).into(),
+            creation_to_qc: (&*counters::CREATION_TO_QC_S).into(),
+            vote_success_wait: (&*counters::VOTE_SUCCESS_WAIT_S).into(),
+        }
+    }
+
+    /// Returns the safety rules states recorded after the recent certificates processing and
+    /// votes, oldest first. Empty unless `consensus_state_history_size` is set.
+    #[cfg(test)]
+    pub fn recent_consensus_states(&self) -> Vec<ConsensusState> {
+        self.consensus_states.iter().cloned().collect()
+    }
+
+    fn record_consensus_state(&mut self, consensus_state: ConsensusState) {
+        let history_size = self.config.consensus_state_history_size;
+        if history_size == 0 {
+            return;
+        }
+        while self.consensus_states.len() >= history_size {
+            self.consensus_states.pop_front();
+        }
+        self.consensus_states.push_back(consensus_state);
+    }
+
     /// Inspect the current consensus state.
     #[cfg(test)]
     pub fn consensus_state(&mut self) -> ConsensusState {
@@ -791,4 +1927,100 @@ impl RoundManager {
     pub fn round_state(&self) -> &RoundState {
         &self.round_state
     }
+
+    /// Returns the time left until the current round times out, None if no round is active.
+    #[cfg(test)]
+    pub fn round_deadline_remaining(&self) -> Option<Duration> {
+        if self.shut_down || self.round_state.current_round() == 0 {
+            return None;
+        }
+        Some(
+            self.round_state
+                .current_round_deadline_timestamp()
+                .checked_sub(self.time_service.get_current_timestamp())
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Stops or resumes proposing in the rounds this validator leads, starting from the next
+    /// round. Voting and syncing are unaffected.
+    #[cfg(test)]
+    pub fn set_proposing_enabled(&mut self, enabled: bool) {
+        self.proposing_enabled = enabled;
+    }
+
+    /// Returns the valid proposer of the current round.
+    #[cfg(test)]
+    pub fn current_proposer(&self) -> Author {
+        self.proposer_for_round(self.round_state.current_round())
+    }
+
+    /// Returns the valid proposer of the given round in the current epoch.
+    #[cfg(test)]
+    pub fn proposer_for_round(&self, round: Round) -> Author {
+        self.proposer_election.get_valid_proposer(round)
+    }
+
+    /// Returns the nearest round after the current one this validator is the valid proposer of,
+    /// None if it doesn't lead any of the next `proposal_lookahead_rounds` rounds.
+    #[cfg(test)]
+    pub fn next_proposal_round(&self) -> Option<Round> {
+        let author = self.proposal_generator.author();
+        let current_round = self.round_state.current_round();
+        (1..=self.config.proposal_lookahead_rounds)
+            .filter_map(|offset| current_round.checked_add(offset))
+            .find(|round| self.proposer_election.is_valid_proposer(author, *round))
+    }
+}
+
+/// Collects the chain of blocks requested by the given block retrieval request, at most
+/// `max_retrieval_blocks` of them.
+fn block_retrieval_response(
+    block_store: &BlockStore,
+    cache: &Mutex<LruCache<HashValue, Block>>,
+    request: &IncomingBlockRetrievalRequest,
+    max_retrieval_blocks: u64,
+) -> BlockRetrievalResponse {
+    let (mut status, num_blocks) = if request.req.num_blocks() > max_retrieval_blocks {
+        (BlockRetrievalStatus::Truncated, max_retrieval_blocks)
+    } else {
+        (BlockRetrievalStatus::Succeeded, request.req.num_blocks())
+    };
+    let mut blocks = vec![];
+    let mut id = request.req.block_id();
+    let mut cache = cache.lock().unwrap();
+    while (blocks.len() as u64) < num_blocks {
+        if let Some(block) = cache.get(&id) {
+            counters::BLOCK_RETRIEVAL_CACHE_HIT_COUNT.inc();
+            id = block.parent_id();
+            blocks.push(block.clone());
+        } else if let Some(executed_block) = block_store.get_block(id) {
+            let block = executed_block.block().clone();
+            cache.put(id, block.clone());
+            id = block.parent_id();
+            blocks.push(block);
+        } else {
+            status = BlockRetrievalStatus::NotEnoughBlocks;
+            break;
+        }
+    }
+
+    if blocks.is_empty() {
+        status = BlockRetrievalStatus::IdNotFound;
+    }
+    BlockRetrievalResponse::new(status, blocks)
+}
+
+fn send_block_retrieval_response(
+    response: BlockRetrievalResponse,
+    request: IncomingBlockRetrievalRequest,
+) -> anyhow::Result<()> {
+    lcs::to_bytes(&ConsensusMsg::BlockRetrievalResponse(Box::new(response)))
+        .and_then(|bytes| {
+            request
+                .response_sender
+                .send(Ok(bytes.into()))
+                .map_err(|e| lcs::Error::Custom(format!("{:?}", e)))
+        })
+        .context("[RoundManager] Failed to process block retrieval")
 }
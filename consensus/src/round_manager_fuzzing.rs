@@ -11,13 +11,14 @@ use crate::{
     network::NetworkSender,
     network_interface::ConsensusNetworkSender,
     persistent_liveness_storage::{PersistentLivenessStorage, RecoveryData},
-    round_manager::RoundManager,
+    round_manager::{RoundManager, RoundManagerHooks},
     test_utils::{EmptyStateComputer, MockStorage, MockTransactionManager},
     util::mock_time_service::SimulatedTimeService,
 };
 use channel::{self, libra_channel, message_queues::QueueStyle};
 use consensus_types::proposal_msg::ProposalMsg;
 use futures::{channel::mpsc, executor::block_on};
+use libra_config::config::ConsensusConfig;
 use libra_types::{
     epoch_change::EpochChangeProof,
     epoch_state::EpochState,
@@ -146,6 +147,7 @@ fn create_node_for_fuzzing() -> RoundManager {
 
     // TODO: have two different nodes, one for proposing, one for accepting a proposal
     let proposer_election = Box::new(RotatingProposer::new(vec![signer.author()], 1));
+    let (backup_proposal_sender, _) = channel::new_test(1_024);
 
     // event processor
     RoundManager::new(
@@ -159,6 +161,9 @@ fn create_node_for_fuzzing() -> RoundManager {
         Box::new(MockTransactionManager::new(None)),
         storage,
         time_service,
+        backup_proposal_sender,
+        ConsensusConfig::default(),
+        RoundManagerHooks::default(),
     )
 }
 
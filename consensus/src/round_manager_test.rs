@@ -3,22 +3,30 @@
 
 use crate::{
     block_storage::{BlockReader, BlockStore},
+    counters,
     liveness::{
         proposal_generator::ProposalGenerator,
         proposer_election::ProposerElection,
         rotating_proposer_election::RotatingProposer,
-        round_state::{ExponentialTimeInterval, RoundState},
+        round_state::{ExponentialTimeInterval, NewRoundEvent, NewRoundReason, RoundState},
     },
     network::{IncomingBlockRetrievalRequest, NetworkSender},
     network_interface::{ConsensusMsg, ConsensusNetworkEvents, ConsensusNetworkSender},
     network_tests::{NetworkPlayground, TwinId},
     persistent_liveness_storage::{PersistentLivenessStorage, RecoveryData},
-    round_manager::RoundManager,
+    round_manager::{
+        CertObserver, CommittedBlockInfo, EpochEnded, PayloadFilter, PeerScoreSink, RoundManager,
+        RoundManagerHooks, RoundTimingSnapshot, SyncUpError, UnverifiedEvent, VerifiedEvent,
+        VotePolicy, NEW_ROUND_LOG_EVENT,
+    },
     test_utils::{
         consensus_runtime, timed_block_on, MockStateComputer, MockStorage, MockTransactionManager,
         TreeInserter,
     },
-    util::time_service::{ClockTimeService, TimeService},
+    util::{
+        mock_time_service::SimulatedTimeService,
+        time_service::{duration_since_epoch, ClockTimeService, TimeService},
+    },
 };
 use channel::{self, libra_channel, message_queues::QueueStyle};
 use consensus_types::{
@@ -27,23 +35,30 @@ use consensus_types::{
         Block,
     },
     block_retrieval::{BlockRetrievalRequest, BlockRetrievalStatus},
-    common::{Author, Payload},
+    common::{Author, Payload, Round},
     proposal_msg::ProposalMsg,
+    quorum_cert::QuorumCert,
     sync_info::SyncInfo,
     timeout::Timeout,
     timeout_certificate::TimeoutCertificate,
+    timeout_msg::TimeoutMsg,
+    vote::Vote,
     vote_msg::VoteMsg,
 };
+use executor_types::StateComputeResult;
 use futures::{
     channel::{mpsc, oneshot},
     executor::block_on,
     stream::select,
-    Stream, StreamExt, TryStreamExt,
+    FutureExt, Stream, StreamExt, TryStreamExt,
 };
+use libra_config::config::{BroadcastStrategy, ConsensusConfig, TimeoutMode};
 use libra_crypto::{hash::CryptoHash, HashValue};
 use libra_types::{
+    block_info::BlockInfo,
     epoch_state::EpochState,
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    transaction::SignedTransaction,
     validator_signer::ValidatorSigner,
     validator_verifier::{random_validator_verifier, ValidatorVerifier},
     waypoint::Waypoint,
@@ -53,7 +68,11 @@ use network::{
     protocols::network::Event,
 };
 use safety_rules::{ConsensusState, PersistentSafetyStorage, SafetyRulesManager};
-use std::{num::NonZeroUsize, sync::Arc, time::Duration};
+use std::{
+    num::NonZeroUsize,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 use tokio::runtime::Handle;
 
 /// Auxiliary struct that is setting up node environment for the test.
@@ -68,6 +87,8 @@ pub struct NodeSetup {
     all_events: Box<dyn Stream<Item = anyhow::Result<Event<ConsensusMsg>>> + Send + Unpin>,
     commit_cb_receiver: mpsc::UnboundedReceiver<LedgerInfoWithSignatures>,
     state_sync_receiver: mpsc::UnboundedReceiver<Payload>,
+    commit_notification_receiver: mpsc::Receiver<CommittedBlockInfo>,
+    config: ConsensusConfig,
     id: usize,
 }
 
@@ -87,6 +108,15 @@ impl NodeSetup {
         playground: &mut NetworkPlayground,
         executor: Handle,
         num_nodes: usize,
+    ) -> Vec<Self> {
+        Self::create_nodes_with_config(playground, executor, num_nodes, ConsensusConfig::default())
+    }
+
+    fn create_nodes_with_config(
+        playground: &mut NetworkPlayground,
+        executor: Handle,
+        num_nodes: usize,
+        config: ConsensusConfig,
     ) -> Vec<Self> {
         let (signers, validators) = random_validator_verifier(num_nodes, None, false);
         let proposer_author = signers[0].author();
@@ -116,6 +146,7 @@ impl NodeSetup {
                 storage,
                 initial_data,
                 safety_rules_manager,
+                config.clone(),
                 id,
             ));
             //id += 1;
@@ -136,6 +167,7 @@ impl NodeSetup {
         storage: Arc<MockStorage>,
         initial_data: RecoveryData,
         safety_rules_manager: SafetyRulesManager,
+        config: ConsensusConfig,
         id: usize,
     ) -> Self {
         let epoch_state = EpochState {
@@ -199,6 +231,8 @@ impl NodeSetup {
         let mut safety_rules = safety_rules_manager.client();
         let proof = storage.retrieve_epoch_change_proof(0).unwrap();
         safety_rules.initialize(&proof).unwrap();
+        let (commit_notification_sender, commit_notification_receiver) = mpsc::channel(16);
+        let (backup_proposal_sender, _) = channel::new_test(1_024);
 
         let mut round_manager = RoundManager::new(
             epoch_state,
@@ -211,6 +245,12 @@ impl NodeSetup {
             Box::new(MockTransactionManager::new(None)),
             storage.clone(),
             time_service,
+            backup_proposal_sender,
+            config.clone(),
+            RoundManagerHooks {
+                commit_notifier: Some(commit_notification_sender),
+                ..RoundManagerHooks::default()
+            },
         );
         block_on(round_manager.start(last_vote_sent));
         Self {
@@ -224,6 +264,8 @@ impl NodeSetup {
             all_events,
             commit_cb_receiver,
             state_sync_receiver,
+            commit_notification_receiver,
+            config,
             id,
         }
     }
@@ -241,6 +283,7 @@ impl NodeSetup {
             self.storage,
             recover_data,
             self.safety_rules_manager,
+            self.config,
             self.id,
         )
     }
@@ -265,6 +308,16 @@ impl NodeSetup {
         }
     }
 
+    pub async fn next_timeout(&mut self) -> TimeoutMsg {
+        match self.all_events.next().await.unwrap().unwrap() {
+            Event::Message((_, msg)) => match msg {
+                ConsensusMsg::TimeoutMsg(t) => *t,
+                msg => panic!("Unexpected Consensus Message: {:?}", msg),
+            },
+            _ => panic!("Unexpected Network Event"),
+        }
+    }
+
     pub async fn next_sync_info(&mut self) -> SyncInfo {
         match self.all_events.next().await.unwrap().unwrap() {
             Event::Message((_, msg)) => match msg {
@@ -408,7 +461,7 @@ fn no_vote_on_mismatch_round() {
                 .pre_process_proposal(good_proposal.clone())
                 .await
                 .unwrap(),
-            good_proposal.take_proposal()
+            Some(good_proposal.take_proposal())
         );
     });
 }
@@ -491,7 +544,39 @@ fn no_vote_on_invalid_proposer() {
                 .pre_process_proposal(good_proposal.clone())
                 .await
                 .unwrap(),
-            good_proposal.take_proposal(),
+            Some(good_proposal.take_proposal()),
+        );
+    });
+}
+
+#[test]
+fn invalid_proposer_counted() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 2);
+    let incorrect_proposer = nodes.pop().unwrap();
+    let mut node = nodes.pop().unwrap();
+    let genesis_qc = certificate_for_genesis();
+    let block_incorrect_proposer =
+        Block::new_proposal(vec![], 1, 1, genesis_qc.clone(), &incorrect_proposer.signer);
+    timed_block_on(&mut runtime, async {
+        let invalid_proposers = counters::INVALID_PROPOSER_COUNT
+            .with_label_values(&["current_round"])
+            .get();
+        let bad_proposal = ProposalMsg::new(
+            block_incorrect_proposer,
+            SyncInfo::new(genesis_qc.clone(), genesis_qc.clone(), None),
+        );
+        assert!(node
+            .round_manager
+            .pre_process_proposal(bad_proposal)
+            .await
+            .is_err());
+        assert!(
+            counters::INVALID_PROPOSER_COUNT
+                .with_label_values(&["current_round"])
+                .get()
+                > invalid_proposers
         );
     });
 }
@@ -525,7 +610,7 @@ fn new_round_on_timeout_certificate() {
                 .pre_process_proposal(skip_round_proposal.clone())
                 .await
                 .unwrap(),
-            skip_round_proposal.take_proposal()
+            Some(skip_round_proposal.take_proposal())
         );
         let old_good_proposal = ProposalMsg::new(
             correct_block.clone(),
@@ -561,6 +646,7 @@ fn response_on_block_retrieval() {
         // first verify that we can retrieve the block if it's in the tree
         let (tx1, rx1) = oneshot::channel();
         let single_block_request = IncomingBlockRetrievalRequest {
+            requester: node.signer.author(),
             req: BlockRetrievalRequest::new(block_id, 1),
             response_sender: tx1,
         };
@@ -583,6 +669,7 @@ fn response_on_block_retrieval() {
         // verify that if a block is not there, return ID_NOT_FOUND
         let (tx2, rx2) = oneshot::channel();
         let missing_block_request = IncomingBlockRetrievalRequest {
+            requester: node.signer.author(),
             req: BlockRetrievalRequest::new(HashValue::random(), 1),
             response_sender: tx2,
         };
@@ -606,6 +693,7 @@ fn response_on_block_retrieval() {
         // if asked for many blocks, return NOT_ENOUGH_BLOCKS
         let (tx3, rx3) = oneshot::channel();
         let many_block_request = IncomingBlockRetrievalRequest {
+            requester: node.signer.author(),
             req: BlockRetrievalRequest::new(block_id, 3),
             response_sender: tx3,
         };
@@ -631,6 +719,215 @@ fn response_on_block_retrieval() {
     });
 }
 
+#[test]
+/// A peer flooding block retrieval requests is rate limited once its burst is used up
+fn block_retrieval_rate_limited() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut config = ConsensusConfig::default();
+    config.block_retrieval_rate_per_sec = 1;
+    config.block_retrieval_burst = 5;
+    let mut node =
+        NodeSetup::create_nodes_with_config(&mut playground, runtime.handle().clone(), 1, config)
+            .pop()
+            .unwrap();
+    let genesis_id = node.block_store.root().id();
+    let flooder = ValidatorSigner::random([9; 32]).author();
+
+    timed_block_on(&mut runtime, async {
+        let mut statuses = vec![];
+        for requester in vec![flooder; 7]
+            .into_iter()
+            .chain(std::iter::once(node.signer.author()))
+        {
+            let (tx, rx) = oneshot::channel();
+            let request = IncomingBlockRetrievalRequest {
+                requester,
+                req: BlockRetrievalRequest::new(genesis_id, 1),
+                response_sender: tx,
+            };
+            node.round_manager
+                .process_block_retrieval(request)
+                .await
+                .unwrap();
+            let bytes = rx.await.unwrap().unwrap();
+            match lcs::from_bytes(&bytes) {
+                Ok(ConsensusMsg::BlockRetrievalResponse(resp)) => statuses.push(resp.status()),
+                _ => panic!("block retrieval failure"),
+            }
+        }
+        // the burst is served, the rest of the flood is rejected without affecting other peers
+        let mut expected = vec![BlockRetrievalStatus::Succeeded; 5];
+        expected.extend(vec![BlockRetrievalStatus::RateLimited; 2]);
+        expected.push(BlockRetrievalStatus::Succeeded);
+        assert_eq!(statuses, expected);
+    });
+}
+
+#[test]
+/// Requests for a longer chain than the retrieval cap are served with the beginning of the chain
+fn block_retrieval_truncated() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut config = ConsensusConfig::default();
+    config.max_retrieval_blocks = 1;
+    let mut node =
+        NodeSetup::create_nodes_with_config(&mut playground, runtime.handle().clone(), 1, config)
+            .pop()
+            .unwrap();
+
+    let genesis_qc = certificate_for_genesis();
+    let block = Block::new_proposal(vec![], 1, 1, genesis_qc.clone(), &node.signer);
+    let block_id = block.id();
+    let proposal = ProposalMsg::new(block, SyncInfo::new(genesis_qc.clone(), genesis_qc, None));
+
+    timed_block_on(&mut runtime, async {
+        node.round_manager
+            .process_proposal_msg(proposal)
+            .await
+            .unwrap();
+
+        let (tx, rx) = oneshot::channel();
+        let request = IncomingBlockRetrievalRequest {
+            requester: node.signer.author(),
+            req: BlockRetrievalRequest::new(block_id, 1000),
+            response_sender: tx,
+        };
+        node.round_manager
+            .process_block_retrieval(request)
+            .await
+            .unwrap();
+        let bytes = rx.await.unwrap().unwrap();
+        let response = match lcs::from_bytes(&bytes) {
+            Ok(ConsensusMsg::BlockRetrievalResponse(resp)) => *resp,
+            _ => panic!("block retrieval failure"),
+        };
+        assert_eq!(response.status(), BlockRetrievalStatus::Truncated);
+        assert_eq!(response.blocks().len(), 1);
+        assert_eq!(response.blocks()[0].id(), block_id);
+    });
+}
+
+#[test]
+/// Repeated retrievals of the same chain are served from the cache of recently served blocks
+fn block_retrieval_served_from_cache() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut node = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 1)
+        .pop()
+        .unwrap();
+
+    let genesis_qc = certificate_for_genesis();
+    let block = Block::new_proposal(vec![], 1, 1, genesis_qc.clone(), &node.signer);
+    let block_id = block.id();
+    let proposal = ProposalMsg::new(block, SyncInfo::new(genesis_qc.clone(), genesis_qc, None));
+
+    timed_block_on(&mut runtime, async {
+        node.round_manager
+            .process_proposal_msg(proposal)
+            .await
+            .unwrap();
+
+        let author = node.signer.author();
+        let request = || {
+            let (tx, rx) = oneshot::channel();
+            let request = IncomingBlockRetrievalRequest {
+                requester: author,
+                req: BlockRetrievalRequest::new(block_id, 1),
+                response_sender: tx,
+            };
+            (request, rx)
+        };
+        let (first, rx) = request();
+        node.round_manager
+            .process_block_retrieval(first)
+            .await
+            .unwrap();
+        assert!(rx.await.unwrap().is_ok());
+        assert_eq!(
+            node.round_manager
+                .block_retrieval_cache
+                .lock()
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let cache_hits = counters::BLOCK_RETRIEVAL_CACHE_HIT_COUNT.get();
+        let (second, rx) = request();
+        node.round_manager
+            .process_block_retrieval(second)
+            .await
+            .unwrap();
+        let bytes = rx.await.unwrap().unwrap();
+        let response = match lcs::from_bytes(&bytes) {
+            Ok(ConsensusMsg::BlockRetrievalResponse(resp)) => *resp,
+            _ => panic!("block retrieval failure"),
+        };
+        assert_eq!(response.status(), BlockRetrievalStatus::Succeeded);
+        assert_eq!(response.blocks()[0].id(), block_id);
+        assert!(counters::BLOCK_RETRIEVAL_CACHE_HIT_COUNT.get() > cache_hits);
+    });
+}
+
+#[test]
+/// The blocks pruned by a commit are evicted from the block retrieval cache
+fn block_retrieval_cache_evicts_pruned_blocks() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut node = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 1)
+        .pop()
+        .unwrap();
+
+    // a fork of genesis that doesn't extend the block committed below
+    let genesis_qc = certificate_for_genesis();
+    let fork = Block::new_proposal(vec![], 2, 2, genesis_qc, &node.signer);
+    let fork_id = fork.id();
+    node.block_store.execute_and_insert_block(fork).unwrap();
+
+    timed_block_on(&mut runtime, async {
+        let (tx, rx) = oneshot::channel();
+        let request = IncomingBlockRetrievalRequest {
+            requester: node.signer.author(),
+            req: BlockRetrievalRequest::new(fork_id, 1),
+            response_sender: tx,
+        };
+        node.round_manager
+            .process_block_retrieval(request)
+            .await
+            .unwrap();
+        assert!(rx.await.unwrap().is_ok());
+        assert!(node
+            .round_manager
+            .block_retrieval_cache
+            .lock()
+            .unwrap()
+            .get(&fork_id)
+            .is_some());
+
+        // commit round 1
+        for _ in 0..3 {
+            let proposal_msg = node.next_proposal().await;
+            node.round_manager
+                .process_proposal_msg(proposal_msg)
+                .await
+                .unwrap();
+            let vote_msg = node.next_vote().await;
+            node.round_manager.process_vote(vote_msg).await.unwrap();
+        }
+        assert_eq!(node.block_store.root().round(), 1);
+        // the fork is still in the tree, but it doesn't descend from the new root
+        assert!(node.block_store.block_exists(fork_id));
+        assert!(node
+            .round_manager
+            .block_retrieval_cache
+            .lock()
+            .unwrap()
+            .get(&fork_id)
+            .is_none());
+    });
+}
+
 #[test]
 /// rebuild a node from previous storage without violating safety guarantees.
 fn recover_on_restart() {
@@ -714,6 +1011,34 @@ fn nil_vote_on_timeout() {
     });
 }
 
+#[test]
+/// In the abstain timeout mode a node that didn't vote broadcasts only a round signature upon
+/// timeout, which still forms a TC.
+fn timeout_without_nil_block() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut config = ConsensusConfig::default();
+    config.timeout_mode = TimeoutMode::RepeatLastOrAbstain;
+    let mut nodes =
+        NodeSetup::create_nodes_with_config(&mut playground, runtime.handle().clone(), 1, config);
+    let node = &mut nodes[0];
+    timed_block_on(&mut runtime, async {
+        node.next_proposal().await;
+        node.round_manager.process_local_timeout(1).await.unwrap();
+        let timeout_msg = node.next_timeout().await;
+        assert_eq!(timeout_msg.round(), 1);
+        assert_eq!(timeout_msg.author(), node.signer.author());
+        // No NIL block has been executed
+        assert_eq!(node.block_store.len(), 1);
+
+        node.round_manager
+            .process_timeout_msg(timeout_msg)
+            .await
+            .unwrap();
+        assert_eq!(node.round_manager.round_state().current_round(), 2);
+    });
+}
+
 #[test]
 /// If the node votes in a round, upon timeout the same vote is re-sent with a timeout signature.
 fn vote_resent_on_timeout() {
@@ -848,3 +1173,1739 @@ fn sync_on_partial_newer_sync_info() {
         let _ = node.next_sync_info().await;
     });
 }
+
+#[test]
+/// A commit notification is sent exactly once when a QC forms a 3-chain
+fn commit_notification_on_new_commit() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 1);
+    let node = &mut nodes[0];
+    timed_block_on(&mut runtime, async {
+        let mut block_ids = vec![];
+        for _ in 0..3 {
+            // Nothing is committed until the 3-chain is formed
+            assert!(node.commit_notification_receiver.try_next().is_err());
+            let proposal_msg = node.next_proposal().await;
+            block_ids.push(proposal_msg.proposal().id());
+            node.round_manager
+                .process_proposal_msg(proposal_msg)
+                .await
+                .unwrap();
+            let vote_msg = node.next_vote().await;
+            // Adding vote to form a QC
+            node.round_manager.process_vote(vote_msg).await.unwrap();
+        }
+        let committed_block = node
+            .commit_notification_receiver
+            .try_next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(committed_block.block_id, block_ids[0]);
+        assert_eq!(committed_block.round, 1);
+        assert_eq!(
+            committed_block
+                .ledger_info
+                .ledger_info()
+                .consensus_block_id(),
+            block_ids[0]
+        );
+        assert!(node.commit_notification_receiver.try_next().is_err());
+    });
+}
+
+#[test]
+/// A batch of votes completing a QC starts exactly one new round
+fn process_votes_forms_single_qc() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 4);
+    timed_block_on(&mut runtime, async {
+        // node 0 is the proposer of every round
+        let proposal_msg = nodes[0].next_proposal().await;
+        for node in nodes.iter_mut() {
+            node.round_manager
+                .process_proposal_msg(proposal_msg.clone())
+                .await
+                .unwrap();
+        }
+        // deliver the votes of the other nodes to node 0
+        playground
+            .wait_for_messages(3, NetworkPlayground::votes_only)
+            .await;
+        let mut votes = vec![];
+        for _ in 0..4 {
+            votes.push(nodes[0].next_vote().await);
+        }
+
+        let node = &mut nodes[0];
+        node.round_manager.process_votes(votes).await.unwrap();
+        let proposal_msg = node.next_proposal().await;
+        assert_eq!(proposal_msg.proposal().round(), 2);
+        assert_eq!(node.round_manager.round_state().current_round(), 2);
+        // no other round has been started
+        assert!(node.all_events.next().now_or_never().is_none());
+    });
+}
+
+#[test]
+/// sync_up gives up after the block retrieval deadline if the peer never responds
+fn sync_up_times_out_on_unresponsive_peer() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut config = ConsensusConfig::default();
+    config.round_initial_timeout_ms = 100;
+    config.sync_up_timeout_ratio = 1.0;
+    config.block_retrieval_timeout_ms = 60_000;
+    let mut nodes =
+        NodeSetup::create_nodes_with_config(&mut playground, runtime.handle().clone(), 2, config);
+    let peer = nodes.pop().unwrap();
+    let mut node = nodes.pop().unwrap();
+    let genesis_qc = certificate_for_genesis();
+    // a block the node doesn't have, certified by both validators
+    let block = Block::new_proposal(vec![], 1, 1, genesis_qc.clone(), &peer.signer);
+    let parent_block_info = genesis_qc.certified_block();
+    let block_qc = gen_test_certificate(
+        vec![&node.signer, &peer.signer],
+        block.gen_block_info(
+            parent_block_info.executed_state_id(),
+            parent_block_info.version(),
+            parent_block_info.next_epoch_state().cloned(),
+        ),
+        parent_block_info.clone(),
+        None,
+    );
+    let sync_info = SyncInfo::new(block_qc, genesis_qc, None);
+    timed_block_on(&mut runtime, async {
+        // the block retrieval request is never answered by the peer
+        let start = std::time::Instant::now();
+        let error = node
+            .round_manager
+            .sync_up(&sync_info, peer.signer.author(), true)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, SyncUpError::RetrievalFailed(_)));
+        assert!(start.elapsed() < Duration::from_secs(10));
+    });
+}
+
+#[test]
+/// The predictions of would_advance and projected_round match what sync_up does
+fn sync_info_projection() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 1);
+    let node = &mut nodes[0];
+    let author = node.signer.author();
+    let genesis_qc = certificate_for_genesis();
+    let stale_sync_info = SyncInfo::new(genesis_qc.clone(), genesis_qc.clone(), None);
+    let timeout = Timeout::new(1, 1);
+    let mut tc = TimeoutCertificate::new(timeout.clone());
+    tc.add_signature(author, timeout.sign(&node.signer));
+    let newer_sync_info = SyncInfo::new(genesis_qc.clone(), genesis_qc, Some(tc));
+
+    timed_block_on(&mut runtime, async {
+        assert_eq!(node.round_manager.round_state().current_round(), 1);
+        assert!(!node.round_manager.would_advance(&stale_sync_info));
+        assert_eq!(node.round_manager.projected_round(&stale_sync_info), 1);
+        node.round_manager
+            .sync_up(&stale_sync_info, author, false)
+            .await
+            .unwrap();
+        assert_eq!(node.round_manager.round_state().current_round(), 1);
+
+        assert!(node.round_manager.would_advance(&newer_sync_info));
+        assert_eq!(node.round_manager.projected_round(&newer_sync_info), 2);
+        // nothing changed until the sync up
+        assert_eq!(node.round_manager.round_state().current_round(), 1);
+        node.round_manager
+            .sync_up(&newer_sync_info, author, false)
+            .await
+            .unwrap();
+        assert_eq!(node.round_manager.round_state().current_round(), 2);
+        assert!(!node.round_manager.would_advance(&newer_sync_info));
+    });
+}
+
+#[test]
+/// The failures of sync_up are typed by their cause
+fn sync_up_error_variants() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 1);
+    let node = &mut nodes[0];
+    let author = node.signer.author();
+    let genesis_qc = certificate_for_genesis();
+    let parent_block_info = genesis_qc.certified_block();
+
+    // a QC certified by a signer outside of the validator set
+    let outsider = ValidatorSigner::random([7; 32]);
+    let block = Block::new_proposal(vec![], 10, 1, genesis_qc.clone(), &outsider);
+    let forged_qc = gen_test_certificate(
+        vec![&outsider],
+        block.gen_block_info(
+            parent_block_info.executed_state_id(),
+            parent_block_info.version(),
+            parent_block_info.next_epoch_state().cloned(),
+        ),
+        parent_block_info.clone(),
+        None,
+    );
+    let forged_sync_info = SyncInfo::new(forged_qc, genesis_qc.clone(), None);
+
+    // a QC of the next epoch
+    let next_epoch_block_info = BlockInfo::new(
+        parent_block_info.epoch() + 1,
+        1,
+        HashValue::random(),
+        parent_block_info.executed_state_id(),
+        parent_block_info.version(),
+        parent_block_info.timestamp_usecs(),
+        None,
+    );
+    let next_epoch_qc = gen_test_certificate(
+        vec![&node.signer],
+        next_epoch_block_info,
+        parent_block_info.clone(),
+        None,
+    );
+    let next_epoch_sync_info = SyncInfo::new(next_epoch_qc.clone(), next_epoch_qc, None);
+
+    // a TC for round 1, which is committed below
+    let timeout = Timeout::new(1, 1);
+    let mut tc = TimeoutCertificate::new(timeout.clone());
+    tc.add_signature(author, timeout.sign(&node.signer));
+    let stale_sync_info = SyncInfo::new(genesis_qc.clone(), genesis_qc, Some(tc));
+
+    timed_block_on(&mut runtime, async {
+        let error = node
+            .round_manager
+            .sync_up(&forged_sync_info, outsider.author(), false)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, SyncUpError::VerificationFailed(_)));
+
+        let error = node
+            .round_manager
+            .sync_up(&next_epoch_sync_info, author, false)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            SyncUpError::EpochMismatch {
+                local: 1,
+                remote: 2
+            }
+        ));
+
+        // commit round 1
+        for _ in 0..3 {
+            let proposal_msg = node.next_proposal().await;
+            node.round_manager
+                .process_proposal_msg(proposal_msg)
+                .await
+                .unwrap();
+            let vote_msg = node.next_vote().await;
+            node.round_manager.process_vote(vote_msg).await.unwrap();
+        }
+        assert_eq!(node.block_store.root().round(), 1);
+        let error = node
+            .round_manager
+            .sync_up(&stale_sync_info, author, false)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            SyncUpError::StaleSyncInfo {
+                round: 1,
+                committed_round: 1
+            }
+        ));
+    });
+}
+
+#[test]
+/// Round transitions are described by a structured log event with a stable set of fields
+fn new_round_structured_log() {
+    let runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 1);
+    let node = &nodes[0];
+    let new_round_event = NewRoundEvent {
+        round: 5,
+        reason: NewRoundReason::QCReady,
+        timeout: Duration::from_millis(1500),
+    };
+    let entry = node.round_manager.new_round_log_entry(&new_round_event);
+    let entry = serde_json::to_value(entry).unwrap();
+    assert_eq!(entry["name"], NEW_ROUND_LOG_EVENT);
+    assert_eq!(
+        entry["data"],
+        serde_json::json!({
+            "epoch": 1,
+            "round": 5,
+            "reason": "QCReady",
+            "proposer": node.signer.author().to_string(),
+            "timeout_ms": 1500,
+        })
+    );
+}
+
+#[test]
+/// The node isn't synced while it lags far behind the rounds observed from its peers
+fn not_synced_until_caught_up() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut config = ConsensusConfig::default();
+    config.round_initial_timeout_ms = 100;
+    config.sync_up_timeout_ratio = 1.0;
+    config.max_synced_round_gap = 2;
+    let mut nodes =
+        NodeSetup::create_nodes_with_config(&mut playground, runtime.handle().clone(), 2, config);
+    let peer = nodes.pop().unwrap();
+    let mut node = nodes.pop().unwrap();
+    let genesis_qc = certificate_for_genesis();
+    // a block of round 10 the node doesn't have, certified by both validators
+    let block = Block::new_proposal(vec![], 10, 1, genesis_qc.clone(), &peer.signer);
+    let parent_block_info = genesis_qc.certified_block();
+    let block_qc = gen_test_certificate(
+        vec![&node.signer, &peer.signer],
+        block.gen_block_info(
+            parent_block_info.executed_state_id(),
+            parent_block_info.version(),
+            parent_block_info.next_epoch_state().cloned(),
+        ),
+        parent_block_info.clone(),
+        None,
+    );
+    let tc_for_round = |round| {
+        let timeout = Timeout::new(1, round);
+        let mut tc = TimeoutCertificate::new(timeout.clone());
+        for signer in &[&node.signer, &peer.signer] {
+            tc.add_signature(signer.author(), timeout.sign(signer));
+        }
+        tc
+    };
+    let (behind_tc, caught_up_tc) = (tc_for_round(7), tc_for_round(9));
+    timed_block_on(&mut runtime, async {
+        assert!(node.round_manager.is_synced());
+        // the block can't be retrieved, the node stays behind
+        node.round_manager
+            .sync_up(
+                &SyncInfo::new(block_qc, genesis_qc.clone(), None),
+                peer.signer.author(),
+                true,
+            )
+            .await
+            .unwrap_err();
+        assert!(!node.round_manager.is_synced());
+
+        for (tc, synced) in vec![(behind_tc, false), (caught_up_tc, true)] {
+            node.round_manager
+                .sync_up(
+                    &SyncInfo::new(genesis_qc.clone(), genesis_qc.clone(), Some(tc)),
+                    peer.signer.author(),
+                    true,
+                )
+                .await
+                .unwrap();
+            assert_eq!(node.round_manager.is_synced(), synced);
+        }
+    });
+}
+
+#[test]
+/// Two different proposals from the same proposer for the same round are reported
+fn proposer_equivocation_detected() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut node = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 1)
+        .pop()
+        .unwrap();
+    let genesis_qc = certificate_for_genesis();
+    let block = Block::new_proposal(vec![], 1, 1, genesis_qc.clone(), &node.signer);
+    let conflicting_block = Block::new_proposal(vec![], 1, 2, genesis_qc.clone(), &node.signer);
+    let sync_info = SyncInfo::new(genesis_qc.clone(), genesis_qc, None);
+    timed_block_on(&mut runtime, async {
+        let equivocations = counters::PROPOSER_EQUIVOCATION_COUNT.get();
+        for proposal in vec![block.clone(), block, conflicting_block] {
+            // voting behavior is not affected by the detection
+            node.round_manager
+                .pre_process_proposal(ProposalMsg::new(proposal, sync_info.clone()))
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            counters::PROPOSER_EQUIVOCATION_COUNT.get(),
+            equivocations + 1
+        );
+    });
+}
+
+#[test]
+/// The equivocation of the primary proposer is reported even after the backup one proposed
+fn primary_proposer_equivocation_detected_after_backup_proposal() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 2);
+    let (primary, backup) = (nodes[0].signer.author(), nodes[1].signer.author());
+    nodes[0].round_manager.proposer_election =
+        Box::new(PrimaryAndBackupProposers { primary, backup });
+    let genesis_qc = certificate_for_genesis();
+    let backup_block = Block::new_proposal(vec![], 1, 1, genesis_qc.clone(), &nodes[1].signer);
+    let block = Block::new_proposal(vec![], 1, 1, genesis_qc.clone(), &nodes[0].signer);
+    let conflicting_block = Block::new_proposal(vec![], 1, 2, genesis_qc.clone(), &nodes[0].signer);
+    let sync_info = SyncInfo::new(genesis_qc.clone(), genesis_qc, None);
+    let node = &mut nodes[0];
+    timed_block_on(&mut runtime, async {
+        let equivocations = counters::PROPOSER_EQUIVOCATION_COUNT.get();
+        for proposal in vec![backup_block, block, conflicting_block] {
+            node.round_manager
+                .pre_process_proposal(ProposalMsg::new(proposal, sync_info.clone()))
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            counters::PROPOSER_EQUIVOCATION_COUNT.get(),
+            equivocations + 1
+        );
+    });
+}
+
+#[test]
+/// Executing a proposal before voting on it should be recorded in the execution histogram
+fn block_execution_latency_observed() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 1);
+    let node = &mut nodes[0];
+
+    let genesis_qc = certificate_for_genesis();
+    timed_block_on(&mut runtime, async {
+        // Start round 1 and clear the message queue
+        node.next_proposal().await;
+
+        let executions = counters::BLOCK_EXECUTION_S
+            .with_label_values(&["proposal"])
+            .get_sample_count();
+        let commits = counters::TXN_MANAGER_COMMIT_S
+            .with_label_values(&["proposal"])
+            .get_sample_count();
+        let proposal = Block::new_proposal(vec![], 1, 1, genesis_qc.clone(), &node.signer);
+        node.round_manager
+            .process_proposed_block(proposal)
+            .await
+            .unwrap();
+        node.next_vote().await;
+        assert!(
+            counters::BLOCK_EXECUTION_S
+                .with_label_values(&["proposal"])
+                .get_sample_count()
+                > executions
+        );
+        assert!(
+            counters::TXN_MANAGER_COMMIT_S
+                .with_label_values(&["proposal"])
+                .get_sample_count()
+                > commits
+        );
+    });
+}
+
+#[test]
+/// Proposals are limited in size once too many certified blocks are waiting to be committed
+fn proposal_size_reduced_under_backpressure() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut config = ConsensusConfig::default();
+    config.backpressure_pending_blocks = 1;
+    config.backpressure_block_size = 0;
+    let mut nodes =
+        NodeSetup::create_nodes_with_config(&mut playground, runtime.handle().clone(), 1, config);
+    let node = &mut nodes[0];
+    timed_block_on(&mut runtime, async {
+        // certify rounds 1 and 2 without committing anything
+        for _ in 0..2 {
+            let proposal_msg = node.next_proposal().await;
+            assert_eq!(proposal_msg.proposal().payload().map_or(0, |p| p.len()), 1);
+            node.round_manager
+                .process_proposal_msg(proposal_msg)
+                .await
+                .unwrap();
+            let vote_msg = node.next_vote().await;
+            node.round_manager.process_vote(vote_msg).await.unwrap();
+        }
+        assert_eq!(node.block_store.uncommitted_block_count(), 2);
+
+        let proposal_msg = node.next_proposal().await;
+        assert_eq!(proposal_msg.proposal().round(), 3);
+        assert_eq!(proposal_msg.proposal().payload().map_or(0, |p| p.len()), 0);
+    });
+}
+
+#[test]
+/// Proposals extending too many uncommitted blocks are neither executed nor voted for, the
+/// timeouts still are
+fn proposal_beyond_max_pending_blocks_rejected() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut config = ConsensusConfig::default();
+    config.max_pending_blocks = 2;
+    let mut nodes =
+        NodeSetup::create_nodes_with_config(&mut playground, runtime.handle().clone(), 1, config);
+    let node = &mut nodes[0];
+    timed_block_on(&mut runtime, async {
+        // stack 2 uncommitted blocks
+        for _ in 0..2 {
+            let proposal_msg = node.next_proposal().await;
+            node.round_manager
+                .process_proposal_msg(proposal_msg)
+                .await
+                .unwrap();
+            let vote_msg = node.next_vote().await;
+            node.round_manager.process_vote(vote_msg).await.unwrap();
+        }
+        assert_eq!(node.block_store.uncommitted_block_count(), 2);
+
+        let proposal_msg = node.next_proposal().await;
+        let block_id = proposal_msg.proposal().id();
+        assert!(node
+            .round_manager
+            .process_proposal_msg(proposal_msg)
+            .await
+            .is_err());
+        assert!(!node.block_store.block_exists(block_id));
+        assert!(node.round_manager.round_state().vote_sent().is_none());
+
+        // the timeout of the round is still voted for
+        node.round_manager.process_local_timeout(3).await.unwrap();
+        let timeout_vote_msg = node.next_vote().await;
+        assert!(timeout_vote_msg.vote().is_timeout());
+        assert_eq!(timeout_vote_msg.vote().vote_data().proposed().round(), 3);
+    });
+}
+
+#[test]
+/// Proposals over the max proposal size are cut down instead of being broadcast as is
+fn oversized_proposal_not_broadcast() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut config = ConsensusConfig::default();
+    config.max_proposal_bytes = 1;
+    let mut nodes =
+        NodeSetup::create_nodes_with_config(&mut playground, runtime.handle().clone(), 1, config);
+    let node = &mut nodes[0];
+    timed_block_on(&mut runtime, async {
+        let proposal_msg = node.next_proposal().await;
+        assert_eq!(proposal_msg.proposal().round(), 1);
+        assert_eq!(proposal_msg.proposal().payload().map(|p| p.len()), Some(0));
+        // the empty block is still a valid proposal
+        node.round_manager
+            .process_proposal_msg(proposal_msg)
+            .await
+            .unwrap();
+        let vote_msg = node.next_vote().await;
+        assert_eq!(vote_msg.vote().vote_data().proposed().round(), 1);
+    });
+}
+
+/// Nominates the same primary and backup proposers for every round.
+struct PrimaryAndBackupProposers {
+    primary: Author,
+    backup: Author,
+}
+
+impl ProposerElection for PrimaryAndBackupProposers {
+    fn get_valid_proposer(&self, _round: Round) -> Author {
+        self.primary
+    }
+
+    fn get_backup_proposer(&self, _round: Round) -> Option<Author> {
+        Some(self.backup)
+    }
+}
+
+#[test]
+/// The proposal of the backup proposer is voted for only when the primary one is missing
+fn backup_proposal_selected_without_primary() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 2);
+    let (primary, backup) = (nodes[0].signer.author(), nodes[1].signer.author());
+    for node in nodes.iter_mut() {
+        node.round_manager.proposer_election =
+            Box::new(PrimaryAndBackupProposers { primary, backup });
+    }
+    let genesis_qc = certificate_for_genesis();
+    timed_block_on(&mut runtime, async {
+        let primary_msg = nodes[0].next_proposal().await;
+        let primary_id = primary_msg.proposal().id();
+        let backup_block = Block::new_proposal(
+            vec![],
+            1,
+            primary_msg.proposal().timestamp_usecs(),
+            genesis_qc,
+            &nodes[1].signer,
+        );
+        let backup_id = backup_block.id();
+        let backup_msg = ProposalMsg::new(backup_block, primary_msg.sync_info().clone());
+
+        // the primary proposal is voted for even though the backup one arrived first
+        let node = &mut nodes[0];
+        node.round_manager
+            .process_proposal_msg(backup_msg.clone())
+            .await
+            .unwrap();
+        assert!(node.round_manager.round_state.vote_sent().is_none());
+        node.round_manager
+            .process_proposal_msg(primary_msg)
+            .await
+            .unwrap();
+        node.round_manager
+            .process_backup_proposal_timeout(1)
+            .await
+            .unwrap();
+        let vote = node.round_manager.round_state.vote_sent().unwrap();
+        assert_eq!(vote.vote_data().proposed().id(), primary_id);
+
+        // without the primary proposal the backup one is voted for once the delay expires
+        let node = &mut nodes[1];
+        node.round_manager
+            .process_proposal_msg(backup_msg)
+            .await
+            .unwrap();
+        assert!(node.round_manager.round_state.vote_sent().is_none());
+        node.round_manager
+            .process_backup_proposal_timeout(1)
+            .await
+            .unwrap();
+        let vote = node.round_manager.round_state.vote_sent().unwrap();
+        assert_eq!(vote.vote_data().proposed().id(), backup_id);
+    });
+}
+
+#[test]
+/// Previewing a proposal is repeatable and leaves the consensus state untouched
+fn preview_proposal_is_side_effect_free() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 1);
+    let node = &mut nodes[0];
+    timed_block_on(&mut runtime, async {
+        let proposal_msg = node.next_proposal().await;
+        let round = proposal_msg.proposal().round();
+        let consensus_state = node.round_manager.consensus_state();
+
+        let first = node.round_manager.preview_proposal(round).await.unwrap();
+        let second = node.round_manager.preview_proposal(round).await.unwrap();
+        for preview in &[&first, &second] {
+            assert!(preview.signature().is_none());
+            assert_eq!(preview.round(), round);
+            assert_eq!(preview.author(), Some(node.signer.author()));
+        }
+        assert_eq!(first.parent_id(), second.parent_id());
+        assert_eq!(first.quorum_cert(), second.quorum_cert());
+        assert_eq!(first.payload(), second.payload());
+
+        assert_eq!(node.round_manager.round_state().current_round(), round);
+        assert!(node.round_manager.round_state().vote_sent().is_none());
+        assert_eq!(node.round_manager.consensus_state(), consensus_state);
+
+        // The actual proposal is still processed and voted on as usual
+        node.round_manager
+            .process_proposal_msg(proposal_msg)
+            .await
+            .unwrap();
+        let vote_msg = node.next_vote().await;
+        assert_eq!(vote_msg.vote().vote_data().proposed().round(), round);
+    });
+}
+
+#[test]
+/// The proposer queries should match the output of the proposer election
+fn proposer_for_current_and_next_round() {
+    let runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 1);
+    let node = &mut nodes[0];
+
+    let proposers: Vec<_> = (0..3u8).map(|i| Author::new([i; Author::LENGTH])).collect();
+    let election = RotatingProposer::new(proposers.clone(), 1);
+    node.round_manager.proposer_election = Box::new(RotatingProposer::new(proposers, 1));
+
+    let round = node.round_manager.round_state().current_round();
+    assert_eq!(
+        node.round_manager.current_proposer(),
+        election.get_valid_proposer(round)
+    );
+    assert_eq!(
+        node.round_manager.proposer_for_round(round + 1),
+        election.get_valid_proposer(round + 1)
+    );
+    assert_ne!(
+        node.round_manager.proposer_for_round(round),
+        node.round_manager.proposer_for_round(round + 1)
+    );
+}
+
+#[test]
+fn next_proposal_round_within_lookahead() {
+    let runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut config = ConsensusConfig::default();
+    config.proposal_lookahead_rounds = 3;
+    let mut nodes =
+        NodeSetup::create_nodes_with_config(&mut playground, runtime.handle().clone(), 1, config);
+    let node = &mut nodes[0];
+    let author = node.signer.author();
+    let round = node.round_manager.round_state().current_round();
+    let other = |i| Author::new([i; Author::LENGTH]);
+
+    // we lead one of every three rounds
+    let proposers = vec![author, other(1), other(2)];
+    let election = RotatingProposer::new(proposers.clone(), 1);
+    node.round_manager.proposer_election = Box::new(RotatingProposer::new(proposers, 1));
+    let next_round = node.round_manager.next_proposal_round().unwrap();
+    assert!(next_round > round && next_round <= round + 3);
+    assert!(election.is_valid_proposer(author, next_round));
+    assert!((round + 1..next_round).all(|r| !election.is_valid_proposer(author, r)));
+
+    // we don't lead any round
+    node.round_manager.proposer_election =
+        Box::new(RotatingProposer::new(vec![other(1), other(2)], 1));
+    assert_eq!(node.round_manager.next_proposal_round(), None);
+}
+
+#[test]
+/// A proposal for a round we can't sync up to is buffered and processed once the round catches up
+fn future_proposal_buffered_until_round_catches_up() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 1);
+    let node = &mut nodes[0];
+
+    let genesis_qc = certificate_for_genesis();
+    let future_block = Block::new_proposal(vec![], 3, 1, genesis_qc.clone(), &node.signer);
+    let future_block_id = future_block.id();
+    let timeout = Timeout::new(1, 2);
+    let timeout_signature = timeout.sign(&node.signer);
+    let mut tc = TimeoutCertificate::new(timeout);
+    tc.add_signature(node.signer.author(), timeout_signature);
+
+    timed_block_on(&mut runtime, async {
+        // Start round 1 and clear the message queue
+        node.next_proposal().await;
+
+        // the SyncInfo of the proposal doesn't bring us to round 3
+        let future_proposal = ProposalMsg::new(
+            future_block,
+            SyncInfo::new(genesis_qc.clone(), genesis_qc.clone(), None),
+        );
+        assert!(node
+            .round_manager
+            .process_proposal_msg(future_proposal)
+            .await
+            .is_err());
+        assert!(node.round_manager.future_proposals.contains_key(&3));
+
+        // TC of round 2 moves us to round 3
+        node.round_manager
+            .process_sync_info_msg(
+                SyncInfo::new(genesis_qc.clone(), genesis_qc.clone(), Some(tc)),
+                node.signer.author(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(node.round_manager.round_state().current_round(), 3);
+        // clear our own proposal for round 3
+        node.next_proposal().await;
+
+        node.round_manager.process_future_proposals().await;
+        assert!(node.round_manager.future_proposals.is_empty());
+        let vote_msg = node.next_vote().await;
+        assert_eq!(vote_msg.vote().vote_data().proposed().id(), future_block_id);
+    });
+}
+
+#[test]
+/// A future proposal whose SyncInfo we fail to sync up to is buffered as well
+fn future_proposal_buffered_on_failed_sync_up() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut config = ConsensusConfig::default();
+    config.round_initial_timeout_ms = 100;
+    config.sync_up_timeout_ratio = 1.0;
+    let mut nodes =
+        NodeSetup::create_nodes_with_config(&mut playground, runtime.handle().clone(), 1, config);
+    let node = &mut nodes[0];
+    let genesis_qc = certificate_for_genesis();
+    let parent_block_info = genesis_qc.certified_block().clone();
+    let timeout = Timeout::new(1, 2);
+    let timeout_signature = timeout.sign(&node.signer);
+    let mut tc = TimeoutCertificate::new(timeout);
+    tc.add_signature(node.signer.author(), timeout_signature);
+
+    timed_block_on(&mut runtime, async {
+        // the proposal of round 1 isn't processed yet, its block can't be retrieved
+        let proposal_msg = node.next_proposal().await;
+        let block = proposal_msg.proposal().clone();
+        let block_qc = gen_test_certificate(
+            vec![&node.signer],
+            block.gen_block_info(
+                parent_block_info.executed_state_id(),
+                parent_block_info.version(),
+                parent_block_info.next_epoch_state().cloned(),
+            ),
+            parent_block_info,
+            None,
+        );
+        let future_block = Block::new_proposal(
+            vec![],
+            3,
+            block.timestamp_usecs() + 1,
+            block_qc.clone(),
+            &node.signer,
+        );
+        let future_block_id = future_block.id();
+        let future_proposal = ProposalMsg::new(
+            future_block,
+            SyncInfo::new(block_qc.clone(), genesis_qc.clone(), None),
+        );
+        assert!(node
+            .round_manager
+            .process_proposal_msg(future_proposal)
+            .await
+            .is_err());
+        assert!(node.round_manager.future_proposals.contains_key(&3));
+
+        // the block of round 1 gets certified, then the TC of round 2 moves us to round 3
+        node.round_manager
+            .process_proposal_msg(proposal_msg)
+            .await
+            .unwrap();
+        let vote_msg = node.next_vote().await;
+        node.round_manager.process_vote(vote_msg).await.unwrap();
+        node.next_proposal().await;
+        node.round_manager
+            .process_sync_info_msg(
+                SyncInfo::new(block_qc, genesis_qc, Some(tc)),
+                node.signer.author(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(node.round_manager.round_state().current_round(), 3);
+        node.next_proposal().await;
+
+        node.round_manager.process_future_proposals().await;
+        let vote_msg = node.next_vote().await;
+        assert_eq!(vote_msg.vote().vote_data().proposed().id(), future_block_id);
+    });
+}
+
+#[test]
+/// A SyncInfo with the same QC but a higher TC moves us to the round after the TC
+fn sync_info_with_newer_tc_only() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 1);
+    let node = &mut nodes[0];
+
+    let genesis_qc = certificate_for_genesis();
+    let tc_for_round = |round| {
+        let timeout = Timeout::new(1, round);
+        let timeout_signature = timeout.sign(&node.signer);
+        let mut tc = TimeoutCertificate::new(timeout);
+        tc.add_signature(node.signer.author(), timeout_signature);
+        tc
+    };
+    let (local_tc, remote_tc) = (tc_for_round(1), tc_for_round(3));
+
+    timed_block_on(&mut runtime, async {
+        node.next_proposal().await;
+        node.round_manager
+            .process_sync_info_msg(
+                SyncInfo::new(genesis_qc.clone(), genesis_qc.clone(), Some(local_tc)),
+                node.signer.author(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(node.round_manager.round_state().current_round(), 2);
+
+        node.round_manager
+            .process_sync_info_msg(
+                SyncInfo::new(genesis_qc.clone(), genesis_qc.clone(), Some(remote_tc)),
+                node.signer.author(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(node.round_manager.round_state().current_round(), 4);
+        assert_eq!(node.block_store.highest_timeout_cert().unwrap().round(), 3);
+    });
+}
+
+#[test]
+/// The results of a batch verification match the positions of the input events
+fn verify_batch_preserves_order() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 1);
+    let node = &mut nodes[0];
+    // not a member of the validator set
+    let outsider = ValidatorSigner::random([9; 32]);
+
+    let genesis_qc = certificate_for_genesis();
+    timed_block_on(&mut runtime, async {
+        let valid_proposal = node.next_proposal().await;
+        node.round_manager
+            .process_proposal_msg(valid_proposal.clone())
+            .await
+            .unwrap();
+        let valid_vote = node.next_vote().await;
+
+        let invalid_proposal = ProposalMsg::new(
+            Block::new_proposal(vec![], 1, 1, genesis_qc.clone(), &outsider),
+            SyncInfo::new(genesis_qc.clone(), genesis_qc.clone(), None),
+        );
+        let invalid_vote = VoteMsg::new(
+            Vote::new(
+                valid_vote.vote().vote_data().clone(),
+                outsider.author(),
+                valid_vote.vote().ledger_info().clone(),
+                &outsider,
+            ),
+            valid_vote.sync_info().clone(),
+        );
+
+        let events = vec![
+            UnverifiedEvent::ProposalMsg(Box::new(valid_proposal)),
+            UnverifiedEvent::VoteMsg(Box::new(invalid_vote)),
+            UnverifiedEvent::ProposalMsg(Box::new(invalid_proposal)),
+            UnverifiedEvent::VoteMsg(Box::new(valid_vote)),
+        ];
+        let results =
+            UnverifiedEvent::verify_batch(events, &node.round_manager.epoch_state().verifier);
+        assert_eq!(results.len(), 4);
+        assert!(matches!(results[0], Ok(VerifiedEvent::ProposalMsg(_))));
+        assert!(results[1].is_err());
+        assert!(results[2].is_err());
+        assert!(matches!(results[3], Ok(VerifiedEvent::VoteMsg(_))));
+    });
+}
+
+#[derive(Default)]
+struct RecordingCertObserver {
+    qcs: Mutex<Vec<(Round, Author)>>,
+    tcs: Mutex<Vec<Round>>,
+}
+
+impl CertObserver for RecordingCertObserver {
+    fn on_qc(&self, qc: &QuorumCert, author: Author) {
+        self.qcs
+            .lock()
+            .unwrap()
+            .push((qc.certified_block().round(), author));
+    }
+
+    fn on_tc(&self, tc: &TimeoutCertificate) {
+        self.tcs.lock().unwrap().push(tc.round());
+    }
+}
+
+#[test]
+/// The cert observer is called exactly once for every aggregated QC and TC
+fn cert_observer_called_on_aggregation() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 2);
+    let observer = Arc::new(RecordingCertObserver::default());
+    nodes[0].round_manager.cert_observer = Some(observer.clone());
+    let node_1_author = nodes[1].signer.author();
+
+    timed_block_on(&mut runtime, async {
+        // round 1: both nodes vote for the proposal of node 0, forming a QC
+        let proposal_msg = nodes[0].next_proposal().await;
+        nodes[1].next_proposal().await;
+        for node in nodes.iter_mut() {
+            node.round_manager
+                .process_proposal_msg(proposal_msg.clone())
+                .await
+                .unwrap();
+        }
+        for _ in 0..2 {
+            let vote_msg = nodes[0].next_vote().await;
+            nodes[0].round_manager.process_vote(vote_msg).await.unwrap();
+        }
+        assert_eq!(*observer.qcs.lock().unwrap(), vec![(1, node_1_author)]);
+
+        // round 2: node 0 times out without voting, node 1 votes for the proposal and then
+        // times out, forming a TC but no QC
+        nodes[0].next_proposal().await;
+        nodes[0]
+            .round_manager
+            .process_local_timeout(2)
+            .await
+            .unwrap();
+        let timeout_vote_msg = nodes[0].next_vote().await;
+        nodes[0]
+            .round_manager
+            .process_vote(timeout_vote_msg)
+            .await
+            .unwrap();
+
+        let proposal_msg = nodes[1].next_proposal().await;
+        nodes[1]
+            .round_manager
+            .process_proposal_msg(proposal_msg)
+            .await
+            .unwrap();
+        nodes[1]
+            .round_manager
+            .process_local_timeout(2)
+            .await
+            .unwrap();
+        for _ in 0..2 {
+            let vote_msg = nodes[0].next_vote().await;
+            nodes[0].round_manager.process_vote(vote_msg).await.unwrap();
+        }
+        assert_eq!(observer.qcs.lock().unwrap().len(), 1);
+        assert_eq!(*observer.tcs.lock().unwrap(), vec![2]);
+        assert_eq!(nodes[0].round_manager.round_state().current_round(), 3);
+    });
+}
+
+struct PanickingCertObserver;
+
+impl CertObserver for PanickingCertObserver {
+    fn on_qc(&self, _qc: &QuorumCert, _author: Author) {
+        panic!("on_qc");
+    }
+
+    fn on_tc(&self, _tc: &TimeoutCertificate) {
+        panic!("on_tc");
+    }
+}
+
+#[test]
+/// A panicking cert observer doesn't prevent the QC from being processed
+fn panicking_cert_observer_is_contained() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 1);
+    let node = &mut nodes[0];
+    node.round_manager.cert_observer = Some(Arc::new(PanickingCertObserver));
+
+    timed_block_on(&mut runtime, async {
+        let proposal_msg = node.next_proposal().await;
+        node.round_manager
+            .process_proposal_msg(proposal_msg)
+            .await
+            .unwrap();
+        let vote_msg = node.next_vote().await;
+        node.round_manager.process_vote(vote_msg).await.unwrap();
+        assert_eq!(node.round_manager.round_state().current_round(), 2);
+    });
+}
+
+struct DenyBlockPolicy(HashValue);
+
+impl VotePolicy for DenyBlockPolicy {
+    fn should_vote(&self, block: &Block, _compute_result: &StateComputeResult) -> bool {
+        block.id() != self.0
+    }
+}
+
+#[test]
+/// A proposal rejected by the vote policy isn't voted for, the round can still time out
+fn vote_policy_abstains() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 1);
+    let node = &mut nodes[0];
+
+    timed_block_on(&mut runtime, async {
+        let proposal_msg = node.next_proposal().await;
+        let proposal_id = proposal_msg.proposal().id();
+        node.round_manager.vote_policy = Some(Arc::new(DenyBlockPolicy(proposal_id)));
+        assert!(node
+            .round_manager
+            .process_proposal_msg(proposal_msg)
+            .await
+            .is_err());
+        assert!(node.round_manager.round_state().vote_sent().is_none());
+        // the block is still executed and kept in the tree
+        assert!(node.block_store.get_block(proposal_id).is_some());
+
+        node.round_manager.process_local_timeout(1).await.unwrap();
+        let timeout_vote_msg = node.next_vote().await;
+        assert!(timeout_vote_msg.vote().is_timeout());
+        assert_ne!(
+            timeout_vote_msg.vote().vote_data().proposed().id(),
+            proposal_id
+        );
+    });
+}
+
+#[test]
+/// With proposing disabled the node keeps voting but doesn't propose in the rounds it leads
+fn proposing_paused_and_resumed() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 1);
+    let node = &mut nodes[0];
+
+    timed_block_on(&mut runtime, async {
+        let proposal_msg = node.next_proposal().await;
+        node.round_manager.set_proposing_enabled(false);
+        node.round_manager
+            .process_proposal_msg(proposal_msg)
+            .await
+            .unwrap();
+        let vote_msg = node.next_vote().await;
+        node.round_manager.process_vote(vote_msg).await.unwrap();
+        // round 2 started, but nothing is proposed
+        assert_eq!(node.round_manager.round_state().current_round(), 2);
+        assert!(node.all_events.next().now_or_never().is_none());
+
+        // still votes on timeout
+        node.round_manager.set_proposing_enabled(true);
+        node.round_manager.process_local_timeout(2).await.unwrap();
+        let vote_msg = node.next_vote().await;
+        node.round_manager.process_vote(vote_msg).await.unwrap();
+        let proposal_msg = node.next_proposal().await;
+        assert_eq!(proposal_msg.proposal().round(), 3);
+    });
+}
+
+#[test]
+/// The proposals of the rounds starting sooner than the min interval after the previous proposal
+/// are held back
+fn proposal_delayed_by_min_interval() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut config = ConsensusConfig::default();
+    config.min_proposal_interval_ms = 1000;
+    let mut nodes =
+        NodeSetup::create_nodes_with_config(&mut playground, runtime.handle().clone(), 1, config);
+    let node = &mut nodes[0];
+    // ahead of the block timestamps, so that voting never waits
+    let time_service = SimulatedTimeService::new();
+    block_on(time_service.sleep(duration_since_epoch() + Duration::from_secs(3600)));
+    node.round_manager.time_service = Arc::new(time_service.clone());
+
+    timed_block_on(&mut runtime, async {
+        let start = time_service.get_current_timestamp();
+        let mut proposal_times = vec![];
+        for round in 1..=2 {
+            let proposal_msg = node.next_proposal().await;
+            assert_eq!(proposal_msg.proposal().round(), round);
+            node.round_manager
+                .process_proposal_msg(proposal_msg)
+                .await
+                .unwrap();
+            let vote_msg = node.next_vote().await;
+            node.round_manager.process_vote(vote_msg).await.unwrap();
+            proposal_times.push(time_service.get_current_timestamp());
+        }
+        assert_eq!(node.next_proposal().await.proposal().round(), 3);
+        // the proposal of round 1 was made long before, the one of round 2 didn't wait
+        assert_eq!(proposal_times[0], start);
+        // the one of round 3 had to
+        assert_eq!(
+            proposal_times[1] - proposal_times[0],
+            Duration::from_secs(1)
+        );
+    });
+}
+
+#[test]
+/// The time left in the round decreases with the clock down to zero at the deadline
+fn round_deadline_remaining_counts_down() {
+    let runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 1);
+    let node = &mut nodes[0];
+    let time_service = SimulatedTimeService::new();
+    node.round_manager.time_service = Arc::new(time_service.clone());
+    node.round_manager.round_state = NodeSetup::create_round_state(Arc::new(time_service.clone()));
+    assert_eq!(node.round_manager.round_deadline_remaining(), None);
+
+    // the round lasts 60 seconds
+    node.round_manager
+        .round_state
+        .process_certificates(node.block_store.sync_info())
+        .unwrap();
+    assert_eq!(
+        node.round_manager.round_deadline_remaining(),
+        Some(Duration::from_secs(60))
+    );
+    block_on(time_service.sleep(Duration::from_secs(20)));
+    assert_eq!(
+        node.round_manager.round_deadline_remaining(),
+        Some(Duration::from_secs(40))
+    );
+    block_on(time_service.sleep(Duration::from_secs(40)));
+    assert_eq!(
+        node.round_manager.round_deadline_remaining(),
+        Some(Duration::from_secs(0))
+    );
+    block_on(time_service.sleep(Duration::from_secs(5)));
+    assert_eq!(
+        node.round_manager.round_deadline_remaining(),
+        Some(Duration::from_secs(0))
+    );
+}
+
+/// Leaves out the first transaction it's asked about.
+#[derive(Default)]
+struct DropFirstTxnFilter(Mutex<Option<SignedTransaction>>);
+
+impl PayloadFilter for DropFirstTxnFilter {
+    fn keep(&self, txn: &SignedTransaction) -> bool {
+        let mut dropped = self.0.lock().unwrap();
+        if dropped.is_none() {
+            *dropped = Some(txn.clone());
+            return false;
+        }
+        dropped.as_ref() != Some(txn)
+    }
+}
+
+#[test]
+/// The transactions rejected by the payload filter are left out of the signed proposal
+fn payload_filter_removes_txn() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 1);
+    let node = &mut nodes[0];
+    let filter = Arc::new(DropFirstTxnFilter::default());
+    node.round_manager.payload_filter = Some(filter.clone());
+    node.round_manager.proposal_generator = ProposalGenerator::new(
+        node.signer.author(),
+        node.block_store.clone(),
+        Box::new(MockTransactionManager::new(None)),
+        node.round_manager.time_service.clone(),
+        3,
+    );
+
+    timed_block_on(&mut runtime, async {
+        let proposal_msg = node.next_proposal().await;
+        node.round_manager
+            .process_proposal_msg(proposal_msg)
+            .await
+            .unwrap();
+        let vote_msg = node.next_vote().await;
+        node.round_manager.process_vote(vote_msg).await.unwrap();
+
+        let proposal_msg = node.next_proposal().await;
+        assert_eq!(proposal_msg.proposal().round(), 2);
+        let dropped = filter.0.lock().unwrap().clone().unwrap();
+        let payload = proposal_msg.proposal().payload().unwrap();
+        assert_eq!(payload.len(), 2);
+        assert!(!payload.contains(&dropped));
+        // the filtered proposal is still valid
+        node.round_manager
+            .process_proposal_msg(proposal_msg)
+            .await
+            .unwrap();
+        assert_eq!(
+            node.next_vote().await.vote().vote_data().proposed().round(),
+            2
+        );
+    });
+}
+
+#[test]
+/// Once shut down at the end of the epoch, the events are rejected right away
+fn shutdown_rejects_events() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 1);
+    let node = &mut nodes[0];
+
+    timed_block_on(&mut runtime, async {
+        let proposal_msg = node.next_proposal().await;
+        node.round_manager
+            .process_proposal_msg(proposal_msg.clone())
+            .await
+            .unwrap();
+        let vote_msg = node.next_vote().await;
+
+        node.round_manager.shutdown().await;
+        let err = node.round_manager.process_vote(vote_msg).await.unwrap_err();
+        assert_eq!(err.downcast_ref::<EpochEnded>(), Some(&EpochEnded(1)));
+        assert!(node
+            .round_manager
+            .process_proposal_msg(proposal_msg)
+            .await
+            .is_err());
+        assert_eq!(node.round_manager.round_state().current_round(), 1);
+    });
+}
+
+#[test]
+/// A forced timeout produces and broadcasts a timeout vote regardless of the local timer, which
+/// moves the round forward once aggregated.
+fn force_timeout_broadcasts_timeout_vote() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 1);
+    let node = &mut nodes[0];
+
+    timed_block_on(&mut runtime, async {
+        node.next_proposal().await;
+        // only the current round can be forced to time out
+        assert!(node.round_manager.force_timeout(2).await.is_err());
+
+        node.round_manager.force_timeout(1).await.unwrap();
+        let timeout_vote_msg = node.next_vote().await;
+        assert!(timeout_vote_msg.vote().is_timeout());
+        assert_eq!(timeout_vote_msg.vote().vote_data().proposed().round(), 1);
+
+        node.round_manager
+            .process_vote(timeout_vote_msg)
+            .await
+            .unwrap();
+        assert_eq!(node.round_manager.round_state().current_round(), 2);
+    });
+}
+
+#[test]
+/// Votes for NIL blocks are counted and still form QCs
+fn nil_vote_counted() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 1);
+    let node = &mut nodes[0];
+
+    timed_block_on(&mut runtime, async {
+        node.next_proposal().await;
+        node.round_manager.process_local_timeout(1).await.unwrap();
+        let nil_vote_msg = node.next_vote().await;
+        let nil_block_id = nil_vote_msg.vote().vote_data().proposed().id();
+        assert!(node
+            .block_store
+            .get_block(nil_block_id)
+            .unwrap()
+            .is_nil_block());
+
+        let nil_votes = counters::NIL_VOTE_RECEIVED_COUNT.get();
+        node.round_manager.process_vote(nil_vote_msg).await.unwrap();
+        assert!(counters::NIL_VOTE_RECEIVED_COUNT.get() > nil_votes);
+        assert!(node
+            .block_store
+            .get_quorum_cert_for_block(nil_block_id)
+            .is_some());
+        assert_eq!(node.round_manager.round_state().current_round(), 2);
+    });
+}
+
+#[derive(Default)]
+struct RecordingPeerScoreSink {
+    invalid: Mutex<Vec<(Author, String)>>,
+    useful: Mutex<Vec<Author>>,
+}
+
+impl PeerScoreSink for RecordingPeerScoreSink {
+    fn report_invalid(&self, peer: Author, reason: &str) {
+        self.invalid
+            .lock()
+            .unwrap()
+            .push((peer, reason.to_string()));
+    }
+
+    fn report_useful(&self, peer: Author) {
+        self.useful.lock().unwrap().push(peer);
+    }
+}
+
+#[test]
+/// A peer sending a SyncInfo that fails verification is reported as invalid exactly once
+fn invalid_sync_info_reported_to_peer_score_sink() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 1);
+    let node = &mut nodes[0];
+    let sink = Arc::new(RecordingPeerScoreSink::default());
+    node.round_manager.peer_score_sink = Some(sink.clone());
+
+    // a QC certified by a signer outside of the validator set
+    let outsider = ValidatorSigner::random([7; 32]);
+    let genesis_qc = certificate_for_genesis();
+    let block = Block::new_proposal(vec![], 10, 1, genesis_qc.clone(), &outsider);
+    let parent_block_info = genesis_qc.certified_block();
+    let forged_qc = gen_test_certificate(
+        vec![&outsider],
+        block.gen_block_info(
+            parent_block_info.executed_state_id(),
+            parent_block_info.version(),
+            parent_block_info.next_epoch_state().cloned(),
+        ),
+        parent_block_info.clone(),
+        None,
+    );
+    timed_block_on(&mut runtime, async {
+        node.round_manager
+            .sync_up(
+                &SyncInfo::new(forged_qc, genesis_qc.clone(), None),
+                outsider.author(),
+                false,
+            )
+            .await
+            .unwrap_err();
+    });
+    assert_eq!(
+        *sink.invalid.lock().unwrap(),
+        vec![(outsider.author(), "invalid sync info".to_string())]
+    );
+    assert!(sink.useful.lock().unwrap().is_empty());
+}
+
+#[test]
+/// A vote for a block we already have is added without syncing up to its SyncInfo, a stale
+/// voter isn't sent our SyncInfo back
+fn vote_for_known_block_skips_sync_up() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 1);
+    let node = &mut nodes[0];
+
+    timed_block_on(&mut runtime, async {
+        let proposal_msg = node.next_proposal().await;
+        node.round_manager
+            .process_proposal_msg(proposal_msg)
+            .await
+            .unwrap();
+        let vote_msg = node.next_vote().await;
+        node.round_manager
+            .process_vote(vote_msg.clone())
+            .await
+            .unwrap();
+        let next_proposal_msg = node.next_proposal().await;
+        assert_eq!(next_proposal_msg.proposal().round(), 2);
+
+        // the late vote carries a SyncInfo that lags behind ours
+        assert!(node
+            .block_store
+            .sync_info()
+            .has_newer_certificates(vote_msg.sync_info()));
+        node.round_manager.process_vote(vote_msg).await.unwrap();
+
+        // the next message is our vote, no SyncInfo was sent in between
+        node.round_manager
+            .process_proposal_msg(next_proposal_msg)
+            .await
+            .unwrap();
+        let vote_msg = node.next_vote().await;
+        assert_eq!(vote_msg.vote().vote_data().proposed().round(), 2);
+    });
+}
+
+#[test]
+/// With the gossip strategy a proposal is sent to exactly `fanout` other validators
+fn gossip_proposal_sent_to_fanout_peers() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut config = ConsensusConfig::default();
+    config.broadcast_strategy = BroadcastStrategy::Gossip { fanout: 2 };
+    let mut nodes =
+        NodeSetup::create_nodes_with_config(&mut playground, runtime.handle().clone(), 4, config);
+    timed_block_on(&mut runtime, async {
+        // node 0 is the proposer of every round
+        let proposal_msg = nodes[0].next_proposal().await;
+        assert_eq!(proposal_msg.proposal().round(), 1);
+        playground
+            .wait_for_messages(2, NetworkPlayground::proposals_only)
+            .await;
+        let mut recipients = 0;
+        for node in nodes.iter_mut().skip(1) {
+            if let Some(event) = node.all_events.next().now_or_never() {
+                match event.unwrap().unwrap() {
+                    Event::Message((_, ConsensusMsg::ProposalMsg(p))) => {
+                        assert_eq!(p.proposal().id(), proposal_msg.proposal().id());
+                        recipients += 1;
+                    }
+                    _ => panic!("Unexpected Network Event"),
+                }
+            }
+        }
+        assert_eq!(recipients, 2);
+    });
+}
+
+#[test]
+/// With the gossip strategy the timeouts still are broadcast to all the validators
+fn gossip_timeout_sent_to_all_validators() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut config = ConsensusConfig::default();
+    config.broadcast_strategy = BroadcastStrategy::Gossip { fanout: 1 };
+    let mut nodes =
+        NodeSetup::create_nodes_with_config(&mut playground, runtime.handle().clone(), 4, config);
+    timed_block_on(&mut runtime, async {
+        nodes[0].next_proposal().await;
+        playground
+            .wait_for_messages(1, NetworkPlayground::proposals_only)
+            .await;
+        for node in nodes.iter_mut().skip(1) {
+            let _ = node.all_events.next().now_or_never();
+        }
+        nodes[0]
+            .round_manager
+            .process_local_timeout(1)
+            .await
+            .unwrap();
+        playground
+            .wait_for_messages(3, NetworkPlayground::timeout_votes_only)
+            .await;
+        for node in nodes.iter_mut().skip(1) {
+            match node.all_events.next().await.unwrap().unwrap() {
+                Event::Message((_, ConsensusMsg::VoteMsg(v))) => assert!(v.vote().is_timeout()),
+                _ => panic!("Unexpected Network Event"),
+            }
+        }
+    });
+}
+
+#[test]
+/// The votes are sent to the proposers of the configured number of upcoming rounds
+fn vote_sent_to_upcoming_leaders() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut config = ConsensusConfig::default();
+    config.vote_upcoming_leaders = 2;
+    let mut nodes =
+        NodeSetup::create_nodes_with_config(&mut playground, runtime.handle().clone(), 4, config);
+    let authors: Vec<_> = nodes.iter().map(|node| node.signer.author()).collect();
+    timed_block_on(&mut runtime, async {
+        let proposal_msg = nodes[0].next_proposal().await;
+        for node in nodes.iter_mut().skip(1) {
+            node.next_proposal().await;
+        }
+        // nodes 2 and 3 lead rounds 2 and 3
+        nodes[1].round_manager.proposer_election =
+            Box::new(RotatingProposer::new(authors.clone(), 1));
+        nodes[1]
+            .round_manager
+            .process_proposed_block(proposal_msg.proposal().clone())
+            .await
+            .unwrap();
+        playground
+            .wait_for_messages(2, NetworkPlayground::votes_only)
+            .await;
+        for (i, node) in nodes.iter_mut().enumerate() {
+            let received_vote = match node.all_events.next().now_or_never() {
+                Some(event) => match event.unwrap().unwrap() {
+                    Event::Message((author, ConsensusMsg::VoteMsg(_))) => {
+                        assert_eq!(author, authors[1]);
+                        true
+                    }
+                    _ => panic!("Unexpected Network Event"),
+                },
+                None => false,
+            };
+            assert_eq!(received_vote, i >= 2);
+        }
+    });
+}
+
+#[test]
+/// Our own proposal looped back to us after it's been processed is ignored without executing or
+/// voting for it again
+fn own_proposal_loopback_ignored() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 1);
+    let node = &mut nodes[0];
+
+    timed_block_on(&mut runtime, async {
+        let proposal_msg = node.next_proposal().await;
+        assert_eq!(proposal_msg.proposer(), node.signer.author());
+        node.round_manager
+            .process_proposal_msg(proposal_msg.clone())
+            .await
+            .unwrap();
+        node.next_vote().await;
+        let executed_block = node
+            .block_store
+            .get_block(proposal_msg.proposal().id())
+            .unwrap();
+
+        // without the guard the repeated vote would be refused with an error
+        node.round_manager
+            .process_proposal_msg(proposal_msg.clone())
+            .await
+            .unwrap();
+        assert!(Arc::ptr_eq(
+            &executed_block,
+            &node
+                .block_store
+                .get_block(proposal_msg.proposal().id())
+                .unwrap()
+        ));
+        assert!(node.all_events.next().now_or_never().is_none());
+    });
+}
+
+#[test]
+/// With a serve delay the block retrieval response is sent after the delay, and it's correct
+fn block_retrieval_served_after_delay() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut config = ConsensusConfig::default();
+    config.block_retrieval_serve_delay_ms = 200;
+    let mut node =
+        NodeSetup::create_nodes_with_config(&mut playground, runtime.handle().clone(), 1, config)
+            .pop()
+            .unwrap();
+
+    timed_block_on(&mut runtime, async {
+        let proposal_msg = node.next_proposal().await;
+        let block_id = proposal_msg.proposal().id();
+        node.round_manager
+            .process_proposal_msg(proposal_msg)
+            .await
+            .unwrap();
+
+        let (tx, rx) = oneshot::channel();
+        let request = IncomingBlockRetrievalRequest {
+            requester: node.signer.author(),
+            req: BlockRetrievalRequest::new(block_id, 2),
+            response_sender: tx,
+        };
+        let start = Instant::now();
+        node.round_manager
+            .process_block_retrieval(request)
+            .await
+            .unwrap();
+        let bytes = rx.await.unwrap().unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(200));
+        let response = match lcs::from_bytes(&bytes) {
+            Ok(ConsensusMsg::BlockRetrievalResponse(resp)) => *resp,
+            _ => panic!("block retrieval failure"),
+        };
+        assert_eq!(response.status(), BlockRetrievalStatus::Succeeded);
+        assert_eq!(response.blocks()[0].id(), block_id);
+        assert_eq!(response.blocks()[1].id(), node.block_store.root().id());
+    });
+}
+
+#[test]
+/// The recorded safety rules states are bounded and never move the last voted round backwards
+fn recent_consensus_states_recorded() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut config = ConsensusConfig::default();
+    config.consensus_state_history_size = 4;
+    let mut node =
+        NodeSetup::create_nodes_with_config(&mut playground, runtime.handle().clone(), 1, config)
+            .pop()
+            .unwrap();
+
+    timed_block_on(&mut runtime, async {
+        assert!(node.round_manager.recent_consensus_states().is_empty());
+        for _ in 0..3 {
+            let proposal_msg = node.next_proposal().await;
+            node.round_manager
+                .process_proposal_msg(proposal_msg)
+                .await
+                .unwrap();
+            let vote_msg = node.next_vote().await;
+            node.round_manager.process_vote(vote_msg).await.unwrap();
+        }
+        let states = node.round_manager.recent_consensus_states();
+        assert_eq!(states.len(), 4);
+        assert!(states
+            .windows(2)
+            .all(|w| w[0].last_voted_round() <= w[1].last_voted_round()));
+        assert_eq!(states.last().unwrap().last_voted_round(), 3);
+    });
+}
+
+#[test]
+/// Within the help interval a stale peer gets our SyncInfo once, unless our state advances
+fn sync_info_help_suppressed_within_interval() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut config = ConsensusConfig::default();
+    config.sync_info_help_interval_ms = 60_000;
+    let mut node =
+        NodeSetup::create_nodes_with_config(&mut playground, runtime.handle().clone(), 1, config)
+            .pop()
+            .unwrap();
+    runtime.spawn(playground.start());
+    let author = node.signer.author();
+    let stale_sync_info = SyncInfo::new(certificate_for_genesis(), certificate_for_genesis(), None);
+
+    timed_block_on(&mut runtime, async {
+        let proposal_msg = node.next_proposal().await;
+        node.round_manager
+            .process_proposal_msg(proposal_msg)
+            .await
+            .unwrap();
+        let vote_msg = node.next_vote().await;
+        node.round_manager.process_vote(vote_msg).await.unwrap();
+        let proposal_msg = node.next_proposal().await;
+
+        let suppressed = counters::SYNC_INFO_MSGS_SUPPRESSED_COUNT.get();
+        for _ in 0..2 {
+            node.round_manager
+                .sync_up(&stale_sync_info, author, true)
+                .await
+                .unwrap();
+        }
+        assert!(counters::SYNC_INFO_MSGS_SUPPRESSED_COUNT.get() > suppressed);
+        assert_eq!(node.next_sync_info().await.highest_certified_round(), 1);
+
+        // the second SyncInfo wasn't sent, the vote is the next message
+        node.round_manager
+            .process_proposal_msg(proposal_msg)
+            .await
+            .unwrap();
+        let vote_msg = node.next_vote().await;
+        node.round_manager.process_vote(vote_msg).await.unwrap();
+        let _ = node.next_proposal().await;
+
+        // our state advanced, the peer is helped again right away
+        node.round_manager
+            .sync_up(&stale_sync_info, author, true)
+            .await
+            .unwrap();
+        assert_eq!(node.next_sync_info().await.highest_certified_round(), 2);
+    });
+}
+
+#[test]
+/// The timing snapshot reflects the durations observed by the round latency histograms
+fn timing_snapshot_reflects_observations() {
+    let runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let node = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 1)
+        .pop()
+        .unwrap();
+
+    let before = node.round_manager.timing_snapshot();
+    counters::CREATION_TO_QC_S.observe_duration(Duration::from_millis(200));
+    counters::CREATION_TO_QC_S.observe_duration(Duration::from_millis(300));
+    let after = node.round_manager.timing_snapshot();
+
+    // other tests may observe durations concurrently
+    let creation_to_qc = &after.creation_to_qc;
+    assert!(creation_to_qc.sample_count >= before.creation_to_qc.sample_count + 2);
+    assert!(creation_to_qc.sample_sum >= before.creation_to_qc.sample_sum + 0.5 - 1e-9);
+    assert!(!creation_to_qc.buckets.is_empty());
+    for (bucket, (upper_bound, count)) in creation_to_qc.buckets.iter().enumerate() {
+        if *upper_bound >= 0.3 {
+            assert!(*count >= before.creation_to_qc.buckets[bucket].1 + 2);
+        }
+    }
+
+    // the snapshot can be exported
+    let json = serde_json::to_string(&after).unwrap();
+    assert_eq!(
+        serde_json::from_str::<RoundTimingSnapshot>(&json)
+            .unwrap()
+            .creation_to_qc
+            .sample_count,
+        creation_to_qc.sample_count
+    );
+}
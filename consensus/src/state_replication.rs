@@ -19,6 +19,12 @@ pub trait TxnManager: Send + Sync {
     /// result, which includes the specifics of what transactions succeeded and failed.
     async fn commit(&mut self, block: &Block, compute_result: &StateComputeResult) -> Result<()>;
 
+    /// Notifies about the committed blocks whose notifications might have been held back in
+    /// order to batch them.
+    async fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+
     /// Bypass the trait object non-clonable limit.
     fn _clone_box(&self) -> Box<dyn TxnManager>;
 
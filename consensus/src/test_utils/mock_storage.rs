@@ -4,7 +4,7 @@
 use crate::{
     epoch_manager::LivenessStorageData,
     persistent_liveness_storage::{
-        LedgerRecoveryData, PersistentLivenessStorage, RecoveryData, RootMetadata,
+        LedgerRecoveryData, PersistentLivenessStorage, RecoveryData, RootMetadata, SyncCheckpoint,
     },
 };
 use anyhow::Result;
@@ -32,6 +32,7 @@ pub struct MockSharedStorage {
 
     // Liveness state
     pub highest_timeout_certificate: Mutex<Option<TimeoutCertificate>>,
+    pub sync_checkpoint: Mutex<Option<SyncCheckpoint>>,
     pub validator_set: ValidatorSet,
 }
 
@@ -43,6 +44,7 @@ impl MockSharedStorage {
             lis: Mutex::new(HashMap::new()),
             last_vote: Mutex::new(None),
             highest_timeout_certificate: Mutex::new(None),
+            sync_checkpoint: Mutex::new(None),
             validator_set,
         }
     }
@@ -159,6 +161,7 @@ impl MockStorage {
             lis: Mutex::new(HashMap::new()),
             last_vote: Mutex::new(None),
             highest_timeout_certificate: Mutex::new(None),
+            sync_checkpoint: Mutex::new(None),
             validator_set: validator_set.clone(),
         });
         let genesis_li = LedgerInfo::mock_genesis(Some(validator_set));
@@ -259,6 +262,34 @@ impl PersistentLivenessStorage for MockStorage {
     fn libra_db(&self) -> Arc<dyn DbReader> {
         unimplemented!()
     }
+
+    fn save_sync_checkpoint(&self, checkpoint: &SyncCheckpoint) -> Result<()> {
+        self.shared_storage
+            .sync_checkpoint
+            .lock()
+            .unwrap()
+            .replace(checkpoint.clone());
+        Ok(())
+    }
+
+    fn retrieve_sync_checkpoint(&self) -> Result<Option<SyncCheckpoint>> {
+        Ok(self.shared_storage.sync_checkpoint.lock().unwrap().clone())
+    }
+
+    fn delete_sync_checkpoint(&self) -> Result<()> {
+        self.shared_storage.sync_checkpoint.lock().unwrap().take();
+        Ok(())
+    }
+
+    fn retrieve_block(&self, block_id: HashValue) -> Result<Option<Block>> {
+        Ok(self
+            .shared_storage
+            .block
+            .lock()
+            .unwrap()
+            .get(&block_id)
+            .cloned())
+    }
 }
 
 /// A storage that ignores any requests, used in the tests that don't care about the storage.
@@ -323,4 +354,20 @@ impl PersistentLivenessStorage for EmptyStorage {
     fn libra_db(&self) -> Arc<dyn DbReader> {
         unimplemented!()
     }
+
+    fn save_sync_checkpoint(&self, _: &SyncCheckpoint) -> Result<()> {
+        Ok(())
+    }
+
+    fn retrieve_sync_checkpoint(&self) -> Result<Option<SyncCheckpoint>> {
+        Ok(None)
+    }
+
+    fn delete_sync_checkpoint(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn retrieve_block(&self, _: HashValue) -> Result<Option<Block>> {
+        Ok(None)
+    }
 }
@@ -15,6 +15,7 @@ use libra_types::{
     vm_error::{StatusCode, VMStatus},
 };
 use rand::Rng;
+use std::time::Duration;
 
 #[derive(Clone)]
 pub struct MockTransactionManager {
@@ -25,7 +26,8 @@ pub struct MockTransactionManager {
 
 impl MockTransactionManager {
     pub fn new(consensus_to_mempool_sender: Option<mpsc::Sender<ConsensusRequest>>) -> Self {
-        let mempool_proxy = consensus_to_mempool_sender.map(MempoolProxy::new);
+        let mempool_proxy = consensus_to_mempool_sender
+            .map(|sender| MempoolProxy::new(sender, Duration::from_millis(0)));
         Self {
             rejected_txns: vec![],
             mempool_proxy,
@@ -6,6 +6,7 @@ use crate::{
     network::NetworkTask,
     network_interface::{ConsensusMsg, ConsensusNetworkEvents, ConsensusNetworkSender},
     network_tests::{NetworkPlayground, TwinId},
+    round_manager::RoundManagerHooks,
     test_utils::{
         consensus_runtime, timed_block_on, MockStateComputer, MockStorage, MockTransactionManager,
     },
@@ -92,6 +93,7 @@ impl SMRNode {
         reconfig_sender.push((), payload).unwrap();
         let (self_sender, self_receiver) = channel::new_test(1_024);
         let (timeout_sender, timeout_receiver) = channel::new_test(1_024);
+        let (backup_proposal_sender, backup_proposal_receiver) = channel::new_test(1_024);
 
         let runtime = Builder::new()
             .thread_name(format!("node-{}", smr_id))
@@ -107,14 +109,21 @@ impl SMRNode {
             self_sender,
             network_sender,
             timeout_sender,
+            backup_proposal_sender,
             txn_manager,
             state_computer,
             storage.clone(),
+            RoundManagerHooks::default(),
         );
         let (network_task, network_receiver) = NetworkTask::new(network_events, self_receiver);
 
         runtime.spawn(network_task.start());
-        runtime.spawn(epoch_mgr.start(timeout_receiver, network_receiver, reconfig_events));
+        runtime.spawn(epoch_mgr.start(
+            timeout_receiver,
+            backup_proposal_receiver,
+            network_receiver,
+            reconfig_events,
+        ));
         Self {
             config,
             smr_id,
@@ -12,19 +12,65 @@ use libra_mempool::{
     CommittedTransaction, ConsensusRequest, ConsensusResponse, TransactionExclusion,
 };
 use libra_types::transaction::TransactionStatus;
-use std::time::Duration;
+use std::{
+    mem,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 use tokio::time::timeout;
 
+#[cfg(test)]
+#[path = "txn_manager_test.rs"]
+mod txn_manager_test;
+
+/// Rejected transactions of the committed blocks that mempool hasn't been notified about yet
+#[derive(Default)]
+struct PendingRejections {
+    txns: Vec<CommittedTransaction>,
+    // time the oldest of the pending transactions was added
+    oldest: Option<Instant>,
+    // time of the last commit
+    last_commit: Option<Instant>,
+}
+
 /// Proxy interface to mempool
 #[derive(Clone)]
 pub struct MempoolProxy {
     consensus_to_mempool_sender: mpsc::Sender<ConsensusRequest>,
+    // The rejected transactions of the blocks committed back-to-back within this window are
+    // sent to mempool in a single notification
+    commit_coalescing_window: Duration,
+    // Shared by the clones, so that no pending rejection is lost when one of them is dropped
+    pending_rejections: Arc<Mutex<PendingRejections>>,
 }
 
 impl MempoolProxy {
-    pub fn new(consensus_to_mempool_sender: mpsc::Sender<ConsensusRequest>) -> Self {
+    pub fn new(
+        consensus_to_mempool_sender: mpsc::Sender<ConsensusRequest>,
+        commit_coalescing_window: Duration,
+    ) -> Self {
         Self {
             consensus_to_mempool_sender,
+            commit_coalescing_window,
+            pending_rejections: Arc::new(Mutex::new(PendingRejections::default())),
+        }
+    }
+
+    async fn notify_rejected(&mut self, rejected_txns: Vec<CommittedTransaction>) -> Result<()> {
+        if rejected_txns.is_empty() {
+            return Ok(());
+        }
+
+        let (callback, callback_rcv) = oneshot::channel();
+        let req = ConsensusRequest::RejectNotification(rejected_txns, callback);
+
+        // send to shared mempool
+        self.consensus_to_mempool_sender.clone().try_send(req)?;
+
+        if let Err(e) = timeout(Duration::from_secs(1), callback_rcv).await {
+            Err(format_err!("[consensus] txn manager did not receive ACK for commit notification sent to mempool on time: {:?}", e))
+        } else {
+            Ok(())
         }
     }
 }
@@ -66,38 +112,53 @@ impl TxnManager for MempoolProxy {
     // Consensus notifies mempool of committed transactions that were rejected
     async fn commit(&mut self, block: &Block, compute_results: &StateComputeResult) -> Result<()> {
         let mut rejected_txns = vec![];
-        let txns = match block.payload() {
-            Some(txns) => txns,
-            None => return Ok(()),
-        };
-        // skip the block metadata txn result
-        for (txn, status) in txns
-            .iter()
-            .zip_eq(compute_results.compute_status().iter().skip(1))
-        {
-            if let TransactionStatus::Discard(_) = status {
-                rejected_txns.push(CommittedTransaction {
-                    sender: txn.sender(),
-                    sequence_number: txn.sequence_number(),
-                });
+        if let Some(txns) = block.payload() {
+            // skip the block metadata txn result
+            for (txn, status) in txns
+                .iter()
+                .zip_eq(compute_results.compute_status().iter().skip(1))
+            {
+                if let TransactionStatus::Discard(_) = status {
+                    rejected_txns.push(CommittedTransaction {
+                        sender: txn.sender(),
+                        sequence_number: txn.sequence_number(),
+                    });
+                }
             }
         }
 
-        if rejected_txns.is_empty() {
-            return Ok(());
-        }
-
-        let (callback, callback_rcv) = oneshot::channel();
-        let req = ConsensusRequest::RejectNotification(rejected_txns, callback);
-
-        // send to shared mempool
-        self.consensus_to_mempool_sender.clone().try_send(req)?;
+        // Hold the rejections back while the blocks are committed back-to-back, unless they have
+        // been pending for the whole window already
+        let rejected_txns = {
+            let mut pending = self.pending_rejections.lock().unwrap();
+            let now = Instant::now();
+            let back_to_back = pending.last_commit.map_or(false, |last_commit| {
+                now.duration_since(last_commit) < self.commit_coalescing_window
+            });
+            pending.last_commit = Some(now);
+            if !rejected_txns.is_empty() {
+                pending.txns.extend(rejected_txns);
+                pending.oldest.get_or_insert(now);
+            }
+            let oldest_expired = pending.oldest.map_or(false, |oldest| {
+                now.duration_since(oldest) >= self.commit_coalescing_window
+            });
+            if back_to_back && !oldest_expired {
+                return Ok(());
+            }
+            pending.oldest = None;
+            mem::take(&mut pending.txns)
+        };
+        self.notify_rejected(rejected_txns).await
+    }
 
-        if let Err(e) = timeout(Duration::from_secs(1), callback_rcv).await {
-            Err(format_err!("[consensus] txn manager did not receive ACK for commit notification sent to mempool on time: {:?}", e))
-        } else {
-            Ok(())
-        }
+    async fn flush(&mut self) -> Result<()> {
+        let rejected_txns = {
+            let mut pending = self.pending_rejections.lock().unwrap();
+            pending.oldest = None;
+            mem::take(&mut pending.txns)
+        };
+        self.notify_rejected(rejected_txns).await
     }
 
     fn _clone_box(&self) -> Box<dyn TxnManager> {
@@ -0,0 +1,77 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    state_replication::TxnManager, test_utils::consensus_runtime, txn_manager::MempoolProxy,
+};
+use consensus_types::block::{
+    block_test_utils::{certificate_for_genesis, random_payload},
+    Block,
+};
+use executor_types::StateComputeResult;
+use futures::{channel::mpsc, StreamExt};
+use libra_crypto::HashValue;
+use libra_mempool::{ConsensusRequest, ConsensusResponse};
+use libra_types::{
+    transaction::TransactionStatus,
+    validator_signer::ValidatorSigner,
+    vm_error::{StatusCode, VMStatus},
+};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+#[test]
+fn test_rejected_txns_coalesced() {
+    let mut runtime = consensus_runtime();
+    let (sender, mut receiver) = mpsc::channel(10);
+    let mut proxy = MempoolProxy::new(sender, Duration::from_secs(3600));
+    // mempool acknowledging the notifications
+    let notifications = Arc::new(Mutex::new(vec![]));
+    let received = notifications.clone();
+    runtime.spawn(async move {
+        while let Some(req) = receiver.next().await {
+            if let ConsensusRequest::RejectNotification(txns, callback) = req {
+                received.lock().unwrap().push(
+                    txns.into_iter()
+                        .map(|txn| (txn.sender, txn.sequence_number))
+                        .collect::<Vec<_>>(),
+                );
+                let _ = callback.send(Ok(ConsensusResponse::CommitResponse()));
+            }
+        }
+    });
+
+    let signer = ValidatorSigner::random(None);
+    let mut rejected = vec![];
+    runtime.block_on(async {
+        for round in 1..=5 {
+            let payload = random_payload(2);
+            // the first txn of every block is rejected
+            rejected.push((payload[0].sender(), payload[0].sequence_number()));
+            let block =
+                Block::new_proposal(payload, round, round, certificate_for_genesis(), &signer);
+            let keep = TransactionStatus::Keep(VMStatus::new(StatusCode::EXECUTED));
+            let discard =
+                TransactionStatus::Discard(VMStatus::new(StatusCode::UNKNOWN_VALIDATION_STATUS));
+            // the first status is the one of the block metadata txn
+            let compute_result = StateComputeResult::new(
+                HashValue::zero(),
+                vec![],
+                0,
+                None,
+                vec![keep.clone(), discard, keep],
+                vec![],
+            );
+            proxy.commit(&block, &compute_result).await.unwrap();
+        }
+        // only the first block of the burst is notified right away
+        assert_eq!(notifications.lock().unwrap().len(), 1);
+        proxy.flush().await.unwrap();
+    });
+
+    let notifications = notifications.lock().unwrap();
+    assert_eq!(notifications.len(), 2);
+    assert_eq!(notifications.concat(), rejected);
+}
@@ -0,0 +1,76 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+};
+
+/// Map of bounded size evicting the least recently used entry once it's full.
+///
+/// Every access stamps the entry with a monotonically increasing tick, the entries are ordered by
+/// their last tick for eviction. A cache of capacity 0 stores nothing.
+pub struct LruCache<K, V> {
+    capacity: usize,
+    entries: HashMap<K, (V, u64)>,
+    recency: BTreeMap<u64, K>,
+    tick: u64,
+}
+
+impl<K: Eq + Hash + Clone, V> LruCache<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+        }
+    }
+
+    /// Returns the value of the key and marks it as the most recently used.
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        self.tick += 1;
+        let tick = self.tick;
+        let (value, last_used) = self.entries.get_mut(key)?;
+        self.recency.remove(last_used);
+        self.recency.insert(tick, key.clone());
+        *last_used = tick;
+        Some(value)
+    }
+
+    /// Inserts the value as the most recently used, evicting the least recently used entry if
+    /// the cache is full.
+    pub fn put(&mut self, key: K, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.tick += 1;
+        if let Some((_, last_used)) = self.entries.remove(&key) {
+            self.recency.remove(&last_used);
+        } else if self.entries.len() == self.capacity {
+            let oldest = self.recency.keys().next().cloned();
+            if let Some(key) = oldest.and_then(|tick| self.recency.remove(&tick)) {
+                self.entries.remove(&key);
+            }
+        }
+        self.recency.insert(self.tick, key.clone());
+        self.entries.insert(key, (value, self.tick));
+    }
+
+    /// Keeps only the entries the predicate holds for.
+    pub fn retain<F: FnMut(&K, &V) -> bool>(&mut self, mut f: F) {
+        let recency = &mut self.recency;
+        self.entries.retain(|key, (value, last_used)| {
+            let keep = f(key, value);
+            if !keep {
+                recency.remove(last_used);
+            }
+            keep
+        });
+    }
+
+    /// Number of entries currently cached.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
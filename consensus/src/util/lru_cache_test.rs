@@ -0,0 +1,26 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::util::lru_cache::LruCache;
+
+#[test]
+fn lru_cache_evicts_least_recently_used() {
+    let mut cache = LruCache::new(2);
+    cache.put(1, "a");
+    cache.put(2, "b");
+    // 1 becomes the most recently used, 2 is evicted next
+    assert_eq!(cache.get(&1), Some(&"a"));
+    cache.put(3, "c");
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.get(&2), None);
+    assert_eq!(cache.get(&1), Some(&"a"));
+    assert_eq!(cache.get(&3), Some(&"c"));
+
+    cache.retain(|key, _| *key > 1);
+    assert_eq!(cache.get(&1), None);
+    assert_eq!(cache.len(), 1);
+
+    let mut disabled = LruCache::new(0);
+    disabled.put(1, "a");
+    assert_eq!(disabled.len(), 0);
+}
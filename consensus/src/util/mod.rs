@@ -2,8 +2,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod config_subscription;
+pub mod lru_cache;
+#[cfg(test)]
+mod lru_cache_test;
 #[cfg(any(test, feature = "fuzzing"))]
 pub mod mock_time_service;
+pub mod rate_limiter;
+#[cfg(test)]
+mod rate_limiter_test;
 pub mod time_service;
 #[cfg(test)]
 mod time_service_test;
@@ -0,0 +1,71 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{collections::HashMap, hash::Hash, time::Duration};
+
+/// Per-key token bucket rate limiter.
+///
+/// Every key gets a bucket of `burst` tokens which is refilled at `rate_per_sec` tokens per
+/// second; each accepted request consumes one token. A bucket that has refilled completely is
+/// indistinguishable from a fresh one, so the buckets of idle keys are dropped. The time is
+/// given by the caller as the duration since an arbitrary origin, e.g. the `TimeService` one.
+pub struct RateLimiter<K> {
+    rate_per_sec: f64,
+    burst: f64,
+    buckets: HashMap<K, Bucket>,
+}
+
+struct Bucket {
+    tokens: f64,
+    last_update: Duration,
+}
+
+impl Bucket {
+    fn refill(&mut self, rate_per_sec: f64, burst: f64, now: Duration) {
+        let elapsed = now.checked_sub(self.last_update).unwrap_or_default();
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * rate_per_sec).min(burst);
+        self.last_update = now;
+    }
+}
+
+impl<K: Eq + Hash> RateLimiter<K> {
+    pub fn new(rate_per_sec: u64, burst: u64) -> Self {
+        Self {
+            rate_per_sec: rate_per_sec as f64,
+            burst: burst as f64,
+            buckets: HashMap::new(),
+        }
+    }
+
+    /// Consumes a token from the bucket of the given key, returns false if the bucket is empty.
+    pub fn try_acquire(&mut self, key: K, now: Duration) -> bool {
+        self.prune(now);
+        let (rate_per_sec, burst) = (self.rate_per_sec, self.burst);
+        let bucket = self.buckets.entry(key).or_insert(Bucket {
+            tokens: burst,
+            last_update: now,
+        });
+        bucket.refill(rate_per_sec, burst, now);
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Number of keys currently tracked.
+    #[cfg(test)]
+    pub fn num_tracked(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Drops the buckets of the keys that have been idle long enough to refill completely.
+    fn prune(&mut self, now: Duration) {
+        let (rate_per_sec, burst) = (self.rate_per_sec, self.burst);
+        self.buckets.retain(|_, bucket| {
+            bucket.refill(rate_per_sec, burst, now);
+            bucket.tokens < burst
+        });
+    }
+}
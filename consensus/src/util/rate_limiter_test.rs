@@ -0,0 +1,28 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::util::rate_limiter::RateLimiter;
+use std::time::Duration;
+
+#[test]
+fn rate_limiter_refills_and_prunes_idle_keys() {
+    let mut limiter = RateLimiter::new(2, 3);
+    let start = Duration::from_secs(1);
+    for _ in 0..3 {
+        assert!(limiter.try_acquire(1, start));
+    }
+    assert!(!limiter.try_acquire(1, start));
+    // other keys have their own bucket
+    assert!(limiter.try_acquire(2, start));
+    assert_eq!(limiter.num_tracked(), 2);
+
+    // half a second refills one token
+    let later = start + Duration::from_millis(500);
+    assert!(limiter.try_acquire(1, later));
+    assert!(!limiter.try_acquire(1, later));
+
+    // both buckets are full again after two seconds, they're dropped until the next request
+    let idle = start + Duration::from_secs(3);
+    assert!(limiter.try_acquire(3, idle));
+    assert_eq!(limiter.num_tracked(), 1);
+}
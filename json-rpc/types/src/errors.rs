@@ -29,6 +29,10 @@ pub enum ServerCode {
     MempoolInvalidUpdate = -32010,
     MempoolVmError = -32011,
     MempoolUnknownError = -32012,
+    MempoolGasPriceTooLow = -32013,
+    MempoolSequenceNumberTooNew = -32014,
+    MempoolAlreadyKnown = -32015,
+    MempoolClassQuotaFull = -32016,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -91,6 +95,10 @@ impl JsonRpcError {
             MempoolStatusCode::InvalidUpdate => ServerCode::MempoolInvalidUpdate,
             MempoolStatusCode::VmError => ServerCode::MempoolVmError,
             MempoolStatusCode::UnknownStatus => ServerCode::MempoolUnknownError,
+            MempoolStatusCode::GasPriceTooLow => ServerCode::MempoolGasPriceTooLow,
+            MempoolStatusCode::SequenceNumberTooNew => ServerCode::MempoolSequenceNumberTooNew,
+            MempoolStatusCode::AlreadyKnown => ServerCode::MempoolAlreadyKnown,
+            MempoolStatusCode::ClassQuotaFull => ServerCode::MempoolClassQuotaFull,
             MempoolStatusCode::Accepted => {
                 return Err(anyhow::format_err!(
                     "[JSON RPC] cannot create mempool error for mempool accepted status"
@@ -3,7 +3,9 @@
 
 use admission_control_service::admission_control_service::AdmissionControlService;
 use backup_service::start_backup_service;
-use consensus::{consensus_provider::start_consensus, gen_consensus_reconfig_subscription};
+use consensus::{
+    consensus_provider::start_consensus, gen_consensus_reconfig_subscription, RoundManagerHooks,
+};
 use debug_interface::node_debug_service::NodeDebugService;
 use executor::{db_bootstrapper::bootstrap_db_if_empty, Executor};
 use executor_types::ChunkExecutor;
@@ -348,6 +350,8 @@ pub fn setup_environment(node_config: &mut NodeConfig) -> LibraHandle {
         consensus_requests,
         state_sync_requests,
         mempool_reconfig_events,
+        None,
+        None,
     );
     debug!("Mempool started in {} ms", instant.elapsed().as_millis());
 
@@ -386,6 +390,7 @@ pub fn setup_environment(node_config: &mut NodeConfig) -> LibraHandle {
             consensus_to_mempool_sender,
             libra_db,
             consensus_reconfig_events,
+            RoundManagerHooks::default(),
         ));
         debug!("Consensus started in {} ms", instant.elapsed().as_millis());
     }
@@ -0,0 +1,11 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use libra_types::{mempool_status::MempoolStatus, transaction::SignedTransaction};
+
+/// Custom admission logic (e.g. account allowlists) consulted before a transaction is added to
+/// Mempool
+pub trait AdmissionFilter: Send + Sync {
+    /// Returns the status reported back to the submitter if the transaction is rejected
+    fn admit(&self, txn: &SignedTransaction) -> Result<(), MempoolStatus>;
+}
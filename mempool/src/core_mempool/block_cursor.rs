@@ -0,0 +1,44 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::core_mempool::index::{OrderedQueueKey, TxnPointer};
+use libra_types::account_address::AccountAddress;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Where the walk of the priority queue for a block stopped, so that the next page of the block
+/// can be assembled without walking the queue from the top again (see `get_block_paged`).
+/// A cursor is only valid as long as Mempool isn't mutated in between the pages
+#[derive(Debug)]
+pub struct BlockCursor {
+    // transactions sent to Consensus, including the ones returned by the previous pages
+    pub(crate) seen: HashSet<TxnPointer>,
+    // number of transactions of every account included in the block so far
+    pub(crate) account_txns: HashMap<AccountAddress, usize>,
+    // transactions that aren't next for their account yet, with their dependency, if any
+    pub(crate) skipped: HashMap<TxnPointer, Option<TxnPointer>>,
+    // transactions waiting for their dependency on a transaction of another account
+    pub(crate) waiting: HashMap<TxnPointer, Vec<TxnPointer>>,
+    // transactions that became includable when the previous page was already full
+    pub(crate) ready: VecDeque<TxnPointer>,
+    // the continuations of the chains in the in-flight blocks not walked yet, highest ranked last
+    pub(crate) continuations: Vec<OrderedQueueKey>,
+    // the last transaction of the queue walked, None if the walk of the queue didn't start
+    pub(crate) last_walked: Option<OrderedQueueKey>,
+    // whether the part of the block reserved for governance transactions was filled
+    pub(crate) governance_added: bool,
+}
+
+impl BlockCursor {
+    pub(crate) fn new(seen: HashSet<TxnPointer>, continuations: Vec<OrderedQueueKey>) -> Self {
+        Self {
+            seen,
+            account_txns: HashMap::new(),
+            skipped: HashMap::new(),
+            waiting: HashMap::new(),
+            ready: VecDeque::new(),
+            continuations,
+            last_walked: None,
+            governance_added: false,
+        }
+    }
+}
@@ -0,0 +1,19 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::SystemTime;
+
+/// Source of the current time for Mempool: system TTLs, cache expiration and latency metrics
+/// are all based on it
+pub trait MempoolClock: Send + Sync {
+    fn now(&self) -> SystemTime;
+}
+
+/// Clock backed by the system time
+pub struct SystemClock;
+
+impl MempoolClock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
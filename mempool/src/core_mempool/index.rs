@@ -6,7 +6,10 @@ use crate::core_mempool::transaction::{MempoolTransaction, TimelineState};
 use libra_types::account_address::AccountAddress;
 use std::{
     cmp::Ordering,
-    collections::{btree_set::Iter, BTreeMap, BTreeSet},
+    collections::{
+        btree_set::{Iter, Range},
+        BTreeMap, BTreeSet,
+    },
     iter::Rev,
     ops::Bound,
     time::Duration,
@@ -17,6 +20,8 @@ pub type AccountTransactions = BTreeMap<u64, MempoolTransaction>;
 /// PriorityIndex represents main Priority Queue in Mempool
 /// It's used to form transaction block for Consensus
 /// Transactions are ordered by gas price. Second level ordering is done by expiration time
+/// Remaining ties are broken by sender address and sequence number, so that nodes with identical
+/// Mempool contents produce identical blocks (see `OrderedQueueKey`)
 ///
 /// We don't store full content of transaction in index
 /// Instead we use `OrderedQueueKey` - logical reference to transaction in main store
@@ -25,6 +30,7 @@ pub struct PriorityIndex {
 }
 
 pub type PriorityQueueIter<'a> = Rev<Iter<'a, OrderedQueueKey>>;
+pub type PriorityQueueRangeIter<'a> = Rev<Range<'a, OrderedQueueKey>>;
 
 impl PriorityIndex {
     pub(crate) fn new() -> Self {
@@ -47,6 +53,11 @@ impl PriorityIndex {
         self.data.contains(&self.make_key(txn))
     }
 
+    /// returns the key of transaction if it's in the index
+    pub(crate) fn get(&self, txn: &MempoolTransaction) -> Option<&OrderedQueueKey> {
+        self.data.get(&self.make_key(txn))
+    }
+
     fn make_key(&self, txn: &MempoolTransaction) -> OrderedQueueKey {
         OrderedQueueKey {
             gas_ranking_score: txn.ranking_score,
@@ -62,6 +73,19 @@ impl PriorityIndex {
         self.data.iter().rev()
     }
 
+    /// returns iterator over the part of the priority queue ranked below `key`, starting from
+    /// the highest ranked transaction
+    pub(crate) fn iter_below(&self, key: &OrderedQueueKey) -> PriorityQueueRangeIter {
+        self.data
+            .range((Bound::Unbounded, Bound::Excluded(key)))
+            .rev()
+    }
+
+    /// returns iterator over priority queue starting from the lowest ranked transaction
+    pub(crate) fn iter_lowest_first(&self) -> Iter<OrderedQueueKey> {
+        self.data.iter()
+    }
+
     pub(crate) fn size(&self) -> usize {
         self.data.len()
     }
@@ -76,12 +100,24 @@ pub struct OrderedQueueKey {
     pub is_governance_txn: bool,
 }
 
+impl OrderedQueueKey {
+    /// Pointer to the transaction of the same account preceding this one, if any
+    pub(crate) fn previous(&self) -> Option<TxnPointer> {
+        self.sequence_number
+            .checked_sub(1)
+            .map(|sequence_number| (self.address, sequence_number))
+    }
+}
+
 impl PartialOrd for OrderedQueueKey {
     fn partial_cmp(&self, other: &OrderedQueueKey) -> Option<Ordering> {
         Some(self.cmp(other))
     }
 }
 
+/// Keys are iterated from the highest to the lowest, i.e. in the order of:
+/// governance transactions first, `gas_ranking_score` desc, `expiration_time` asc,
+/// `address` desc, `sequence_number` asc
 impl Ord for OrderedQueueKey {
     fn cmp(&self, other: &OrderedQueueKey) -> Ordering {
         match self.is_governance_txn.cmp(&other.is_governance_txn) {
@@ -203,7 +239,7 @@ impl TimelineIndex {
 
     /// read all transactions from timeline since <timeline_id>
     pub(crate) fn read_timeline(
-        &mut self,
+        &self,
         timeline_id: u64,
         count: usize,
     ) -> Vec<(AccountAddress, u64)> {
@@ -222,7 +258,7 @@ impl TimelineIndex {
 
     /// read all transactions from timeline for timeline id in range (`start_timeline_id`, `end_timeline_id`]
     pub(crate) fn range(
-        &mut self,
+        &self,
         start_timeline_id: u64,
         end_timeline_id: u64,
     ) -> Vec<(AccountAddress, u64)> {
@@ -236,6 +272,33 @@ impl TimelineIndex {
         batch
     }
 
+    /// timeline id of the most recently added transaction, 0 if there's none
+    pub(crate) fn latest_timeline_id(&self) -> u64 {
+        self.timeline_id - 1
+    }
+
+    pub(crate) fn size(&self) -> usize {
+        self.timeline.len()
+    }
+
+    /// renumber the transactions to consecutive timeline ids starting from 1, in timeline order
+    /// returns the (old, new) timeline ids of the transactions
+    pub(crate) fn compact(&mut self) -> Vec<(u64, u64)> {
+        let timeline = std::mem::take(&mut self.timeline);
+        let mut renumbered = Vec::with_capacity(timeline.len());
+        for (new_id, (old_id, txn)) in (1..).zip(timeline) {
+            self.timeline.insert(new_id, txn);
+            renumbered.push((old_id, new_id));
+        }
+        self.timeline_id = renumbered.len() as u64 + 1;
+        renumbered
+    }
+
+    /// iterate over the whole timeline in timeline id order
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&u64, &(AccountAddress, u64))> {
+        self.timeline.iter()
+    }
+
     /// add transaction to index
     pub(crate) fn insert(&mut self, txn: &mut MempoolTransaction) {
         self.timeline.insert(
@@ -284,6 +347,11 @@ impl ParkingLotIndex {
         self.data.iter().rev().next().cloned()
     }
 
+    /// iterates over "non-ready" transactions in the order `pop` returns them
+    pub(crate) fn iter(&self) -> Rev<Iter<TxnPointer>> {
+        self.data.iter().rev()
+    }
+
     pub(crate) fn size(&self) -> usize {
         self.data.len()
     }
@@ -299,6 +367,12 @@ impl From<&MempoolTransaction> for TxnPointer {
     }
 }
 
+impl From<&TTLOrderingKey> for TxnPointer {
+    fn from(key: &TTLOrderingKey) -> Self {
+        (key.address, key.sequence_number)
+    }
+}
+
 impl From<&OrderedQueueKey> for TxnPointer {
     fn from(key: &OrderedQueueKey) -> Self {
         (key.address, key.sequence_number)
@@ -3,26 +3,39 @@
 
 //! mempool is used to track transactions which have been submitted but not yet
 //! agreed upon.
+#[cfg(test)]
+use crate::core_mempool::mempool_snapshot::MempoolSnapshot;
 use crate::{
     core_mempool::{
-        index::TxnPointer,
-        transaction::{MempoolTransaction, TimelineState},
+        admission_filter::AdmissionFilter,
+        block_cursor::BlockCursor,
+        clock::{MempoolClock, SystemClock},
+        index::{OrderedQueueKey, TxnPointer},
+        mempool_stats::MempoolStats,
+        timeline_remapping::TimelineRemapping,
+        transaction::{
+            MempoolTransaction, TimelineNotifications, TimelineState, TimelineStateCallback,
+            TransactionView,
+        },
         transaction_store::TransactionStore,
         ttl_cache::TtlCache,
+        txn_revalidator::TxnRevalidator,
     },
-    OP_COUNTERS,
+    counters, OP_COUNTERS,
 };
+use anyhow::{ensure, Result};
 use debug_interface::prelude::*;
 use libra_config::config::NodeConfig;
+use libra_crypto::hash::{CryptoHash, HashValue};
 use libra_logger::prelude::*;
 use libra_types::{
     account_address::AccountAddress,
     mempool_status::{MempoolStatus, MempoolStatusCode},
-    transaction::SignedTransaction,
+    transaction::{SignedTransaction, Transaction},
 };
 use std::{
-    cmp::max,
-    collections::HashSet,
+    cmp::{max, min},
+    collections::{HashMap, HashSet, VecDeque},
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
@@ -36,22 +49,335 @@ pub struct Mempool {
     // used to measure e2e latency of transaction in system, as well as time it takes to pick it up
     // by consensus
     pub(crate) metrics_cache: TtlCache<(AccountAddress, u64), SystemTime>,
+    // recently committed transactions, a delayed re-broadcast of one of them is rejected without
+    // looking into the store
+    committed_cache: TtlCache<(AccountAddress, u64), ()>,
+    // hashes of recently added transactions by sender and sequence number, an identical copy of
+    // one of them that's still resident is recognized as already known without being processed
+    // again. None if the check is disabled
+    known_txn_hashes: Option<TtlCache<TxnPointer, HashValue>>,
     pub system_transaction_timeout: Duration,
+    // max number of outstanding transactions per account
+    max_txns_per_account: usize,
+    // max distance between the sequence number of an admitted transaction and the current
+    // sequence number of its account
+    max_sequence_gap: u64,
+    // min gas price of non-governance transactions admitted into Mempool
+    min_gas_price: u64,
+    // min gas price while Mempool is not under pressure, the floor the adjustment starts from
+    base_min_gas_price: u64,
+    // min gas price when Mempool is full, 0 if the min gas price isn't adjusted to the occupancy
+    max_gas_floor: u64,
+    // occupancy of Mempool above which the min gas price is raised
+    gas_floor_occupancy_threshold: f64,
+    capacity: usize,
+    // fraction of a block reserved for governance transactions and their ancestors
+    governance_reserved_fraction: f64,
+    // max number of transactions of a single account in a block
+    max_txns_per_account_per_block: Option<usize>,
+    // max number of transactions returned by a single timeline read
+    max_timeline_read: usize,
+    // increase of the ranking score of a transaction for every second it's resident in Mempool
+    ranking_aging_coefficient: f64,
+    // share the block space across the senders by weighted round-robin
+    fair_queuing: bool,
+    // weights of the senders under fair queuing, the others have weight 1
+    account_weights: HashMap<AccountAddress, u64>,
+    // number of timeline compactions so far
+    timeline_epoch: u64,
+    // source of the current time
+    clock: Box<dyn MempoolClock>,
+    // custom admission logic consulted before a transaction is added
+    admission_filter: Option<Box<dyn AdmissionFilter>>,
 }
 
 impl Mempool {
-    pub fn new(config: &NodeConfig) -> Self {
+    /// Creates Mempool, the transactions rejected by the admission filter (if any) are not added
+    pub fn new(config: &NodeConfig, admission_filter: Option<Box<dyn AdmissionFilter>>) -> Self {
+        Self::new_with_clock(config, admission_filter, Box::new(SystemClock))
+    }
+
+    /// Creates Mempool that reads the current time from the given clock
+    pub fn new_with_clock(
+        config: &NodeConfig,
+        admission_filter: Option<Box<dyn AdmissionFilter>>,
+        clock: Box<dyn MempoolClock>,
+    ) -> Self {
         Mempool {
             transactions: TransactionStore::new(&config.mempool),
             sequence_number_cache: TtlCache::new(config.mempool.capacity, Duration::from_secs(100)),
             metrics_cache: TtlCache::new(config.mempool.capacity, Duration::from_secs(100)),
+            committed_cache: TtlCache::new(
+                config.mempool.capacity,
+                Duration::from_secs(config.mempool.committed_cache_ttl_secs),
+            ),
+            known_txn_hashes: match config.mempool.known_txn_hash_ttl_secs {
+                0 => None,
+                ttl_secs => Some(TtlCache::new(
+                    config.mempool.capacity,
+                    Duration::from_secs(ttl_secs),
+                )),
+            },
             system_transaction_timeout: Duration::from_secs(
                 config.mempool.system_transaction_timeout_secs,
             ),
+            max_txns_per_account: config.mempool.max_txns_per_account,
+            max_sequence_gap: config.mempool.max_sequence_gap,
+            min_gas_price: config.mempool.min_gas_price,
+            base_min_gas_price: config.mempool.min_gas_price,
+            max_gas_floor: config.mempool.max_gas_floor,
+            gas_floor_occupancy_threshold: config.mempool.gas_floor_occupancy_threshold,
+            capacity: config.mempool.capacity,
+            governance_reserved_fraction: config.mempool.governance_reserved_fraction,
+            max_txns_per_account_per_block: config.mempool.max_txns_per_account_per_block,
+            max_timeline_read: config.mempool.max_timeline_read,
+            ranking_aging_coefficient: config.mempool.ranking_aging_coefficient,
+            fair_queuing: config.mempool.fair_queuing,
+            account_weights: HashMap::new(),
+            timeline_epoch: 0,
+            clock,
+            admission_filter,
+        }
+    }
+
+    /// Sets the min gas price of newly admitted non-governance transactions.
+    /// Transactions already in Mempool are not affected. If the min gas price follows the
+    /// occupancy, this is the floor it returns to when Mempool drains
+    #[cfg(test)]
+    pub fn set_min_gas_price(&mut self, price: u64) {
+        self.min_gas_price = price;
+        self.base_min_gas_price = price;
+    }
+
+    /// Returns the min gas price currently enforced on non-governance transactions
+    #[cfg(test)]
+    pub fn min_gas_price(&self) -> u64 {
+        self.min_gas_price
+    }
+
+    /// Sets the weight of the sender's share of the block space under fair queuing, a weight of
+    /// `n` gets the sender up to `n` transactions every round. The weight is at least 1
+    #[cfg(test)]
+    pub fn set_account_weight(&mut self, sender: AccountAddress, weight: u64) {
+        if weight <= 1 {
+            self.account_weights.remove(&sender);
+        } else {
+            self.account_weights.insert(sender, weight);
+        }
+    }
+
+    /// Sets the callback notified of the timeline state transitions of the transactions, see
+    /// `TimelineStateCallback` for the constraints on it
+    pub fn set_timeline_state_callback(&mut self, callback: TimelineStateCallback) {
+        self.transactions.set_timeline_state_callback(callback);
+    }
+
+    /// Takes out the timeline state transitions queued by the updates since the last call. The
+    /// caller is expected to `notify` them once it has released the Mempool lock
+    pub fn take_timeline_notifications(&mut self) -> TimelineNotifications {
+        self.transactions.take_timeline_notifications()
+    }
+
+    /// Returns the transaction with the given sender and sequence number together with its
+    /// metadata, if it's resident in Mempool
+    pub fn get_transaction(
+        &self,
+        sender: &AccountAddress,
+        sequence_number: u64,
+    ) -> Option<TransactionView> {
+        self.transactions.get_with_metadata(sender, sequence_number)
+    }
+
+    /// Updates the ranking score and gas amount of a resident transaction in place, e.g. after the
+    /// sender bumped its gas price. The transaction keeps its timeline position, so it isn't
+    /// broadcast again. Returns false if the transaction is not in Mempool
+    #[cfg(test)]
+    pub fn update_rank(
+        &mut self,
+        sender: &AccountAddress,
+        seq: u64,
+        new_rankin_score: u64,
+        new_gas: u64,
+    ) -> bool {
+        let updated = self
+            .transactions
+            .update_rank(sender, seq, new_rankin_score, new_gas);
+        if updated {
+            OP_COUNTERS.inc("update_rank");
+        }
+        updated
+    }
+
+    /// Returns the gas price at each of the given percentiles (in the 0 to 100 range) of the
+    /// transactions ready to be broadcast, e.g. for fee estimation. The nearest-rank method is
+    /// used, all the percentiles are 0 if there is no such transaction.
+    #[cfg(test)]
+    pub fn gas_price_percentiles(&self, pcts: &[f64]) -> Vec<u64> {
+        let gas_prices = self.transactions.ready_gas_prices();
+        if gas_prices.is_empty() {
+            return vec![0; pcts.len()];
         }
+        pcts.iter()
+            .map(|pct| {
+                let rank = (pct.max(0.0).min(100.0) / 100.0 * gas_prices.len() as f64).ceil();
+                gas_prices[(rank as usize).max(1) - 1]
+            })
+            .collect()
+    }
+
+    /// Returns the first `n` transactions of the priority queue together with their metadata, in
+    /// descending priority order, e.g. for a fee oracle. Unlike `get_block`, nothing is recorded
+    #[cfg(test)]
+    pub fn top_transactions(&self, n: usize) -> Vec<TransactionView> {
+        self.transactions
+            .iter_queue()
+            .take(n)
+            .filter_map(|key| {
+                self.transactions
+                    .get_with_metadata(&key.address, key.sequence_number)
+            })
+            .collect()
+    }
+
+    /// Returns the accounts whose next transaction is resident in Mempool and can be included in
+    /// a block right away, in ascending order
+    #[cfg(test)]
+    pub fn ready_accounts(&self) -> Vec<AccountAddress> {
+        let mut accounts: Vec<_> = self
+            .transactions
+            .iter_queue()
+            .filter(|txn| self.is_next_for_account(&txn.address, txn.sequence_number))
+            .map(|txn| txn.address)
+            .collect();
+        accounts.sort();
+        accounts.dedup();
+        accounts
+    }
+
+    /// Returns the accounts whose lowest resident transaction doesn't follow their current
+    /// sequence number, along with the number of missing transactions, in ascending order
+    /// Unless the client resubmits the missing transactions, these accounts are stuck
+    #[cfg(test)]
+    pub fn stuck_accounts(&self) -> Vec<(AccountAddress, u64)> {
+        let mut accounts: Vec<_> = self
+            .transactions
+            .lowest_sequence_numbers()
+            .into_iter()
+            .filter_map(|(address, lowest_seq)| {
+                self.sequence_number_cache
+                    .get(&address)
+                    .filter(|seq| **seq < lowest_seq)
+                    .map(|seq| (address, lowest_seq - seq))
+            })
+            .collect();
+        accounts.sort();
+        accounts
+    }
+
+    /// Returns true if the transaction with the given sequence number is the next one to be
+    /// executed for the account
+    fn is_next_for_account(&self, address: &AccountAddress, sequence_number: u64) -> bool {
+        self.sequence_number_cache.get(address) == Some(&sequence_number)
+    }
+
+    /// Returns all transactions resident in Mempool, e.g. to persist them over a restart
+    #[cfg(test)]
+    pub fn snapshot(&self) -> MempoolSnapshot {
+        let transactions = self.transactions.views();
+        let sequence_numbers = transactions
+            .iter()
+            .filter_map(|view| {
+                let sender = view.txn.sender();
+                self.sequence_number_cache
+                    .get(&sender)
+                    .map(|sequence_number| (sender, *sequence_number))
+            })
+            .collect();
+        MempoolSnapshot {
+            transactions,
+            sequence_numbers,
+        }
+    }
+
+    /// Re-inserts the transactions of the snapshot in priority order, as if they were submitted
+    /// in that order: when they don't fit into Mempool or exceed the limit of their account, the
+    /// transactions that aren't ready and then the lowest ranked ones are dropped.
+    /// Their system TTL starts over from the current time, their position in the timeline is
+    /// reassigned.
+    /// The sequence number of every account is looked up once with `db_sequence_number`: the
+    /// snapshot misses the accounts whose cached sequence number expired and is stale for the
+    /// accounts with transactions committed since it was taken
+    #[cfg(test)]
+    pub fn restore<F>(&mut self, snapshot: MempoolSnapshot, db_sequence_number: F)
+    where
+        F: Fn(&AccountAddress) -> u64,
+    {
+        let MempoolSnapshot {
+            mut transactions,
+            sequence_numbers,
+        } = snapshot;
+        transactions.sort_by(|a, b| {
+            (b.is_governance_txn, b.ranking_score)
+                .cmp(&(a.is_governance_txn, a.ranking_score))
+                .then_with(|| a.expiration_time.cmp(&b.expiration_time))
+                .then_with(|| a.txn.sender().cmp(&b.txn.sender()))
+                .then_with(|| a.txn.sequence_number().cmp(&b.txn.sequence_number()))
+        });
+        let mut restored = 0;
+        let mut db_sequence_numbers = HashMap::new();
+        for view in transactions {
+            let sender = view.txn.sender();
+            let known_sequence_number = *db_sequence_numbers.entry(sender).or_insert_with(|| {
+                max(
+                    sequence_numbers.get(&sender).cloned().unwrap_or_default(),
+                    db_sequence_number(&sender),
+                )
+            });
+            let sequence_number = self.account_sequence_number(&sender, known_sequence_number);
+            self.sequence_number_cache
+                .insert_at(sender, sequence_number, self.clock.now());
+            let timeline_state = match view.timeline_state {
+                TimelineState::NonQualified => TimelineState::NonQualified,
+                _ => TimelineState::NotReady,
+            };
+            let status = self.insert_txn(
+                view.txn,
+                view.gas_amount,
+                view.ranking_score,
+                sequence_number,
+                timeline_state,
+                view.is_governance_txn,
+                view.depends_on,
+            );
+            if status.code == MempoolStatusCode::Accepted {
+                restored += 1;
+            }
+        }
+        info!("[Mempool] restored {} transactions from snapshot", restored);
+    }
+
+    /// Evicts all transactions of the given account, e.g. when its signing key is compromised.
+    /// The account is treated as unknown afterwards.
+    /// Returns the number of removed transactions
+    #[cfg(test)]
+    pub fn remove_account_transactions(&mut self, sender: &AccountAddress) -> usize {
+        let removed = self.transactions.remove_account(sender);
+        for sequence_number in removed.iter() {
+            self.metrics_cache.remove(&(*sender, *sequence_number));
+        }
+        self.sequence_number_cache.remove(sender);
+        OP_COUNTERS.inc_by("remove_account_transactions", removed.len());
+        debug!(
+            "[Mempool] removed {} transactions of account {}",
+            removed.len(),
+            sender
+        );
+        removed.len()
     }
 
     /// This function will be called once the transaction has been stored
+    /// A notification for a transaction the account's sequence number is already past (e.g. a
+    /// retried commit notification) is ignored
     pub(crate) fn remove_transaction(
         &mut self,
         sender: &AccountAddress,
@@ -65,6 +391,12 @@ impl Mempool {
             sequence_number,
             is_rejected
         );
+        if let Some(&current_seq_number) = self.sequence_number_cache.get(&sender) {
+            if current_seq_number > sequence_number {
+                counters::DUPLICATE_REMOVE_TRANSACTION_COUNT.inc();
+                return;
+            }
+        }
         self.log_latency(*sender, sequence_number, "e2e.latency");
         self.metrics_cache.remove(&(*sender, sequence_number));
         OP_COUNTERS.inc(&format!("remove_transaction.{}", is_rejected));
@@ -85,16 +417,59 @@ impl Mempool {
             }
         } else {
             // update current cached sequence number for account
-            let new_seq_number = max(current_seq_number, sequence_number + 1);
-            self.sequence_number_cache.insert(*sender, new_seq_number);
+            let new_seq_number = max(current_seq_number, sequence_number.saturating_add(1));
+            let now = self.clock.now();
+            self.sequence_number_cache
+                .insert_at(*sender, new_seq_number, now);
+            self.committed_cache
+                .insert_at((*sender, sequence_number), (), now);
+            if sequence_number == u64::MAX {
+                // no transaction can follow the one with the max sequence number
+                self.transactions.remove_account(&sender);
+            } else {
+                self.transactions
+                    .commit_transaction(&sender, new_seq_number);
+            }
+        }
+    }
+
+    /// Commits all transactions of the account up to `up_to_seq` inclusive in one pass: the
+    /// account's sequence number is advanced to `up_to_seq + 1` and the resident transactions
+    /// below it are removed
+    pub(crate) fn commit_transactions(&mut self, sender: &AccountAddress, up_to_seq: u64) {
+        let current_seq_number = self
+            .sequence_number_cache
+            .remove(&sender)
+            .unwrap_or_default();
+        let new_seq_number = max(current_seq_number, up_to_seq.saturating_add(1));
+        let now = self.clock.now();
+        self.sequence_number_cache
+            .insert_at(*sender, new_seq_number, now);
+        let removed = if up_to_seq == u64::MAX {
+            // no transaction can follow the one with the max sequence number
+            self.transactions.remove_account(&sender)
+        } else {
             self.transactions
-                .commit_transaction(&sender, new_seq_number);
+                .commit_transaction(&sender, new_seq_number)
+        };
+        for &sequence_number in removed.iter() {
+            self.log_latency(*sender, sequence_number, "e2e.latency");
+            self.metrics_cache.remove(&(*sender, sequence_number));
+            self.committed_cache
+                .insert_at((*sender, sequence_number), (), now);
         }
+        OP_COUNTERS.inc_by("commit_transactions", removed.len());
+        trace!(
+            "[Mempool] committed {} transactions of account {} up to {}",
+            removed.len(),
+            sender,
+            up_to_seq
+        );
     }
 
     fn log_latency(&mut self, account: AccountAddress, sequence_number: u64, metric: &str) {
         if let Some(&creation_time) = self.metrics_cache.get(&(account, sequence_number)) {
-            if let Ok(time_delta) = SystemTime::now().duration_since(creation_time) {
+            if let Ok(time_delta) = self.clock.now().duration_since(creation_time) {
                 OP_COUNTERS.observe_duration(metric, time_delta);
             }
         }
@@ -102,6 +477,8 @@ impl Mempool {
 
     /// Used to add a transaction to the Mempool
     /// Performs basic validation: checks account's sequence number
+    #[allow(clippy::too_many_arguments)]
+    #[cfg(test)]
     pub(crate) fn add_txn(
         &mut self,
         txn: SignedTransaction,
@@ -110,19 +487,172 @@ impl Mempool {
         db_sequence_number: u64,
         timeline_state: TimelineState,
         is_governance_txn: bool,
+        depends_on: Option<TxnPointer>,
+    ) -> MempoolStatus {
+        let hash = match self.check_known(&txn) {
+            Ok(hash) => hash,
+            Err(status) => return status,
+        };
+        let pointer = (txn.sender(), txn.sequence_number());
+        if let Err(status) = self.admit(&txn) {
+            return status;
+        }
+        let sequence_number = self.account_sequence_number(&txn.sender(), db_sequence_number);
+        self.sequence_number_cache
+            .insert_at(txn.sender(), sequence_number, self.clock.now());
+        let status = self.insert_txn(
+            txn,
+            gas_amount,
+            rankin_score,
+            sequence_number,
+            timeline_state,
+            is_governance_txn,
+            depends_on,
+        );
+        self.record_known(pointer, hash, &status);
+        status
+    }
+
+    /// Used to add a batch of transactions to the Mempool
+    /// Account's sequence number is looked up in the cache once per account and reused for
+    /// the rest of the batch. Transactions are inserted in batch order
+    /// Returns insertion status for every transaction in the batch
+    pub(crate) fn add_txns(
+        &mut self,
+        txns: Vec<(SignedTransaction, u64, u64, u64, TimelineState, bool)>,
+    ) -> Vec<MempoolStatus> {
+        let mut sequence_numbers: HashMap<AccountAddress, u64> = HashMap::new();
+        let mut statuses = vec![];
+        for (
+            txn,
+            gas_amount,
+            rankin_score,
+            db_sequence_number,
+            timeline_state,
+            is_governance_txn,
+        ) in txns
+        {
+            let hash = match self.check_known(&txn) {
+                Ok(hash) => hash,
+                Err(status) => {
+                    statuses.push(status);
+                    continue;
+                }
+            };
+            if let Err(status) = self.admit(&txn) {
+                statuses.push(status);
+                continue;
+            }
+            let sender = txn.sender();
+            let pointer = (sender, txn.sequence_number());
+            let sequence_number = match sequence_numbers.get(&sender) {
+                Some(sequence_number) => max(*sequence_number, db_sequence_number),
+                None => self.account_sequence_number(&sender, db_sequence_number),
+            };
+            sequence_numbers.insert(sender, sequence_number);
+            let status = self.insert_txn(
+                txn,
+                gas_amount,
+                rankin_score,
+                sequence_number,
+                timeline_state,
+                is_governance_txn,
+                None,
+            );
+            self.record_known(pointer, hash, &status);
+            statuses.push(status);
+        }
+        for (sender, sequence_number) in sequence_numbers {
+            self.sequence_number_cache
+                .insert_at(sender, sequence_number, self.clock.now());
+        }
+        statuses
+    }
+
+    /// returns the hash of the transaction (None if the check is disabled), or the already known
+    /// status if an identical transaction has been added recently and is still resident
+    fn check_known(&self, txn: &SignedTransaction) -> Result<Option<HashValue>, MempoolStatus> {
+        let known = match self.known_txn_hashes.as_ref() {
+            Some(known) => known,
+            None => return Ok(None),
+        };
+        let hash = Transaction::UserTransaction(txn.clone()).hash();
+        let (sender, sequence_number) = (txn.sender(), txn.sequence_number());
+        // a transaction that left Mempool, whichever way, isn't known anymore
+        if known.get(&(sender, sequence_number)) == Some(&hash)
+            && self.transactions.contains(&sender, sequence_number)
+        {
+            OP_COUNTERS.inc("already_known");
+            return Err(
+                MempoolStatus::new(MempoolStatusCode::AlreadyKnown).with_message(format!(
+                    "transaction {}:{} is already known",
+                    txn.sender(),
+                    txn.sequence_number(),
+                )),
+            );
+        }
+        Ok(Some(hash))
+    }
+
+    /// remembers the hash of an accepted transaction
+    fn record_known(&mut self, txn: TxnPointer, hash: Option<HashValue>, status: &MempoolStatus) {
+        if let (Some(known), Some(hash)) = (self.known_txn_hashes.as_mut(), hash) {
+            if status.code == MempoolStatusCode::Accepted {
+                known.insert_at(txn, hash, self.clock.now());
+            }
+        }
+    }
+
+    /// consults the admission filter (if any)
+    fn admit(&self, txn: &SignedTransaction) -> Result<(), MempoolStatus> {
+        match &self.admission_filter {
+            Some(filter) => filter.admit(txn).map_err(|status| {
+                OP_COUNTERS.inc("admission_filter_rejected");
+                status
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// returns the highest known sequence number of the account
+    fn account_sequence_number(&self, account: &AccountAddress, db_sequence_number: u64) -> u64 {
+        self.sequence_number_cache
+            .get(account)
+            .map_or(db_sequence_number, |value| max(*value, db_sequence_number))
+    }
+
+    /// inserts transaction given the account's current sequence number
+    #[allow(clippy::too_many_arguments)]
+    fn insert_txn(
+        &mut self,
+        txn: SignedTransaction,
+        gas_amount: u64,
+        rankin_score: u64,
+        sequence_number: u64,
+        timeline_state: TimelineState,
+        is_governance_txn: bool,
+        depends_on: Option<TxnPointer>,
     ) -> MempoolStatus {
         trace_event!("mempool::add_txn", {"txn", txn.sender(), txn.sequence_number()});
         trace!(
             "[Mempool] Adding transaction to mempool: {}:{}:{}",
             &txn.sender(),
             txn.sequence_number(),
-            db_sequence_number,
+            sequence_number,
         );
-        let cached_value = self.sequence_number_cache.get(&txn.sender());
-        let sequence_number =
-            cached_value.map_or(db_sequence_number, |value| max(*value, db_sequence_number));
-        self.sequence_number_cache
-            .insert(txn.sender(), sequence_number);
+
+        // don't accept transactions that have been committed recently
+        if self
+            .committed_cache
+            .get(&(txn.sender(), txn.sequence_number()))
+            .is_some()
+        {
+            return MempoolStatus::new(MempoolStatusCode::InvalidSeqNumber).with_message(format!(
+                "transaction {}:{} has already been committed",
+                txn.sender(),
+                txn.sequence_number(),
+            ));
+        }
 
         // don't accept old transactions (e.g. seq is less than account's current seq_number)
         if txn.sequence_number() < sequence_number {
@@ -133,13 +663,57 @@ impl Mempool {
             ));
         }
 
-        let expiration_time = SystemTime::now()
+        // don't let transactions far in the future stay parked indefinitely
+        if txn.sequence_number() - sequence_number > self.max_sequence_gap {
+            return MempoolStatus::new(MempoolStatusCode::SequenceNumberTooNew).with_message(
+                format!(
+                    "transaction sequence number is {}, current sequence number is {}, max sequence gap: {}",
+                    txn.sequence_number(),
+                    sequence_number,
+                    self.max_sequence_gap,
+                ),
+            );
+        }
+
+        // don't accept transactions below the current min gas price, governance transactions
+        // are always admitted
+        if !is_governance_txn && txn.gas_unit_price() < self.min_gas_price {
+            return MempoolStatus::new(MempoolStatusCode::GasPriceTooLow).with_message(format!(
+                "transaction gas price is {}, min gas price is {}",
+                txn.gas_unit_price(),
+                self.min_gas_price,
+            ));
+        }
+
+        // don't let a single account take up too much of mempool,
+        // an update of an existing transaction doesn't take up extra space
+        if !self
+            .transactions
+            .contains(&txn.sender(), txn.sequence_number())
+        {
+            let txns_count = self
+                .transactions
+                .outstanding_txns_count(&txn.sender(), sequence_number);
+            if txns_count >= self.max_txns_per_account {
+                return MempoolStatus::new(MempoolStatusCode::TooManyTransactions).with_message(
+                    format!(
+                        "too many txns for account {}: {}, max txns per account: {}",
+                        txn.sender(),
+                        txns_count,
+                        self.max_txns_per_account,
+                    ),
+                );
+            }
+        }
+
+        let now = self.clock.now();
+        let expiration_time = now
             .duration_since(UNIX_EPOCH)
             .expect("init timestamp failure")
             + self.system_transaction_timeout;
         if timeline_state != TimelineState::NonQualified {
             self.metrics_cache
-                .insert((txn.sender(), txn.sequence_number()), SystemTime::now());
+                .insert_at((txn.sender(), txn.sequence_number()), now, now);
         }
 
         let txn_info = MempoolTransaction::new(
@@ -149,6 +723,8 @@ impl Mempool {
             rankin_score,
             timeline_state,
             is_governance_txn,
+            depends_on,
+            now,
         );
 
         let status = self.transactions.insert(txn_info, sequence_number);
@@ -160,65 +736,76 @@ impl Mempool {
     /// `batch_size` - size of requested block
     /// `seen_txns` - transactions that were sent to Consensus but were not committed yet
     ///  Mempool should filter out such transactions
-    #[allow(clippy::explicit_counter_loop)]
     pub(crate) fn get_block(
         &mut self,
         batch_size: u64,
-        mut seen: HashSet<TxnPointer>,
+        seen: HashSet<TxnPointer>,
     ) -> Vec<SignedTransaction> {
-        let mut result = vec![];
-        // Helper DS. Helps to mitigate scenarios where account submits several transactions
-        // with increasing gas price (e.g. user submits transactions with sequence number 1, 2
-        // and gas_price 1, 10 respectively)
-        // Later txn has higher gas price and will be observed first in priority index iterator,
-        // but can't be executed before first txn. Once observed, such txn will be saved in
-        // `skipped` DS and rechecked once it's ancestor becomes available
-        let mut skipped = HashSet::new();
-        let seen_size = seen.len();
-        let mut txn_walked = 0usize;
-        // iterate over the queue of transactions based on gas price
-        'main: for txn in self.transactions.iter_queue() {
-            txn_walked += 1;
-            if seen.contains(&TxnPointer::from(txn)) {
-                continue;
-            }
-            let seq = txn.sequence_number;
-            let account_sequence_number = self.sequence_number_cache.get(&txn.address);
-            let seen_previous = seq > 0 && seen.contains(&(txn.address, seq - 1));
-            // include transaction if it's "next" for given account or
-            // we've already sent its ancestor to Consensus
-            if seen_previous || account_sequence_number == Some(&seq) {
-                let ptr = TxnPointer::from(txn);
-                seen.insert(ptr);
-                trace_event!("mempool::get_block", {"txn", txn.address, txn.sequence_number});
-                result.push(ptr);
-                if (result.len() as u64) == batch_size {
-                    break;
-                }
+        self.get_block_with_max_txns_per_account(
+            batch_size,
+            seen,
+            self.max_txns_per_account_per_block,
+        )
+    }
 
-                // check if we can now include some transactions
-                // that were skipped before for given account
-                let mut skipped_txn = (txn.address, seq + 1);
-                while skipped.contains(&skipped_txn) {
-                    seen.insert(skipped_txn);
-                    result.push(skipped_txn);
-                    if (result.len() as u64) == batch_size {
-                        break 'main;
-                    }
-                    skipped_txn = (txn.address, skipped_txn.1 + 1);
-                }
-            } else {
-                skipped.insert(TxnPointer::from(txn));
-            }
-        }
+    /// Same as `get_block`, but includes at most `max_txns_per_account_per_block` transactions
+    /// of every account (if specified). The remaining transactions of an account that reached the
+    /// limit are skipped for this block only
+    pub(crate) fn get_block_with_max_txns_per_account(
+        &mut self,
+        batch_size: u64,
+        seen: HashSet<TxnPointer>,
+        max_txns_per_account_per_block: Option<usize>,
+    ) -> Vec<SignedTransaction> {
+        let result = self.eligible_txns(batch_size, seen, max_txns_per_account_per_block);
+        self.block_txns(result)
+    }
+
+    /// Fetches the next page of up to `batch_size` transactions of a block for consensus, so that
+    /// a large block can be assembled incrementally. Given the cursor of the previous page, the
+    /// walk of the priority queue resumes where that page stopped: the pages add up to the block
+    /// a single `get_block` call of their total size returns, except that the governance share
+    /// is reserved from the first page. `seen` is only consulted by the first page, the cursor
+    /// carries it along. No cursor is returned once the queue is walked to the end
+    /// Under fair queuing every page shares its space across the senders on its own
+    #[cfg(test)]
+    pub(crate) fn get_block_paged(
+        &mut self,
+        batch_size: u64,
+        seen: HashSet<TxnPointer>,
+        cursor: Option<BlockCursor>,
+    ) -> (Vec<SignedTransaction>, Option<BlockCursor>) {
+        let mut cursor = cursor.unwrap_or_else(|| self.block_cursor(seen));
+        let max_txns_per_account_per_block = self.max_txns_per_account_per_block;
+        let (result, exhausted) = if self.fair_queuing {
+            let result = self.fair_queued_txns(
+                batch_size,
+                cursor.seen.clone(),
+                max_txns_per_account_per_block,
+            );
+            cursor.seen.extend(result.iter().cloned());
+            let exhausted = (result.len() as u64) < batch_size;
+            (result, exhausted)
+        } else {
+            self.walk_queue(batch_size, &mut cursor, max_txns_per_account_per_block)
+        };
+        let block = self.block_txns(result);
+        (block, if exhausted { None } else { Some(cursor) })
+    }
+
+    /// Converts the transaction pointers of a block to the transactions
+    fn block_txns(&mut self, result: Vec<TxnPointer>) -> Vec<SignedTransaction> {
         let result_size = result.len();
         // convert transaction pointers to real values
         let block: Vec<_> = result
             .into_iter()
             .filter_map(|(address, seq)| self.transactions.get(&address, seq))
             .collect();
-        debug!("mempool::get_block: seen_consensus={}, walked={}, seen_after={}, result_size={}, block_size={}",
-               seen_size, txn_walked, seen.len(), result_size, block.len());
+        debug!(
+            "mempool::get_block: result_size={}, block_size={}",
+            result_size,
+            block.len()
+        );
         for transaction in &block {
             self.log_latency(
                 transaction.sender(),
@@ -229,14 +816,526 @@ impl Mempool {
         block
     }
 
+    /// Returns the number of transactions `get_block` would include in a block of unlimited size
+    /// for the given `seen` set, without building the block
+    #[cfg(test)]
+    pub fn count_eligible(&self, seen: &HashSet<TxnPointer>) -> usize {
+        self.eligible_txns(u64::MAX, seen.clone(), self.max_txns_per_account_per_block)
+            .len()
+    }
+
+    /// Walks the priority queue and returns the transactions to include in the next block, in
+    /// order, see `get_block_with_max_txns_per_account`
+    fn eligible_txns(
+        &self,
+        batch_size: u64,
+        seen: HashSet<TxnPointer>,
+        max_txns_per_account_per_block: Option<usize>,
+    ) -> Vec<TxnPointer> {
+        if self.fair_queuing {
+            return self.fair_queued_txns(batch_size, seen, max_txns_per_account_per_block);
+        }
+        let mut cursor = self.block_cursor(seen);
+        self.walk_queue(batch_size, &mut cursor, max_txns_per_account_per_block)
+            .0
+    }
+
+    /// Starts the walk of the priority queue for a block
+    fn block_cursor(&self, seen: HashSet<TxnPointer>) -> BlockCursor {
+        // Transactions whose ancestor Consensus already has, but that weren't included in a
+        // block yet, are walked first (in priority order), so that the chains of the accounts in
+        // the in-flight blocks aren't left partially filled. They're walked again as part of the
+        // queue, but skipped as seen by then
+        let mut continuations: Vec<_> = seen
+            .iter()
+            .filter_map(|(address, seq)| {
+                seq.checked_add(1)
+                    .and_then(|next_seq| self.transactions.queue_key(address, next_seq))
+            })
+            .filter(|key| !seen.contains(&TxnPointer::from(*key)))
+            .cloned()
+            .collect();
+        // walked from the back
+        continuations.sort();
+        BlockCursor::new(seen, continuations)
+    }
+
+    /// Walks the priority queue from the cursor until `batch_size` transactions are included,
+    /// returns them and whether the queue was walked to the end
+    fn walk_queue(
+        &self,
+        batch_size: u64,
+        cursor: &mut BlockCursor,
+        max_txns_per_account_per_block: Option<usize>,
+    ) -> (Vec<TxnPointer>, bool) {
+        let mut result = vec![];
+        let seen_size = cursor.seen.len();
+        let mut txn_walked = 0usize;
+        let exhausted = self.fill_page(
+            batch_size,
+            cursor,
+            max_txns_per_account_per_block,
+            &mut result,
+            &mut txn_walked,
+        );
+        debug!(
+            "mempool::eligible_txns: seen_consensus={}, walked={}, seen_after={}, result_size={}",
+            seen_size,
+            txn_walked,
+            cursor.seen.len(),
+            result.len()
+        );
+        (result, exhausted)
+    }
+
+    #[allow(clippy::explicit_counter_loop)]
+    fn fill_page(
+        &self,
+        batch_size: u64,
+        cursor: &mut BlockCursor,
+        max_txns_per_account_per_block: Option<usize>,
+        result: &mut Vec<TxnPointer>,
+        txn_walked: &mut usize,
+    ) -> bool {
+        if !cursor.governance_added {
+            self.add_governance_txns(batch_size, &mut cursor.seen, result);
+            for (address, _) in result.iter() {
+                *cursor.account_txns.entry(*address).or_default() += 1;
+            }
+            cursor.governance_added = true;
+        }
+        // the transactions left over by the previous page come first
+        if (result.len() as u64) >= batch_size
+            || self.include_ready(batch_size, cursor, max_txns_per_account_per_block, result)
+        {
+            return false;
+        }
+        while let Some(txn) = cursor.continuations.pop() {
+            *txn_walked += 1;
+            if self.walk_txn(
+                &txn,
+                batch_size,
+                cursor,
+                max_txns_per_account_per_block,
+                result,
+            ) {
+                return false;
+            }
+        }
+        // with a nonzero aging coefficient the queue is re-ranked by the time the transactions
+        // have been waiting, so that low gas transactions aren't starved by a steady stream of
+        // higher gas ones
+        let queue: Box<dyn Iterator<Item = &OrderedQueueKey>> =
+            if self.ranking_aging_coefficient > 0.0 {
+                let queue = self
+                    .transactions
+                    .iter_queue_aged(self.clock.now(), self.ranking_aging_coefficient);
+                // the aged queue is ranked again for every page, the walk resumes after the
+                // transaction it stopped at
+                let start = cursor.last_walked.as_ref().map_or(0, |last| {
+                    queue
+                        .iter()
+                        .position(|key| *key == last)
+                        .map_or(queue.len(), |position| position + 1)
+                });
+                Box::new(queue.into_iter().skip(start))
+            } else {
+                match cursor.last_walked.as_ref() {
+                    Some(last) => Box::new(self.transactions.iter_queue_below(last)),
+                    None => Box::new(self.transactions.iter_queue()),
+                }
+            };
+        // iterate over the queue of transactions based on gas price
+        for txn in queue {
+            *txn_walked += 1;
+            if self.walk_txn(
+                txn,
+                batch_size,
+                cursor,
+                max_txns_per_account_per_block,
+                result,
+            ) {
+                cursor.last_walked = Some(txn.clone());
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Includes the transaction if it's next for its account and its dependency is satisfied,
+    /// otherwise sets it aside until it is. Returns true once the block is full
+    fn walk_txn(
+        &self,
+        txn: &OrderedQueueKey,
+        batch_size: u64,
+        cursor: &mut BlockCursor,
+        max_txns_per_account_per_block: Option<usize>,
+        result: &mut Vec<TxnPointer>,
+    ) -> bool {
+        if cursor.seen.contains(&TxnPointer::from(txn)) {
+            return false;
+        }
+        // the account's transactions following this one won't be included either, as their
+        // ancestor isn't
+        if Self::reached_limit(
+            &cursor.account_txns,
+            txn.address,
+            max_txns_per_account_per_block,
+        ) {
+            return false;
+        }
+        let seq = txn.sequence_number;
+        let depends_on = self.transactions.depends_on(&txn.address, seq);
+        let seen_previous = txn
+            .previous()
+            .map_or(false, |previous| cursor.seen.contains(&previous));
+        // include transaction if it's "next" for given account or
+        // we've already sent its ancestor to Consensus
+        if !(seen_previous || self.is_next_for_account(&txn.address, seq)) {
+            // Helps to mitigate scenarios where account submits several transactions with
+            // increasing gas price (e.g. user submits transactions with sequence number 1, 2
+            // and gas_price 1, 10 respectively)
+            // Later txn has higher gas price and will be observed first in priority index
+            // iterator, but can't be executed before first txn. Once observed, such txn is
+            // skipped (together with its dependency, if any) and rechecked once it's ancestor
+            // becomes available
+            cursor.skipped.insert(TxnPointer::from(txn), depends_on);
+            return false;
+        }
+        if let Some(dependency) = depends_on {
+            // Transactions whose dependency on a transaction of another account isn't satisfied
+            // yet are included as soon as the dependency is. Transactions with cyclic
+            // dependencies are never included, but they don't prevent the rest of the queue from
+            // being walked
+            if !self.dependency_ready(dependency, &cursor.seen) {
+                cursor
+                    .waiting
+                    .entry(dependency)
+                    .or_default()
+                    .push(TxnPointer::from(txn));
+                return false;
+            }
+        }
+        cursor.ready.push_back(TxnPointer::from(txn));
+        self.include_ready(batch_size, cursor, max_txns_per_account_per_block, result)
+    }
+
+    /// Includes the transactions ready to be included, together with the skipped and waiting
+    /// transactions they unblock. Returns true once the block is full, the remaining ready
+    /// transactions are left for the next page
+    fn include_ready(
+        &self,
+        batch_size: u64,
+        cursor: &mut BlockCursor,
+        max_txns_per_account_per_block: Option<usize>,
+        result: &mut Vec<TxnPointer>,
+    ) -> bool {
+        while let Some(ptr) = cursor.ready.pop_front() {
+            if Self::reached_limit(&cursor.account_txns, ptr.0, max_txns_per_account_per_block) {
+                continue;
+            }
+            *cursor.account_txns.entry(ptr.0).or_default() += 1;
+            cursor.seen.insert(ptr);
+            trace_event!("mempool::get_block", {"txn", ptr.0, ptr.1});
+            result.push(ptr);
+
+            // check if we can now include the transaction that was skipped before
+            // for given account
+            if let Some((next_txn, depends_on)) = ptr
+                .1
+                .checked_add(1)
+                .and_then(|next_seq| cursor.skipped.remove_entry(&(ptr.0, next_seq)))
+            {
+                match depends_on {
+                    Some(dependency) if !self.dependency_ready(dependency, &cursor.seen) => {
+                        cursor.waiting.entry(dependency).or_default().push(next_txn);
+                    }
+                    _ => cursor.ready.push_back(next_txn),
+                }
+            }
+            // as well as the transactions that were waiting for this one
+            if let Some(dependents) = cursor.waiting.remove(&ptr) {
+                cursor.ready.extend(dependents);
+            }
+            if (result.len() as u64) >= batch_size {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn reached_limit(
+        account_txns: &HashMap<AccountAddress, usize>,
+        address: AccountAddress,
+        max_txns_per_account_per_block: Option<usize>,
+    ) -> bool {
+        max_txns_per_account_per_block.map_or(false, |max_txns| {
+            account_txns.get(&address).map_or(0, |count| *count) >= max_txns
+        })
+    }
+
+    /// Deficit round-robin over the senders with ready transactions: every round, the deficit of
+    /// a sender grows by its weight and as many of its transactions as the deficit allows are
+    /// included, in sequence number order. The senders are visited in the priority order of
+    /// their first ready transaction. Governance transactions are added first, as usual
+    fn fair_queued_txns(
+        &self,
+        batch_size: u64,
+        mut seen: HashSet<TxnPointer>,
+        max_txns_per_account_per_block: Option<usize>,
+    ) -> Vec<TxnPointer> {
+        let mut result = vec![];
+        self.add_governance_txns(batch_size, &mut seen, &mut result);
+        let mut account_txns: HashMap<AccountAddress, usize> = HashMap::new();
+        for (address, _) in &result {
+            *account_txns.entry(*address).or_default() += 1;
+        }
+        // the transactions every sender can contribute, in sequence number order
+        let mut senders = vec![];
+        let mut queues: HashMap<AccountAddress, VecDeque<u64>> = HashMap::new();
+        for txn in self.transactions.iter_queue() {
+            if !queues.contains_key(&txn.address) {
+                senders.push(txn.address);
+                queues.insert(txn.address, self.ready_chain(txn.address, &seen));
+            }
+        }
+        let mut deficits: HashMap<AccountAddress, u64> = HashMap::new();
+        while (result.len() as u64) < batch_size && !senders.is_empty() {
+            for address in &senders {
+                let queue = queues.get_mut(address).expect("queue of sender must exist");
+                let count = account_txns.entry(*address).or_default();
+                let deficit = deficits.entry(*address).or_default();
+                *deficit += self
+                    .account_weights
+                    .get(address)
+                    .map_or(1, |weight| *weight);
+                while *deficit > 0
+                    && (result.len() as u64) < batch_size
+                    && max_txns_per_account_per_block.map_or(true, |max_txns| *count < max_txns)
+                {
+                    match queue.pop_front() {
+                        Some(seq) => {
+                            trace_event!("mempool::get_block", {"txn", address, seq});
+                            result.push((*address, seq));
+                            *deficit -= 1;
+                            *count += 1;
+                        }
+                        None => break,
+                    }
+                }
+            }
+            senders.retain(|address| {
+                !queues[address].is_empty()
+                    && max_txns_per_account_per_block
+                        .map_or(true, |max_txns| account_txns[address] < max_txns)
+            });
+        }
+        debug!(
+            "mempool::fair_queued_txns: senders={}, result_size={}",
+            queues.len(),
+            result.len()
+        );
+        result
+    }
+
+    /// The consecutive transactions of the sender that can be included in the next block: from
+    /// its next transaction (or the one following those already seen) up to the first gap or
+    /// unsatisfied dependency
+    fn ready_chain(&self, address: AccountAddress, seen: &HashSet<TxnPointer>) -> VecDeque<u64> {
+        let mut chain = VecDeque::new();
+        let mut seq = match self.sequence_number_cache.get(&address) {
+            Some(seq) => *seq,
+            None => return chain,
+        };
+        while seen.contains(&(address, seq)) {
+            match seq.checked_add(1) {
+                Some(next_seq) => seq = next_seq,
+                None => return chain,
+            }
+        }
+        while self.transactions.queue_key(&address, seq).is_some() {
+            if self
+                .transactions
+                .depends_on(&address, seq)
+                .map_or(false, |dependency| !self.dependency_ready(dependency, seen))
+            {
+                break;
+            }
+            chain.push_back(seq);
+            match seq.checked_add(1) {
+                Some(next_seq) => seq = next_seq,
+                None => break,
+            }
+        }
+        chain
+    }
+
+    /// Fills the part of the block reserved for governance transactions
+    /// Governance transactions are already ranked first by the priority queue, but one that
+    /// depends on pending transactions of its account can't be included before them, and these
+    /// might be outranked by high gas transactions of other accounts. Here every governance
+    /// transaction is included together with its not yet seen ancestors, as long as the whole
+    /// chain fits into the reserved part of the block
+    fn add_governance_txns(
+        &self,
+        batch_size: u64,
+        seen: &mut HashSet<TxnPointer>,
+        result: &mut Vec<TxnPointer>,
+    ) {
+        // saturate before the cast, an out of range float to int cast is undefined behavior
+        let reserved = (batch_size as f64 * self.governance_reserved_fraction.max(0.0)).ceil();
+        let reserved = if reserved < batch_size as f64 {
+            reserved as u64
+        } else {
+            batch_size
+        };
+        for txn in self
+            .transactions
+            .iter_queue()
+            .take_while(|txn| txn.is_governance_txn)
+        {
+            if seen.contains(&TxnPointer::from(txn)) {
+                continue;
+            }
+            let account_sequence_number = self.sequence_number_cache.get(&txn.address);
+            // walk back the chain of pending transactions until the account's next transaction
+            // or a transaction following an already seen one
+            let mut chain = vec![];
+            let mut seq = txn.sequence_number;
+            let ready = loop {
+                chain.push((txn.address, seq));
+                if account_sequence_number == Some(&seq)
+                    || (seq > 0 && seen.contains(&(txn.address, seq - 1)))
+                {
+                    break true;
+                }
+                if seq == 0 || !self.transactions.contains(&txn.address, seq - 1) {
+                    break false;
+                }
+                seq -= 1;
+            };
+            let dependencies_ready = chain.iter().all(|(address, seq)| {
+                self.transactions
+                    .depends_on(address, *seq)
+                    .map_or(true, |dependency| self.dependency_ready(dependency, seen))
+            });
+            if ready && dependencies_ready && (result.len() + chain.len()) as u64 <= reserved {
+                for ptr in chain.into_iter().rev() {
+                    seen.insert(ptr);
+                    result.push(ptr);
+                }
+            }
+        }
+    }
+
+    /// A dependency is satisfied once it's been sent to Consensus or committed
+    fn dependency_ready(&self, dependency: TxnPointer, seen: &HashSet<TxnPointer>) -> bool {
+        let (address, sequence_number) = dependency;
+        seen.contains(&dependency)
+            || self
+                .sequence_number_cache
+                .get(&address)
+                .map_or(false, |account_sequence_number| {
+                    *account_sequence_number > sequence_number
+                })
+    }
+
     /// periodic core mempool garbage collection
     /// removes all expired transactions
-    /// clears expired entries in metrics cache and sequence number cache
+    /// clears expired entries in metrics cache, sequence number cache, committed cache and
+    /// known transaction hashes
     pub(crate) fn gc(&mut self) {
-        let now = SystemTime::now();
-        self.transactions.gc_by_system_ttl();
+        self.gc_expired_and_collect();
+    }
+
+    /// same as `gc`, but returns the expired transactions
+    /// so the caller can notify their submitters
+    pub(crate) fn gc_expired_and_collect(&mut self) -> Vec<SignedTransaction> {
+        let now = self.clock.now();
+        let expired = self.transactions.gc_by_system_ttl(now);
         self.metrics_cache.gc(now);
         self.sequence_number_cache.gc(now);
+        self.committed_cache.gc(now);
+        if let Some(known) = self.known_txn_hashes.as_mut() {
+            known.gc(now);
+        }
+        self.update_gas_floor();
+        expired
+    }
+
+    /// Recomputes the min gas price from the occupancy of Mempool: the base min gas price up to
+    /// the occupancy threshold, growing linearly to the max gas floor at full capacity
+    fn update_gas_floor(&mut self) {
+        if self.max_gas_floor == 0 {
+            return;
+        }
+        let occupancy = self.transactions.size() as f64 / self.capacity.max(1) as f64;
+        let threshold = self.gas_floor_occupancy_threshold.max(0.0).min(1.0);
+        let max_gas_floor = max(self.max_gas_floor, self.base_min_gas_price);
+        self.min_gas_price = if occupancy <= threshold {
+            self.base_min_gas_price
+        } else if threshold >= 1.0 {
+            max_gas_floor
+        } else {
+            let pressure = ((occupancy - threshold) / (1.0 - threshold)).min(1.0);
+            self.base_min_gas_price
+                + ((max_gas_floor - self.base_min_gas_price) as f64 * pressure).round() as u64
+        };
+        OP_COUNTERS.set("min_gas_price", self.min_gas_price as usize);
+    }
+
+    /// same as `gc`, but also compacts the timeline ids if they're mostly unused, see
+    /// `TransactionStore::compact_timeline`. All the timeline consumers must have read past
+    /// `low_water_mark`, and have to be translated with the returned remapping, if any.
+    /// A `low_water_mark` past the latest timeline id is rejected
+    pub fn gc_and_compact_timeline(
+        &mut self,
+        low_water_mark: u64,
+    ) -> Result<Option<TimelineRemapping>> {
+        let latest_timeline_id = self.transactions.latest_timeline_id();
+        ensure!(
+            low_water_mark <= latest_timeline_id,
+            "low-water mark {} is past the latest timeline id {}",
+            low_water_mark,
+            latest_timeline_id
+        );
+        self.gc();
+        let remapping = self.transactions.compact_timeline(low_water_mark);
+        if remapping.is_some() {
+            self.timeline_epoch += 1;
+        }
+        Ok(remapping)
+    }
+
+    /// Returns the number of timeline compactions so far, the timeline ids handed out before a
+    /// compaction don't identify the same transactions after it
+    pub(crate) fn timeline_epoch(&self) -> u64 {
+        self.timeline_epoch
+    }
+
+    /// Returns the timeline id of the most recently added transaction
+    pub(crate) fn latest_timeline_id(&self) -> u64 {
+        self.transactions.latest_timeline_id()
+    }
+
+    /// Runs every resident transaction through the validator and removes the ones that are no
+    /// longer valid, e.g. after a reconfiguration. The following transactions of their accounts
+    /// can't be included in a block until the gap is filled again
+    /// Returns the number of transactions that were removed
+    pub fn revalidate(&mut self, validator: &dyn TxnRevalidator) -> usize {
+        let removed = self
+            .transactions
+            .remove_if_not(|txn| validator.is_valid(txn));
+        for txn in &removed {
+            self.metrics_cache
+                .remove(&(txn.sender(), txn.sequence_number()));
+        }
+        OP_COUNTERS.inc_by("revalidate.removed", removed.len());
+        debug!(
+            "[Mempool] revalidation removed {} transactions",
+            removed.len()
+        );
+        removed.len()
     }
 
     /// Garbage collection based on client-specified expiration time
@@ -244,24 +1343,54 @@ impl Mempool {
         self.transactions.gc_by_expiration_time(block_time);
     }
 
-    /// Read `count` transactions from timeline since `timeline_id`
+    /// Returns the counts of the transactions currently in Mempool
+    pub fn stats(&self) -> MempoolStats {
+        let mut stats = self.transactions.stats();
+        stats.metrics_cache_size = self.metrics_cache.size();
+        stats
+    }
+
+    /// Read `count` transactions from timeline since `timeline_id`, `count` is clamped to
+    /// `max_timeline_read`
     /// Returns block of transactions and new last_timeline_id
     pub(crate) fn read_timeline(
         &mut self,
         timeline_id: u64,
         count: usize,
     ) -> (Vec<SignedTransaction>, u64) {
-        self.transactions.read_timeline(timeline_id, count)
+        let count = min(count, self.max_timeline_read);
+        let (batch, last_timeline_id) = self.transactions.read_timeline(timeline_id, count);
+        self.record_timeline_read(batch.len(), last_timeline_id);
+        (batch, last_timeline_id)
     }
 
     /// Read transactions from timeline whose timeline id is in range
     /// `start_timeline_id` (exclusive) to `end_timeline_id` (inclusive)
+    /// The range spans at most `max_timeline_read` timeline ids, the ones past it are left for
+    /// the next read, starting from the returned timeline id
     pub(crate) fn timeline_range(
         &mut self,
         start_timeline_id: u64,
         end_timeline_id: u64,
-    ) -> Vec<SignedTransaction> {
-        self.transactions
-            .timeline_range(start_timeline_id, end_timeline_id)
+    ) -> (Vec<SignedTransaction>, u64) {
+        let end_timeline_id = min(
+            end_timeline_id,
+            start_timeline_id.saturating_add(self.max_timeline_read as u64),
+        );
+        let batch = self
+            .transactions
+            .timeline_range(start_timeline_id, end_timeline_id);
+        self.record_timeline_read(batch.len(), end_timeline_id);
+        (batch, end_timeline_id)
+    }
+
+    fn record_timeline_read(&self, batch_size: usize, last_timeline_id: u64) {
+        counters::TIMELINE_READ_COUNT.inc();
+        counters::TIMELINE_READ_BATCH_SIZE.observe(batch_size as f64);
+        let lag = self
+            .transactions
+            .latest_timeline_id()
+            .saturating_sub(last_timeline_id);
+        counters::TIMELINE_READ_LAG.set(lag as i64);
     }
 }
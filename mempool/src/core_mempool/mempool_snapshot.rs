@@ -0,0 +1,15 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::core_mempool::transaction::TransactionView;
+use libra_types::account_address::AccountAddress;
+use std::collections::HashMap;
+
+/// MempoolSnapshot holds all transactions resident in Mempool at the moment it was taken,
+/// so that they can be carried over a restart
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MempoolSnapshot {
+    pub transactions: Vec<TransactionView>,
+    // current sequence numbers of the accounts of the transactions, as known by Mempool
+    pub sequence_numbers: HashMap<AccountAddress, u64>,
+}
@@ -0,0 +1,20 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+/// MempoolStats are the counts of the transactions in Mempool at the moment they were taken,
+/// e.g. for a debug endpoint
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MempoolStats {
+    // number of transactions in Mempool
+    pub total_txns: usize,
+    // number of transactions by timeline state
+    pub ready_txns: usize,
+    pub not_ready_txns: usize,
+    pub non_qualified_txns: usize,
+    // number of accounts with transactions in Mempool
+    pub accounts: usize,
+    // timeline id of the most recently added transaction
+    pub timeline_id: u64,
+    // number of entries in the metrics cache
+    pub metrics_cache_size: usize,
+}
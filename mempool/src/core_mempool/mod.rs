@@ -1,12 +1,30 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+mod admission_filter;
+mod block_cursor;
+mod clock;
 mod index;
 mod mempool;
+#[cfg(test)]
+mod mempool_snapshot;
+mod mempool_stats;
+mod timeline_remapping;
 mod transaction;
 mod transaction_store;
 mod ttl_cache;
+mod txn_revalidator;
 
 #[cfg(test)]
 pub use self::ttl_cache::TtlCache;
-pub use self::{index::TxnPointer, mempool::Mempool as CoreMempool, transaction::TimelineState};
+pub use self::{
+    admission_filter::AdmissionFilter,
+    block_cursor::BlockCursor,
+    clock::{MempoolClock, SystemClock},
+    index::TxnPointer,
+    mempool::Mempool as CoreMempool,
+    mempool_stats::MempoolStats,
+    timeline_remapping::TimelineRemapping,
+    transaction::{TimelineNotifications, TimelineState, TimelineStateCallback, TransactionView},
+    txn_revalidator::TxnRevalidator,
+};
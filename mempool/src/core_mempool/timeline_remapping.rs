@@ -0,0 +1,51 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeMap;
+
+/// TimelineRemapping translates the timeline ids issued before a timeline compaction to the
+/// renumbered ones. Only the timeline ids at or above the low-water mark the compaction was
+/// requested with can be translated, the transactions below it were read by all the consumers
+#[derive(Clone, Debug)]
+pub struct TimelineRemapping {
+    low_water_mark: u64,
+    // renumbered id of the low-water mark
+    new_low_water_mark: u64,
+    // renumbered ids of the transactions above the low-water mark
+    ids: BTreeMap<u64, u64>,
+}
+
+impl TimelineRemapping {
+    /// `renumbered` holds the (old, new) timeline ids of all the transactions in timeline order
+    pub(crate) fn new(low_water_mark: u64, renumbered: Vec<(u64, u64)>) -> Self {
+        let mut new_low_water_mark = 0;
+        let mut ids = BTreeMap::new();
+        for (old_id, new_id) in renumbered {
+            if old_id <= low_water_mark {
+                new_low_water_mark = new_id;
+            } else {
+                ids.insert(old_id, new_id);
+            }
+        }
+        Self {
+            low_water_mark,
+            new_low_water_mark,
+            ids,
+        }
+    }
+
+    /// Translates a timeline id (e.g. the last one read by a consumer) to the renumbered
+    /// timeline, reading from the returned id yields the same transactions as reading from the
+    /// original one before the compaction. None if the id is below the low-water mark
+    pub fn remap(&self, timeline_id: u64) -> Option<u64> {
+        if timeline_id < self.low_water_mark {
+            return None;
+        }
+        Some(
+            self.ids
+                .range(..=timeline_id)
+                .next_back()
+                .map_or(self.new_low_water_mark, |(_, new_id)| *new_id),
+        )
+    }
+}
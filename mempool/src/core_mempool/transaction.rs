@@ -2,7 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use libra_types::{account_address::AccountAddress, transaction::SignedTransaction};
-use std::time::Duration;
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
 
 #[derive(Clone)]
 pub struct MempoolTransaction {
@@ -13,6 +16,10 @@ pub struct MempoolTransaction {
     pub ranking_score: u64,
     pub timeline_state: TimelineState,
     pub is_governance_txn: bool,
+    // transaction of another account that has to be included in a block before this one
+    pub depends_on: Option<(AccountAddress, u64)>,
+    // time the transaction entered Mempool
+    pub insertion_time: SystemTime,
 }
 
 impl MempoolTransaction {
@@ -23,6 +30,8 @@ impl MempoolTransaction {
         ranking_score: u64,
         timeline_state: TimelineState,
         is_governance_txn: bool,
+        depends_on: Option<(AccountAddress, u64)>,
+        insertion_time: SystemTime,
     ) -> Self {
         Self {
             txn,
@@ -31,6 +40,8 @@ impl MempoolTransaction {
             expiration_time,
             timeline_state,
             is_governance_txn,
+            depends_on,
+            insertion_time,
         }
     }
     pub(crate) fn get_sequence_number(&self) -> u64 {
@@ -44,6 +55,72 @@ impl MempoolTransaction {
     }
 }
 
+/// Snapshot of a transaction resident in Mempool together with its metadata
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransactionView {
+    pub txn: SignedTransaction,
+    pub timeline_state: TimelineState,
+    pub ranking_score: u64,
+    // system expiration time of transaction
+    pub expiration_time: Duration,
+    pub gas_amount: u64,
+    pub is_governance_txn: bool,
+    // transaction of another account that has to be included in a block before this one
+    pub depends_on: Option<(AccountAddress, u64)>,
+}
+
+impl From<&MempoolTransaction> for TransactionView {
+    fn from(transaction: &MempoolTransaction) -> Self {
+        Self {
+            txn: transaction.txn.clone(),
+            timeline_state: transaction.timeline_state,
+            ranking_score: transaction.ranking_score,
+            expiration_time: transaction.expiration_time,
+            gas_amount: transaction.gas_amount,
+            is_governance_txn: transaction.is_governance_txn,
+            depends_on: transaction.depends_on,
+        }
+    }
+}
+
+/// Callback invoked with `(sender, sequence_number, old_state, new_state)` whenever a transaction
+/// resident in Mempool changes its timeline state, e.g. when a parked transaction becomes ready
+/// for broadcast or a ready one gets parked. The transitions are queued during a Mempool update
+/// and the callback is only invoked with them by `TimelineNotifications::notify`, after the
+/// caller has released the Mempool lock.
+pub type TimelineStateCallback =
+    Box<dyn Fn(AccountAddress, u64, TimelineState, TimelineState) + Send + Sync>;
+
+/// Timeline state transitions queued by Mempool updates, taken out of Mempool with
+/// `Mempool::take_timeline_notifications` while holding the lock and delivered with `notify`
+/// once it's released
+#[derive(Default)]
+pub struct TimelineNotifications {
+    callback: Option<Arc<TimelineStateCallback>>,
+    transitions: Vec<(AccountAddress, u64, TimelineState, TimelineState)>,
+}
+
+impl TimelineNotifications {
+    pub(crate) fn new(
+        callback: Option<Arc<TimelineStateCallback>>,
+        transitions: Vec<(AccountAddress, u64, TimelineState, TimelineState)>,
+    ) -> Self {
+        Self {
+            callback,
+            transitions,
+        }
+    }
+
+    /// Invokes the callback with the queued transitions, in the order they happened
+    pub fn notify(self) {
+        if let Some(callback) = self.callback {
+            for (sender, sequence_number, old_state, new_state) in self.transitions {
+                callback(sender, sequence_number, old_state, new_state);
+            }
+        }
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
 pub enum TimelineState {
     // transaction is ready for broadcast
@@ -4,10 +4,15 @@
 use crate::{
     core_mempool::{
         index::{
-            AccountTransactions, ParkingLotIndex, PriorityIndex, PriorityQueueIter, TTLIndex,
-            TimelineIndex,
+            AccountTransactions, OrderedQueueKey, ParkingLotIndex, PriorityIndex,
+            PriorityQueueIter, PriorityQueueRangeIter, TTLIndex, TimelineIndex, TxnPointer,
+        },
+        mempool_stats::MempoolStats,
+        timeline_remapping::TimelineRemapping,
+        transaction::{
+            MempoolTransaction, TimelineNotifications, TimelineState, TimelineStateCallback,
+            TransactionView,
         },
-        transaction::{MempoolTransaction, TimelineState},
     },
     OP_COUNTERS,
 };
@@ -20,8 +25,11 @@ use libra_types::{
     transaction::SignedTransaction,
 };
 use std::{
+    cmp::Ordering,
     collections::HashMap,
+    mem,
     ops::Bound,
+    sync::Arc,
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
@@ -44,7 +52,17 @@ pub struct TransactionStore {
 
     // configuration
     capacity: usize,
-    capacity_per_user: usize,
+    // max number of governance and regular transactions respectively
+    governance_capacity: usize,
+    regular_capacity: usize,
+    min_gas_price_bump_pct: u64,
+    // number of resident governance transactions, the rest are regular ones
+    governance_txns: usize,
+
+    // notified of the timeline state transitions of the transactions
+    timeline_state_callback: Option<Arc<TimelineStateCallback>>,
+    // transitions not taken out for notification yet, only queued if there's a callback
+    timeline_transitions: Vec<(AccountAddress, u64, TimelineState, TimelineState)>,
 }
 
 impl TransactionStore {
@@ -64,10 +82,29 @@ impl TransactionStore {
 
             // configuration
             capacity: config.capacity,
-            capacity_per_user: config.capacity_per_user,
+            governance_capacity: (config.capacity as f64 * config.governance_capacity_fraction)
+                as usize,
+            regular_capacity: (config.capacity as f64 * config.regular_capacity_fraction) as usize,
+            min_gas_price_bump_pct: config.min_gas_price_bump_pct,
+            governance_txns: 0,
+
+            timeline_state_callback: None,
+            timeline_transitions: vec![],
         }
     }
 
+    pub(crate) fn set_timeline_state_callback(&mut self, callback: TimelineStateCallback) {
+        self.timeline_state_callback = Some(Arc::new(callback));
+    }
+
+    /// takes out the timeline state transitions queued since the last call
+    pub(crate) fn take_timeline_notifications(&mut self) -> TimelineNotifications {
+        TimelineNotifications::new(
+            self.timeline_state_callback.clone(),
+            mem::take(&mut self.timeline_transitions),
+        )
+    }
+
     /// fetch transaction by account address + sequence_number
     pub(crate) fn get(
         &self,
@@ -84,6 +121,99 @@ impl TransactionStore {
         None
     }
 
+    /// fetch transaction together with its metadata by account address + sequence_number
+    pub(crate) fn get_with_metadata(
+        &self,
+        address: &AccountAddress,
+        sequence_number: u64,
+    ) -> Option<TransactionView> {
+        self.transactions
+            .get(&address)
+            .and_then(|txns| txns.get(&sequence_number))
+            .map(TransactionView::from)
+    }
+
+    /// updates the ranking score and gas amount of a resident transaction, its position in the
+    /// priority queue follows the new score while the timeline entry is left untouched
+    /// returns false if the transaction is not in the store
+    #[cfg(test)]
+    pub(crate) fn update_rank(
+        &mut self,
+        address: &AccountAddress,
+        sequence_number: u64,
+        ranking_score: u64,
+        gas_amount: u64,
+    ) -> bool {
+        let txn = match self
+            .transactions
+            .get_mut(address)
+            .and_then(|txns| txns.get_mut(&sequence_number))
+        {
+            Some(txn) => txn,
+            None => return false,
+        };
+        let in_queue = self.priority_index.contains(txn);
+        if in_queue {
+            self.priority_index.remove(txn);
+        }
+        txn.ranking_score = ranking_score;
+        txn.gas_amount = gas_amount;
+        if in_queue {
+            self.priority_index.insert(txn);
+        }
+        true
+    }
+
+    /// returns all transactions together with their metadata
+    #[cfg(test)]
+    pub(crate) fn views(&self) -> Vec<TransactionView> {
+        self.transactions
+            .values()
+            .flat_map(|txns| txns.values())
+            .map(TransactionView::from)
+            .collect()
+    }
+
+    /// fetch the dependency of the transaction with given account address + sequence_number
+    pub(crate) fn depends_on(
+        &self,
+        address: &AccountAddress,
+        sequence_number: u64,
+    ) -> Option<TxnPointer> {
+        self.transactions
+            .get(address)
+            .and_then(|txns| txns.get(&sequence_number))
+            .and_then(|txn| txn.depends_on)
+    }
+
+    /// check if transaction with given account address + sequence_number is present
+    pub(crate) fn contains(&self, address: &AccountAddress, sequence_number: u64) -> bool {
+        self.transactions
+            .get(address)
+            .map_or(false, |txns| txns.contains_key(&sequence_number))
+    }
+
+    /// lowest sequence number of the resident transactions of every account
+    #[cfg(test)]
+    pub(crate) fn lowest_sequence_numbers(&self) -> Vec<(AccountAddress, u64)> {
+        self.transactions
+            .iter()
+            .filter_map(|(address, txns)| txns.keys().next().map(|seq| (*address, *seq)))
+            .collect()
+    }
+
+    /// number of transactions of given account that are not committed yet, i.e. the ones with
+    /// sequence number >= `current_sequence_number`
+    pub(crate) fn outstanding_txns_count(
+        &self,
+        address: &AccountAddress,
+        current_sequence_number: u64,
+    ) -> usize {
+        self.transactions
+            .get(address)
+            .map_or(0, |txns| txns.range(current_sequence_number..).count())
+    }
+
     /// insert transaction into TransactionStore
     /// performs validation checks and updates indexes
     pub(crate) fn insert(
@@ -100,12 +230,24 @@ impl TransactionStore {
 
         if self.check_if_full(&txn, current_sequence_number) {
             return MempoolStatus::new(MempoolStatusCode::MempoolIsFull).with_message(format!(
-                "mempool size: {}, capacity: {}",
+                "mempool is full, mempool size: {}, capacity: {}",
                 self.system_ttl_index.size(),
                 self.capacity,
             ));
         }
 
+        if self.check_if_class_full(&txn, current_sequence_number) {
+            let (class, capacity) = if txn.is_governance_txn {
+                ("governance", self.governance_capacity)
+            } else {
+                ("regular", self.regular_capacity)
+            };
+            return MempoolStatus::new(MempoolStatusCode::ClassQuotaFull).with_message(format!(
+                "mempool quota of {} transactions is full, capacity: {}",
+                class, capacity,
+            ));
+        }
+
         let address = txn.get_sender();
         let sequence_number = txn.get_sequence_number();
 
@@ -116,20 +258,12 @@ impl TransactionStore {
         self.clean_committed_transactions(&address, current_sequence_number);
 
         if let Some(txns) = self.transactions.get_mut(&address) {
-            // capacity check
-            if txns.len() >= self.capacity_per_user {
-                return MempoolStatus::new(MempoolStatusCode::TooManyTransactions).with_message(
-                    format!(
-                        "txns length: {} capacity per user: {}",
-                        txns.len(),
-                        self.capacity_per_user,
-                    ),
-                );
-            }
-
             // insert into storage and other indexes
             self.system_ttl_index.insert(&txn);
             self.expiration_time_index.insert(&txn);
+            if txn.is_governance_txn {
+                self.governance_txns += 1;
+            }
             txns.insert(sequence_number, txn);
             self.track_indices();
         }
@@ -144,7 +278,8 @@ impl TransactionStore {
     }
 
     /// checks if Mempool is full
-    /// If it's full, tries to free some space by evicting transactions from ParkingLot
+    /// If it's full, tries to free some space by evicting transactions from ParkingLot and, if
+    /// that's not enough, by evicting the lowest ranked transaction that is outranked by `txn`
     /// We only evict on attempt to insert a transaction that would be ready for broadcast upon insertion
     fn check_if_full(&mut self, txn: &MempoolTransaction, curr_sequence_number: u64) -> bool {
         if self.system_ttl_index.size() >= self.capacity
@@ -160,10 +295,104 @@ impl TransactionStore {
                     self.index_remove(&txn);
                 }
             }
+            if self.system_ttl_index.size() >= self.capacity {
+                self.evict_lowest_ranked(txn, false);
+            }
         }
         self.system_ttl_index.size() >= self.capacity
     }
 
+    /// checks if the quota of the class of `txn` (governance or regular transactions) is full
+    /// If it's full, tries to free some space by evicting a transaction of the same class from
+    /// ParkingLot and, if that's not enough, the lowest ranked transaction of the same class that
+    /// is outranked by `txn`
+    fn check_if_class_full(&mut self, txn: &MempoolTransaction, curr_sequence_number: u64) -> bool {
+        let is_governance_txn = txn.is_governance_txn;
+        if self.class_size(is_governance_txn) >= self.class_capacity(is_governance_txn)
+            && self.check_txn_ready(txn, curr_sequence_number)
+        {
+            // try to free some space in the quota from ParkingLot
+            if let Some((address, sequence_number)) = self.parked_txn_of_class(is_governance_txn) {
+                if let Some(txn) = self
+                    .transactions
+                    .get_mut(&address)
+                    .and_then(|txns| txns.remove(&sequence_number))
+                {
+                    self.index_remove(&txn);
+                }
+            }
+            if self.class_size(is_governance_txn) >= self.class_capacity(is_governance_txn) {
+                self.evict_lowest_ranked(txn, true);
+            }
+        }
+        self.class_size(is_governance_txn) >= self.class_capacity(is_governance_txn)
+    }
+
+    /// returns the first transaction of ParkingLot of the given class
+    fn parked_txn_of_class(&self, is_governance_txn: bool) -> Option<TxnPointer> {
+        self.parking_lot_index
+            .iter()
+            .find(|(address, sequence_number)| {
+                self.transactions
+                    .get(address)
+                    .and_then(|txns| txns.get(sequence_number))
+                    .map_or(false, |txn| txn.is_governance_txn == is_governance_txn)
+            })
+            .cloned()
+    }
+
+    fn class_size(&self, is_governance_txn: bool) -> usize {
+        if is_governance_txn {
+            self.governance_txns
+        } else {
+            self.system_ttl_index.size() - self.governance_txns
+        }
+    }
+
+    fn class_capacity(&self, is_governance_txn: bool) -> usize {
+        if is_governance_txn {
+            self.governance_capacity
+        } else {
+            self.regular_capacity
+        }
+    }
+
+    /// evicts the lowest ranked ready transaction if it's outranked by `txn`, only among the
+    /// transactions of the same class if `same_class_only` is set
+    /// A transaction is never evicted if another transaction of the same account depends on it
+    /// (i.e. only the last transaction of an account can be evicted), nor if it's sent by the
+    /// sender of `txn`
+    fn evict_lowest_ranked(&mut self, txn: &MempoolTransaction, same_class_only: bool) {
+        let victim = self
+            .priority_index
+            .iter_lowest_first()
+            .take_while(|key| {
+                (key.is_governance_txn, key.gas_ranking_score)
+                    < (txn.is_governance_txn, txn.ranking_score)
+            })
+            .filter(|key| !same_class_only || key.is_governance_txn == txn.is_governance_txn)
+            .find(|key| {
+                key.address != txn.get_sender()
+                    && !self.transactions.get(&key.address).map_or(false, |txns| {
+                        key.sequence_number
+                            .checked_add(1)
+                            .map_or(false, |next| txns.contains_key(&next))
+                    })
+            })
+            .map(|key| (key.address, key.sequence_number));
+
+        if let Some((address, sequence_number)) = victim {
+            if let Some(victim_txn) = self
+                .transactions
+                .get_mut(&address)
+                .and_then(|txns| txns.remove(&sequence_number))
+            {
+                OP_COUNTERS.inc("evict.lowest_ranked");
+                self.index_remove(&victim_txn);
+            }
+        }
+    }
+
     /// check if a transaction would be ready for broadcast in mempool upon insertion (without inserting it)
     /// Two ways this can happen:
     /// 1. txn sequence number == curr_sequence_number
@@ -192,14 +421,19 @@ impl TransactionStore {
 
     /// check if transaction is already present in Mempool
     /// e.g. given request is update
-    /// we allow increase in gas price to speed up process
+    /// we allow increase in gas price to speed up process, as long as the gas price is bumped by
+    /// at least `min_gas_price_bump_pct` percent
     fn handle_gas_price_update(&mut self, txn: &MempoolTransaction) -> Result<()> {
+        let min_gas_price_bump_pct = self.min_gas_price_bump_pct;
         if let Some(txns) = self.transactions.get_mut(&txn.get_sender()) {
             if let Some(current_version) = txns.get_mut(&txn.get_sequence_number()) {
                 if current_version.txn.max_gas_amount() == txn.txn.max_gas_amount()
                     && current_version.txn.payload() == txn.txn.payload()
                     && current_version.txn.expiration_time() == txn.txn.expiration_time()
                     && current_version.get_gas_price() < txn.get_gas_price()
+                    && u128::from(txn.get_gas_price()) * 100
+                        >= u128::from(current_version.get_gas_price())
+                            * (100 + u128::from(min_gas_price_bump_pct))
                 {
                     if let Some(txn) = txns.remove(&txn.get_sequence_number()) {
                         self.index_remove(&txn);
@@ -231,8 +465,19 @@ impl TransactionStore {
 
                 if txn.timeline_state == TimelineState::NotReady {
                     self.timeline_index.insert(txn);
+                    if self.timeline_state_callback.is_some() {
+                        self.timeline_transitions.push((
+                            txn.get_sender(),
+                            txn.get_sequence_number(),
+                            TimelineState::NotReady,
+                            txn.timeline_state,
+                        ));
+                    }
+                }
+                match sequence_number.checked_add(1) {
+                    Some(next_sequence_number) => sequence_number = next_sequence_number,
+                    None => break,
                 }
-                sequence_number += 1;
             }
 
             let mut parking_lot_txns = 0;
@@ -251,45 +496,84 @@ impl TransactionStore {
         }
     }
 
-    fn clean_committed_transactions(&mut self, address: &AccountAddress, sequence_number: u64) {
+    fn clean_committed_transactions(
+        &mut self,
+        address: &AccountAddress,
+        sequence_number: u64,
+    ) -> Vec<u64> {
         // remove all previous seq number transactions for this account
         // This can happen if transactions are sent to multiple nodes and one of
         // nodes has sent the transaction to consensus but this node still has the
         // transaction sitting in mempool
+        let mut removed = vec![];
         if let Some(txns) = self.transactions.get_mut(&address) {
             let mut active = txns.split_off(&sequence_number);
             let txns_for_removal = txns.clone();
             txns.clear();
             txns.append(&mut active);
 
-            for transaction in txns_for_removal.values() {
+            for (&sequence_number, transaction) in txns_for_removal.iter() {
                 self.index_remove(transaction);
+                removed.push(sequence_number);
             }
         }
+        removed
     }
 
     /// handles transaction commit
     /// it includes deletion of all transactions with sequence number <= `account_sequence_number`
     /// and potential promotion of sequential txns to PriorityIndex/TimelineIndex
+    /// Returns the sequence numbers of the removed transactions
     pub(crate) fn commit_transaction(
         &mut self,
         account: &AccountAddress,
         account_sequence_number: u64,
-    ) {
-        self.clean_committed_transactions(account, account_sequence_number);
+    ) -> Vec<u64> {
+        let removed = self.clean_committed_transactions(account, account_sequence_number);
         self.process_ready_transactions(account, account_sequence_number);
+        removed
     }
 
     pub(crate) fn reject_transaction(&mut self, account: &AccountAddress, _sequence_number: u64) {
+        self.remove_account(account);
+    }
+
+    /// removes all transactions of the given account
+    /// Returns the sequence numbers of the removed transactions
+    pub(crate) fn remove_account(&mut self, account: &AccountAddress) -> Vec<u64> {
+        let mut removed = vec![];
         if let Some(txns) = self.transactions.remove(&account) {
-            for transaction in txns.values() {
+            for (&sequence_number, transaction) in txns.iter() {
                 self.index_remove(&transaction);
+                removed.push(sequence_number);
             }
         }
+        removed
+    }
+
+    /// removes the transactions the predicate doesn't hold for, the following transactions of
+    /// their accounts are parked. Returns the transactions that were removed
+    pub(crate) fn remove_if_not<F>(&mut self, predicate: F) -> Vec<SignedTransaction>
+    where
+        F: Fn(&SignedTransaction) -> bool,
+    {
+        let failed = self
+            .transactions
+            .iter()
+            .flat_map(|(address, txns)| {
+                txns.iter()
+                    .filter(|(_, txn)| !predicate(&txn.txn))
+                    .map(move |(sequence_number, _)| (*address, *sequence_number))
+            })
+            .collect();
+        self.gc_txns("revalidate", failed)
     }
 
     /// removes transaction from all indexes
     fn index_remove(&mut self, txn: &MempoolTransaction) {
+        if txn.is_governance_txn {
+            self.governance_txns -= 1;
+        }
         self.system_ttl_index.remove(&txn);
         self.expiration_time_index.remove(&txn);
         self.priority_index.remove(&txn);
@@ -322,6 +606,47 @@ impl TransactionStore {
         (batch, last_timeline_id)
     }
 
+    /// Renumbers the timeline to consecutive timeline ids, if less than half of the issued ones
+    /// are still in use. The relative order of the transactions is preserved. All the timeline
+    /// consumers must be past `low_water_mark`, they're translated with the returned remapping.
+    /// The renumbered transactions are reported as `Ready(old_id)` -> `Ready(new_id)` transitions
+    pub(crate) fn compact_timeline(&mut self, low_water_mark: u64) -> Option<TimelineRemapping> {
+        let size = self.timeline_index.size() as u64;
+        if self.timeline_index.latest_timeline_id() - size <= size {
+            return None;
+        }
+        let renumbered = self.timeline_index.compact();
+        for (&timeline_id, (address, sequence_number)) in self.timeline_index.iter() {
+            if let Some(txn) = self
+                .transactions
+                .get_mut(address)
+                .and_then(|txns| txns.get_mut(sequence_number))
+            {
+                let old_state = txn.timeline_state;
+                txn.timeline_state = TimelineState::Ready(timeline_id);
+                if old_state != txn.timeline_state && self.timeline_state_callback.is_some() {
+                    self.timeline_transitions.push((
+                        *address,
+                        *sequence_number,
+                        old_state,
+                        txn.timeline_state,
+                    ));
+                }
+            }
+        }
+        Some(TimelineRemapping::new(low_water_mark, renumbered))
+    }
+
+    /// Returns the number of transactions in Mempool
+    pub(crate) fn size(&self) -> usize {
+        self.system_ttl_index.size()
+    }
+
+    /// Returns timeline id of the most recently added transaction
+    pub(crate) fn latest_timeline_id(&self) -> u64 {
+        self.timeline_index.latest_timeline_id()
+    }
+
     /// Returns block of transactions with timeline id in the range `start_timeline_id` exclusive to `end_timeline_id` inclusive
     pub(crate) fn timeline_range(
         &mut self,
@@ -344,13 +669,32 @@ impl TransactionStore {
         batch
     }
 
+    /// Returns the counts of the transactions in the store
+    pub(crate) fn stats(&self) -> MempoolStats {
+        let mut stats = MempoolStats {
+            total_txns: self.system_ttl_index.size(),
+            accounts: self.transactions.len(),
+            timeline_id: self.timeline_index.latest_timeline_id(),
+            ..MempoolStats::default()
+        };
+        for txn in self.transactions.values().flat_map(|txns| txns.values()) {
+            match txn.timeline_state {
+                TimelineState::Ready(_) => stats.ready_txns += 1,
+                TimelineState::NotReady => stats.not_ready_txns += 1,
+                TimelineState::NonQualified => stats.non_qualified_txns += 1,
+            }
+        }
+        stats
+    }
+
     /// GC old transactions
-    pub(crate) fn gc_by_system_ttl(&mut self) {
-        let now = SystemTime::now()
+    /// Returns the transactions that were removed
+    pub(crate) fn gc_by_system_ttl(&mut self, now: SystemTime) -> Vec<SignedTransaction> {
+        let now = now
             .duration_since(UNIX_EPOCH)
             .expect("init timestamp failure");
 
-        self.gc(now, true);
+        self.gc(now, true)
     }
 
     /// GC old transactions based on client-specified expiration time
@@ -358,7 +702,7 @@ impl TransactionStore {
         self.gc(block_time, false);
     }
 
-    fn gc(&mut self, now: Duration, by_system_ttl: bool) {
+    fn gc(&mut self, now: Duration, by_system_ttl: bool) -> Vec<SignedTransaction> {
         let (index_name, index) = if by_system_ttl {
             ("gc.system_ttl_index", &mut self.system_ttl_index)
         } else {
@@ -366,26 +710,116 @@ impl TransactionStore {
         };
         OP_COUNTERS.inc(index_name);
 
-        for key in index.gc(now) {
-            if let Some(txns) = self.transactions.get_mut(&key.address) {
+        let expired = index.gc(now).iter().map(TxnPointer::from).collect();
+        self.gc_txns(index_name, expired)
+    }
+
+    /// removes expired transactions from storage, returns the transactions that were removed
+    fn gc_txns(&mut self, index_name: &str, expired: Vec<TxnPointer>) -> Vec<SignedTransaction> {
+        let mut removed = vec![];
+        for (address, sequence_number) in expired {
+            if let Some(txns) = self.transactions.get_mut(&address) {
                 // mark all following transactions as non-ready
-                for (_, t) in txns.range((Bound::Excluded(key.sequence_number), Bound::Unbounded)) {
+                for (_, t) in txns.range_mut((Bound::Excluded(sequence_number), Bound::Unbounded)) {
                     self.parking_lot_index.insert(&t);
                     self.priority_index.remove(&t);
                     self.timeline_index.remove(&t);
+                    if let TimelineState::Ready(_) = t.timeline_state {
+                        if self.timeline_state_callback.is_some() {
+                            self.timeline_transitions.push((
+                                t.get_sender(),
+                                t.get_sequence_number(),
+                                t.timeline_state,
+                                TimelineState::NotReady,
+                            ));
+                        }
+                        t.timeline_state = TimelineState::NotReady;
+                    }
                 }
-                if let Some(txn) = txns.remove(&key.sequence_number) {
+                if let Some(txn) = txns.remove(&sequence_number) {
                     let is_active = self.priority_index.contains(&txn);
                     let status = if is_active { "active" } else { "parked" };
                     OP_COUNTERS.inc(&format!("{}.{}", index_name, status));
                     self.index_remove(&txn);
+                    removed.push(txn.txn);
                 }
             }
         }
         self.track_indices();
+        removed
     }
 
     pub(crate) fn iter_queue(&self) -> PriorityQueueIter {
         self.priority_index.iter()
     }
+
+    /// returns the rest of the priority queue after `key`, for a walk resumed where it stopped
+    pub(crate) fn iter_queue_below(&self, key: &OrderedQueueKey) -> PriorityQueueRangeIter {
+        self.priority_index.iter_below(key)
+    }
+
+    /// returns the priority queue ordered by the ranking score increased by `aging_coefficient`
+    /// for every second a transaction has been resident in Mempool
+    /// Governance transactions are still ranked first, the ties are broken by the queue order
+    pub(crate) fn iter_queue_aged(
+        &self,
+        now: SystemTime,
+        aging_coefficient: f64,
+    ) -> Vec<&OrderedQueueKey> {
+        let mut queue: Vec<_> = self
+            .priority_index
+            .iter()
+            .map(|key| {
+                let seconds_resident = self
+                    .transactions
+                    .get(&key.address)
+                    .and_then(|txns| txns.get(&key.sequence_number))
+                    .and_then(|txn| now.duration_since(txn.insertion_time).ok())
+                    .map_or(0.0, |resident| resident.as_secs_f64());
+                let priority = key.gas_ranking_score as f64 + aging_coefficient * seconds_resident;
+                (key, priority)
+            })
+            .collect();
+        // the sort is stable, so the keys with equal priority keep the queue order
+        queue.sort_by(|(a, a_priority), (b, b_priority)| {
+            b.is_governance_txn.cmp(&a.is_governance_txn).then(
+                b_priority
+                    .partial_cmp(a_priority)
+                    .unwrap_or(Ordering::Equal),
+            )
+        });
+        queue.into_iter().map(|(key, _)| key).collect()
+    }
+
+    /// returns the gas prices of the transactions in the priority queue that are ready to be
+    /// broadcast, in ascending order
+    #[cfg(test)]
+    pub(crate) fn ready_gas_prices(&self) -> Vec<u64> {
+        let mut gas_prices: Vec<_> = self
+            .priority_index
+            .iter_lowest_first()
+            .filter_map(|key| {
+                self.transactions
+                    .get(&key.address)
+                    .and_then(|txns| txns.get(&key.sequence_number))
+            })
+            .filter(|txn| matches!(txn.timeline_state, TimelineState::Ready(_)))
+            .map(|txn| txn.get_gas_price())
+            .collect();
+        gas_prices.sort_unstable();
+        gas_prices
+    }
+
+    /// fetch priority queue key of transaction by account address + sequence_number
+    /// if transaction is ready to be included in a block
+    pub(crate) fn queue_key(
+        &self,
+        address: &AccountAddress,
+        sequence_number: u64,
+    ) -> Option<&OrderedQueueKey> {
+        self.transactions
+            .get(address)
+            .and_then(|txns| txns.get(&sequence_number))
+            .and_then(|txn| self.priority_index.get(txn))
+    }
 }
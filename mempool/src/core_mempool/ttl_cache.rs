@@ -36,6 +36,11 @@ where
     }
 
     pub fn insert(&mut self, key: K, value: V) {
+        self.insert_at(key, value, SystemTime::now())
+    }
+
+    /// Inserts the entry as of the given time, it expires `default_timeout` later
+    pub fn insert_at(&mut self, key: K, value: V, now: SystemTime) {
         // remove old entry if it exists
         match self.data.get(&key) {
             Some(info) => {
@@ -55,7 +60,7 @@ where
         }
 
         // insert new one
-        if let Some(expiration_time) = SystemTime::now().checked_add(self.default_timeout) {
+        if let Some(expiration_time) = now.checked_add(self.default_timeout) {
             self.ttl_index.insert(expiration_time, key.clone());
             let value_info = ValueInfo {
                 value,
@@ -0,0 +1,11 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use libra_types::transaction::SignedTransaction;
+
+/// Validation of the resident transactions against the current on-chain configuration, e.g. after
+/// a reconfiguration rotated the allowed transaction formats or the gas schedule
+pub trait TxnRevalidator {
+    /// Returns false if the transaction can no longer be executed and has to be dropped
+    fn is_valid(&self, txn: &SignedTransaction) -> bool;
+}
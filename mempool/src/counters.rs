@@ -1,7 +1,10 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use libra_metrics::{register_int_counter, register_int_counter_vec, IntCounter, IntCounterVec};
+use libra_metrics::{
+    register_histogram, register_int_counter, register_int_counter_vec, register_int_gauge,
+    Histogram, IntCounter, IntCounterVec, IntGauge,
+};
 use once_cell::sync::Lazy;
 
 /// Counter of pending network events to Mempool
@@ -58,3 +61,40 @@ pub static SHARED_MEMPOOL_TRANSACTION_BROADCAST: Lazy<IntCounter> = Lazy::new(||
     )
     .unwrap()
 });
+
+/// Number of commit or reject notifications for transactions the account had already moved past
+pub static DUPLICATE_REMOVE_TRANSACTION_COUNT: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "libra_mempool_duplicate_remove_transaction_count",
+        "Number of notifications for transactions already committed, ignored by mempool"
+    )
+    .unwrap()
+});
+
+/// Number of reads of the timeline (`read_timeline` and `timeline_range`)
+pub static TIMELINE_READ_COUNT: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "libra_mempool_timeline_read_count",
+        "Number of reads of the mempool timeline"
+    )
+    .unwrap()
+});
+
+/// Histogram of the number of transactions returned by a single timeline read
+pub static TIMELINE_READ_BATCH_SIZE: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        "libra_mempool_timeline_read_batch_size",
+        "Histogram of the number of transactions returned by a timeline read",
+        vec![1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0, 200.0, 500.0]
+    )
+    .unwrap()
+});
+
+/// Gap between the latest timeline id and the last timeline id served by a timeline read
+pub static TIMELINE_READ_LAG: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "libra_mempool_timeline_read_lag",
+        "Gap between the latest timeline id and the last timeline id served by a timeline read"
+    )
+    .unwrap()
+});
@@ -59,6 +59,7 @@
 /// This module provides mocks of shared mempool for tests.
 #[cfg(any(test, feature = "fuzzing"))]
 mod tests;
+pub use core_mempool::{AdmissionFilter, TimelineState, TimelineStateCallback};
 pub use shared_mempool::{
     bootstrap, network,
     types::{
@@ -8,6 +8,7 @@ use crate::{
     counters,
     network::{MempoolNetworkEvents, MempoolSyncMsg},
     shared_mempool::{
+        peer_manager::PeerManager,
         tasks,
         types::{notify_subscribers, SharedMempool, SharedMempoolNotification},
     },
@@ -87,7 +88,7 @@ pub(crate) async fn coordinator<V>(
             }
             config_update = mempool_reconfig_events.select_next_some() => {
                 bounded_executor
-                .spawn(tasks::process_config_update(config_update, smp.validator.clone()))
+                .spawn(tasks::process_config_update(config_update, smp.clone()))
                 .await;
             },
             peer = scheduled_broadcasts.select_next_some() => {
@@ -170,14 +171,32 @@ pub(crate) async fn coordinator<V>(
     crit!("[shared mempool] inbound_network_task terminated");
 }
 
-/// GC all expired transactions by SystemTTL
-pub(crate) async fn gc_coordinator(mempool: Arc<Mutex<CoreMempool>>, gc_interval_ms: u64) {
+/// GC all expired transactions by SystemTTL and compact the timeline ids once all the peers have
+/// been sent the transactions they refer to
+pub(crate) async fn gc_coordinator(
+    mempool: Arc<Mutex<CoreMempool>>,
+    peer_manager: Arc<PeerManager>,
+    gc_interval_ms: u64,
+) {
     let mut interval = interval(Duration::from_millis(gc_interval_ms));
     while let Some(_interval) = interval.next().await {
-        mempool
-            .lock()
-            .expect("[shared mempool] failed to acquire mempool lock")
-            .gc();
+        let timeline_notifications = {
+            let mut mempool = mempool
+                .lock()
+                .expect("[shared mempool] failed to acquire mempool lock");
+            // the peers' timeline ids are only advanced under the mempool lock, see
+            // `tasks::execute_broadcast`
+            let low_water_mark = peer_manager
+                .timeline_low_water_mark()
+                .unwrap_or_else(|| mempool.latest_timeline_id());
+            match mempool.gc_and_compact_timeline(low_water_mark) {
+                Ok(Some(remapping)) => peer_manager.remap_timeline(&remapping),
+                Ok(None) => {}
+                Err(e) => error!("[shared mempool] failed to compact the timeline: {}", e),
+            }
+            mempool.take_timeline_notifications()
+        };
+        timeline_notifications.notify();
     }
 
     crit!("SharedMempool gc_task terminated");
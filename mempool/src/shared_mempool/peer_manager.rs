@@ -1,6 +1,7 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::core_mempool::TimelineRemapping;
 use libra_config::config::{PeerNetworkId, UpstreamConfig};
 use std::{collections::HashMap, sync::Mutex};
 
@@ -71,6 +72,30 @@ impl PeerManager {
             });
     }
 
+    /// returns the lowest timeline id the peers have been sent transactions up to, None if
+    /// there are no peers
+    pub fn timeline_low_water_mark(&self) -> Option<u64> {
+        self.peer_info
+            .lock()
+            .expect("failed to acquire peer_info lock")
+            .values()
+            .map(|state| state.timeline_id)
+            .min()
+    }
+
+    /// translates the timeline ids of the peers after a timeline compaction, the peers that
+    /// joined since the low-water mark was taken start over
+    pub fn remap_timeline(&self, remapping: &TimelineRemapping) {
+        for state in self
+            .peer_info
+            .lock()
+            .expect("failed to acquire peer_info lock")
+            .values_mut()
+        {
+            state.timeline_id = remapping.remap(state.timeline_id).unwrap_or(0);
+        }
+    }
+
     pub fn is_upstream_peer(&self, peer: PeerNetworkId) -> bool {
         self.upstream_config.is_upstream_peer(peer)
     }
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    core_mempool::CoreMempool,
+    core_mempool::{AdmissionFilter, CoreMempool, TimelineStateCallback},
     network::{MempoolNetworkEvents, MempoolNetworkSender},
     shared_mempool::{
         coordinator::{coordinator, gc_coordinator},
@@ -72,7 +72,7 @@ pub(crate) fn start_shared_mempool<V>(
         network_senders,
         db,
         validator,
-        peer_manager,
+        peer_manager: peer_manager.clone(),
         subscribers,
     };
 
@@ -89,6 +89,7 @@ pub(crate) fn start_shared_mempool<V>(
 
     executor.spawn(gc_coordinator(
         mempool,
+        peer_manager,
         config.mempool.system_transaction_gc_interval_ms,
     ));
 }
@@ -104,6 +105,10 @@ pub fn bootstrap(
     consensus_requests: Receiver<ConsensusRequest>,
     state_sync_requests: Receiver<CommitNotification>,
     mempool_reconfig_events: libra_channel::Receiver<(), OnChainConfigPayload>,
+    // Custom admission logic consulted before a transaction is added to Mempool, if any
+    admission_filter: Option<Box<dyn AdmissionFilter>>,
+    // Observer of the timeline state transitions of the resident transactions, if any
+    timeline_state_callback: Option<TimelineStateCallback>,
 ) -> Runtime {
     let runtime = Builder::new()
         .thread_name("shared-mem-")
@@ -111,7 +116,11 @@ pub fn bootstrap(
         .enable_all()
         .build()
         .expect("[shared mempool] failed to create runtime");
-    let mempool = Arc::new(Mutex::new(CoreMempool::new(&config)));
+    let mut mempool = CoreMempool::new(&config, admission_filter);
+    if let Some(callback) = timeline_state_callback {
+        mempool.set_timeline_state_callback(callback);
+    }
+    let mempool = Arc::new(Mutex::new(mempool));
     let vm_validator = Arc::new(RwLock::new(VMValidator::new(Arc::clone(&db))));
     start_shared_mempool(
         runtime.handle(),
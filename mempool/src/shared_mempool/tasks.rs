@@ -4,7 +4,7 @@
 //! Tasks that are executed by coordinators (short-lived compared to coordinators)
 
 use crate::{
-    core_mempool::{CoreMempool, TimelineState, TxnPointer},
+    core_mempool::{CoreMempool, TimelineState, TxnPointer, TxnRevalidator},
     counters,
     network::{MempoolNetworkSender, MempoolSyncMsg},
     shared_mempool::types::{
@@ -29,9 +29,9 @@ use libra_types::{
 };
 use std::{
     cmp,
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     ops::Deref,
-    sync::{Arc, Mutex, RwLock},
+    sync::{Arc, Mutex},
     time::{Duration, Instant},
 };
 use tokio::runtime::Handle;
@@ -66,6 +66,12 @@ where
     V: TransactionValidation,
 {
     let peer_manager = &smp.peer_manager;
+    // the timeline is read and the peer's timeline id advanced under the mempool lock, so that
+    // a timeline compaction can't renumber the timeline in between
+    let mut mempool = smp
+        .mempool
+        .lock()
+        .expect("[shared mempool] failed to acquire mempool lock");
     let timeline_id = if peer_manager.is_picked_peer(peer) {
         let state = peer_manager.get_peer_state(peer);
         if state.is_alive {
@@ -77,11 +83,8 @@ where
         return;
     };
 
-    let (transactions, new_timeline_id) = smp
-        .mempool
-        .lock()
-        .expect("[shared mempool] failed to acquire mempool lock")
-        .read_timeline(timeline_id, smp.config.shared_mempool_batch_size);
+    let (transactions, new_timeline_id) =
+        mempool.read_timeline(timeline_id, smp.config.shared_mempool_batch_size);
 
     if transactions.is_empty() {
         return;
@@ -92,7 +95,7 @@ where
         .get_mut(&peer.network_id())
         .expect("[shared mempool] missing network sender");
 
-    let request_id = create_request_id(timeline_id, new_timeline_id);
+    let request_id = create_request_id(timeline_id, new_timeline_id, mempool.timeline_epoch());
     let txns_ct = transactions.len();
     if let Err(e) = send_mempool_sync_msg(
         MempoolSyncMsg::BroadcastTransactionsRequest {
@@ -242,11 +245,12 @@ where
         })
         .collect::<Vec<_>>();
 
-    {
+    let timeline_notifications = {
         let mut mempool = smp
             .mempool
             .lock()
             .expect("[shared mempool] failed to acquire mempool lock");
+        let mut batch = vec![];
         for (idx, (transaction, sequence_number)) in transactions.into_iter().enumerate() {
             if let Ok(validation_result) = &validation_results[idx] {
                 match validation_result.status() {
@@ -254,15 +258,14 @@ where
                         let gas_amount = transaction.max_gas_amount();
                         let rankin_score = validation_result.score();
                         let is_governance_txn = validation_result.is_governance_txn();
-                        let mempool_status = mempool.add_txn(
+                        batch.push((
                             transaction,
                             gas_amount,
                             rankin_score,
                             sequence_number,
                             timeline_state,
                             is_governance_txn,
-                        );
-                        statuses.push((mempool_status, None));
+                        ));
                     }
                     Some(validation_status) => {
                         statuses.push((
@@ -273,7 +276,15 @@ where
                 }
             }
         }
-    }
+        statuses.extend(
+            mempool
+                .add_txns(batch)
+                .into_iter()
+                .map(|mempool_status| (mempool_status, None)),
+        );
+        mempool.take_timeline_notifications()
+    };
+    timeline_notifications.notify();
     notify_subscribers(SharedMempoolNotification::NewTransactions, &smp.subscribers);
     statuses
 }
@@ -318,14 +329,32 @@ pub(crate) fn process_broadcast_ack(
     }
 
     match parse_request_id(request_id) {
-        Ok((start_id, end_id)) => {
-            let mut mempool = mempool
-                .lock()
-                .expect("[shared mempool] failed to acquire mempool lock");
+        Ok((start_id, end_id, timeline_epoch)) => {
+            let timeline_notifications = {
+                let mut mempool = mempool
+                    .lock()
+                    .expect("[shared mempool] failed to acquire mempool lock");
+                // the timeline was compacted since the broadcast, the ids are stale
+                if timeline_epoch != mempool.timeline_epoch() {
+                    return;
+                }
 
-            for txn in mempool.timeline_range(start_id, end_id).iter() {
-                mempool.remove_transaction(&txn.sender(), txn.sequence_number(), false);
-            }
+                // the range is read in chunks of at most `max_timeline_read` timeline ids
+                let mut timeline_id = start_id;
+                while timeline_id < end_id {
+                    let (transactions, next_timeline_id) =
+                        mempool.timeline_range(timeline_id, end_id);
+                    for txn in transactions.iter() {
+                        mempool.remove_transaction(&txn.sender(), txn.sequence_number(), false);
+                    }
+                    if next_timeline_id <= timeline_id {
+                        break;
+                    }
+                    timeline_id = next_timeline_id;
+                }
+                mempool.take_timeline_notifications()
+            };
+            timeline_notifications.notify();
         }
         Err(err) => warn!("[shared mempool] ACK with invalid request_id: {:?}", err),
     }
@@ -399,51 +428,94 @@ async fn commit_txns(
     block_timestamp_usecs: u64,
     is_rejected: bool,
 ) {
-    let mut pool = mempool
-        .lock()
-        .expect("[shared mempool] failed to get mempool lock");
+    let timeline_notifications = {
+        let mut pool = mempool
+            .lock()
+            .expect("[shared mempool] failed to get mempool lock");
 
-    for transaction in transactions {
-        pool.remove_transaction(
-            &transaction.sender,
-            transaction.sequence_number,
-            is_rejected,
-        );
-    }
+        if is_rejected {
+            for transaction in transactions {
+                pool.remove_transaction(&transaction.sender, transaction.sequence_number, true);
+            }
+        } else {
+            // commit each account's transactions up to its highest committed sequence number
+            let mut highest_committed = HashMap::new();
+            for transaction in transactions {
+                let seq = highest_committed
+                    .entry(transaction.sender)
+                    .or_insert(transaction.sequence_number);
+                *seq = cmp::max(*seq, transaction.sequence_number);
+            }
+            for (sender, sequence_number) in highest_committed {
+                pool.commit_transactions(&sender, sequence_number);
+            }
+        }
 
-    if block_timestamp_usecs > 0 {
-        pool.gc_by_expiration_time(Duration::from_micros(block_timestamp_usecs));
-    }
+        if block_timestamp_usecs > 0 {
+            pool.gc_by_expiration_time(Duration::from_micros(block_timestamp_usecs));
+        }
+        pool.take_timeline_notifications()
+    };
+    timeline_notifications.notify();
 }
 
 /// processes on-chain reconfiguration notification
 pub(crate) async fn process_config_update<V>(
     config_update: OnChainConfigPayload,
-    validator: Arc<RwLock<V>>,
+    smp: SharedMempool<V>,
 ) where
     V: TransactionValidation,
 {
     // restart VM validator
-    validator
+    smp.validator
         .write()
         .unwrap()
         .restart(config_update)
         .expect("failed to restart VM validator");
+
+    // drop the resident transactions the new configuration no longer accepts
+    let validator = smp.validator.read().unwrap();
+    let revalidator = VMRevalidator(&*validator);
+    smp.mempool
+        .lock()
+        .expect("[shared mempool] failed to acquire mempool lock")
+        .revalidate(&revalidator);
+    notify_subscribers(SharedMempoolNotification::Reconfig, &smp.subscribers);
+}
+
+/// Revalidates the resident transactions with the VM validator, see `CoreMempool::revalidate`.
+/// A transaction the validator fails to process is kept, only the rejected ones are removed.
+struct VMRevalidator<'a, V>(&'a V);
+
+impl<'a, V> TxnRevalidator for VMRevalidator<'a, V>
+where
+    V: TransactionValidation,
+{
+    fn is_valid(&self, txn: &SignedTransaction) -> bool {
+        match self.0.validate_transaction(txn.clone()) {
+            Ok(result) => result.status().is_none(),
+            Err(_) => true,
+        }
+    }
 }
 
 /// creates uniques request id for the batch in the format "{start_id}_{end_id}"
 /// where start is an id in timeline index  that is lower than the first txn in a batch
 /// and end equals to timeline ID of last transaction in a batch
-fn create_request_id(start_timeline_id: u64, end_timeline_id: u64) -> String {
-    format!("{}_{}", start_timeline_id, end_timeline_id)
+fn create_request_id(start_timeline_id: u64, end_timeline_id: u64, timeline_epoch: u64) -> String {
+    format!(
+        "{}_{}_{}",
+        start_timeline_id, end_timeline_id, timeline_epoch
+    )
 }
 
-/// parses request_id according to format "{start_id}_{end_id}"
-fn parse_request_id(request_id: String) -> Result<(u64, u64)> {
+/// parses request_id according to format "{start_id}_{end_id}_{timeline_epoch}"
+fn parse_request_id(request_id: String) -> Result<(u64, u64, u64)> {
     let timeline_ids: Vec<_> = request_id.split('_').collect();
-    ensure!(timeline_ids.len() == 2, "invalid request_id {}", request_id);
+    ensure!(timeline_ids.len() == 3, "invalid request_id {}", request_id);
     let start_id = timeline_ids[0].parse::<u64>()?;
     let end_id = timeline_ids[1].parse::<u64>()?;
+    let timeline_epoch = timeline_ids[2].parse::<u64>()?;
     ensure!(start_id < end_id, "invalid broadcast range {}", request_id);
-    Ok((start_id, end_id))
+    Ok((start_id, end_id, timeline_epoch))
 }
@@ -58,6 +58,7 @@ pub enum SharedMempoolNotification {
     NewTransactions,
     ACK,
     Broadcast,
+    Reconfig,
 }
 
 pub(crate) fn notify_subscribers(
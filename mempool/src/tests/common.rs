@@ -1,7 +1,7 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::core_mempool::{CoreMempool, TimelineState, TxnPointer};
+use crate::core_mempool::{CoreMempool, MempoolClock, TimelineState, TxnPointer};
 use anyhow::{format_err, Result};
 use libra_config::config::NodeConfig;
 use libra_crypto::{ed25519::Ed25519PrivateKey, PrivateKey, Uniform};
@@ -13,11 +13,16 @@ use libra_types::{
 };
 use once_cell::sync::Lazy;
 use rand::{rngs::StdRng, SeedableRng};
-use std::{collections::HashSet, iter::FromIterator};
+use std::{
+    collections::HashSet,
+    iter::FromIterator,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
 
 pub(crate) fn setup_mempool() -> (CoreMempool, ConsensusMock) {
     (
-        CoreMempool::new(&NodeConfig::random()),
+        CoreMempool::new(&NodeConfig::random(), None),
         ConsensusMock::new(),
     )
 }
@@ -114,6 +119,7 @@ pub(crate) fn add_txns_to_mempool(
             0,
             TimelineState::NotReady,
             transaction.is_governance_txn,
+            None,
         );
         transactions.push(txn);
     }
@@ -133,6 +139,7 @@ pub(crate) fn add_signed_txn(pool: &mut CoreMempool, transaction: SignedTransact
             0,
             TimelineState::NotReady,
             false,
+            None,
         )
         .code
     {
@@ -184,3 +191,27 @@ pub(crate) fn exist_in_metrics_cache(mempool: &CoreMempool, txn: &SignedTransact
         .get(&(txn.sender(), txn.sequence_number()))
         .is_some()
 }
+
+/// Clock that only moves when it's advanced, clones share the same time
+#[derive(Clone)]
+pub(crate) struct FakeClock {
+    now: Arc<Mutex<SystemTime>>,
+}
+
+impl FakeClock {
+    pub(crate) fn new(now: SystemTime) -> Self {
+        Self {
+            now: Arc::new(Mutex::new(now)),
+        }
+    }
+
+    pub(crate) fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+impl MempoolClock for FakeClock {
+    fn now(&self) -> SystemTime {
+        *self.now.lock().unwrap()
+    }
+}
@@ -2,17 +2,23 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    core_mempool::{CoreMempool, TimelineState, TtlCache},
+    core_mempool::{AdmissionFilter, CoreMempool, TimelineState, TtlCache, TxnRevalidator},
+    counters,
     tests::common::{
         add_signed_txn, add_txn, add_txns_to_mempool, exist_in_metrics_cache, setup_mempool,
-        TestTransaction,
+        ConsensusMock, FakeClock, TestTransaction,
     },
 };
 use libra_config::config::NodeConfig;
-use libra_types::transaction::SignedTransaction;
+use libra_types::{
+    account_address::AccountAddress,
+    mempool_status::{MempoolStatus, MempoolStatusCode},
+    transaction::SignedTransaction,
+};
 use std::{
     collections::HashSet,
-    time::{Duration, SystemTime},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 #[test]
@@ -154,6 +160,38 @@ fn test_update_invalid_transaction_in_mempool() {
     assert_eq!(next_tnx[0].gas_unit_price(), 1);
 }
 
+#[test]
+fn test_gas_price_update_min_bump() {
+    let mut config = NodeConfig::random();
+    config.mempool.min_gas_price_bump_pct = 50;
+    let mut pool = CoreMempool::new(&config, None);
+    add_txn(&mut pool, TestTransaction::new(0, 0, 10)).unwrap();
+
+    // the bump is not big enough, original transaction is kept
+    let status = pool.add_txn(
+        TestTransaction::new(0, 0, 14).make_signed_transaction(),
+        0,
+        14,
+        0,
+        TimelineState::NotReady,
+        false,
+        None,
+    );
+    assert_eq!(status.code, MempoolStatusCode::InvalidUpdate);
+    let (timeline, last_timeline_id) = pool.read_timeline(0, 10);
+    assert_eq!(timeline.len(), 1);
+    assert_eq!(timeline[0].gas_unit_price(), 10);
+    assert_eq!(last_timeline_id, 1);
+
+    // the transaction is replaced and gets a new position in the timeline
+    let replacement = TestTransaction::new(0, 0, 15).make_signed_transaction();
+    add_signed_txn(&mut pool, replacement.clone()).unwrap();
+    let (timeline, last_timeline_id) = pool.read_timeline(0, 10);
+    assert_eq!(timeline, vec![replacement.clone()]);
+    assert_eq!(last_timeline_id, 2);
+    assert_eq!(pool.get_block(10, HashSet::new()), vec![replacement]);
+}
+
 #[test]
 fn test_remove_transaction() {
     let (mut pool, mut consensus) = setup_mempool();
@@ -175,13 +213,68 @@ fn test_remove_transaction() {
     assert_eq!(consensus.get_block(&mut pool, 1), vec!(new_txns[1].clone()));
 }
 
+#[test]
+fn test_priority_ordering_tie_breaking() {
+    let config = NodeConfig::random();
+    let clock = FakeClock::new(UNIX_EPOCH + Duration::from_secs(1000));
+    let mut pool = CoreMempool::new_with_clock(&config, None, Box::new(clock.clone()));
+    let (low, high) = if TestTransaction::get_address(0) < TestTransaction::get_address(1) {
+        (0, 1)
+    } else {
+        (1, 0)
+    };
+    let highest_score = TestTransaction::new(2, 0, 2).make_signed_transaction();
+    let earliest_expiration = TestTransaction::new(3, 0, 1).make_signed_transaction();
+    add_signed_txn(&mut pool, earliest_expiration.clone()).unwrap();
+    clock.advance(Duration::from_secs(1));
+    let low_txns = vec![
+        TestTransaction::new(low, 0, 1).make_signed_transaction(),
+        TestTransaction::new(low, 1, 1).make_signed_transaction(),
+    ];
+    let high_txn = TestTransaction::new(high, 0, 1).make_signed_transaction();
+    for txn in low_txns.iter().chain(vec![&high_txn, &highest_score]) {
+        add_signed_txn(&mut pool, txn.clone()).unwrap();
+    }
+
+    // highest score first, then earliest expiration time, then highest sender address, then
+    // lowest sequence number, regardless of insertion order
+    assert_eq!(
+        pool.get_block(10, HashSet::new()),
+        vec![
+            highest_score,
+            earliest_expiration,
+            high_txn,
+            low_txns[0].clone(),
+            low_txns[1].clone(),
+        ]
+    );
+}
+
+#[test]
+fn test_add_txns_batch() {
+    let mut pool = setup_mempool().0;
+    let txns: Vec<_> = (0..3)
+        .map(|seq| TestTransaction::new(0, seq, 1).make_signed_transaction())
+        .collect();
+    let statuses = pool.add_txns(
+        txns.iter()
+            .map(|txn| (txn.clone(), 0, 1, 0, TimelineState::NotReady, false))
+            .collect(),
+    );
+    assert_eq!(statuses.len(), 3);
+    assert!(statuses
+        .iter()
+        .all(|status| status.code == MempoolStatusCode::Accepted));
+    assert_eq!(pool.get_block(10, HashSet::new()), txns);
+}
+
 #[test]
 fn test_system_ttl() {
     // created mempool with system_transaction_timeout = 0
     // All transactions are supposed to be evicted on next gc run
     let mut config = NodeConfig::random();
     config.mempool.system_transaction_timeout_secs = 0;
-    let mut mempool = CoreMempool::new(&config);
+    let mut mempool = CoreMempool::new(&config, None);
 
     add_txn(&mut mempool, TestTransaction::new(0, 0, 10)).unwrap();
 
@@ -197,6 +290,28 @@ fn test_system_ttl() {
     assert_eq!(vec![transaction.make_signed_transaction()], batch);
 }
 
+#[test]
+fn test_gc_expired_and_collect() {
+    let mut config = NodeConfig::random();
+    config.mempool.system_transaction_timeout_secs = 0;
+    let mut mempool = CoreMempool::new(&config, None);
+
+    let expired = TestTransaction::new(0, 0, 10);
+    add_txn(&mut mempool, expired.clone()).unwrap();
+
+    mempool.system_transaction_timeout = Duration::from_secs(10);
+    let fresh = TestTransaction::new(1, 0, 1);
+    add_txn(&mut mempool, fresh.clone()).unwrap();
+
+    // only the expired transaction is returned, the fresh one stays in mempool
+    assert_eq!(
+        mempool.gc_expired_and_collect(),
+        vec![expired.make_signed_transaction()]
+    );
+    let batch = mempool.get_block(2, HashSet::new());
+    assert_eq!(vec![fresh.make_signed_transaction()], batch);
+}
+
 #[test]
 fn test_commit_callback() {
     // consensus commit callback should unlock txns in parking lot
@@ -283,7 +398,7 @@ fn test_capacity() {
     let mut config = NodeConfig::random();
     config.mempool.capacity = 1;
     config.mempool.system_transaction_timeout_secs = 0;
-    let mut pool = CoreMempool::new(&config);
+    let mut pool = CoreMempool::new(&config, None);
 
     // error on exceeding limit
     add_txn(&mut pool, TestTransaction::new(1, 0, 1)).unwrap();
@@ -299,11 +414,193 @@ fn test_capacity() {
     assert!(add_txn(&mut pool, TestTransaction::new(1, 2, 1)).is_ok());
 }
 
+#[test]
+fn test_max_txns_per_account() {
+    let mut config = NodeConfig::random();
+    config.mempool.max_txns_per_account = 3;
+    let mut pool = CoreMempool::new(&config, None);
+
+    for seq in 0..3 {
+        add_txn(&mut pool, TestTransaction::new(1, seq, 1)).unwrap();
+    }
+    // the account is at its cap
+    let txn = TestTransaction::new(1, 3, 1).make_signed_transaction();
+    let status = pool.add_txn(txn, 0, 1, 0, TimelineState::NotReady, false, None);
+    assert_eq!(status.code, MempoolStatusCode::TooManyTransactions);
+    // other accounts are not affected
+    add_txn(&mut pool, TestTransaction::new(0, 0, 1)).unwrap();
+
+    // commit transaction and free space
+    pool.remove_transaction(&TestTransaction::get_address(1), 0, false);
+    add_txn(&mut pool, TestTransaction::new(1, 3, 1)).unwrap();
+}
+
+#[test]
+fn test_update_rank() {
+    let mut pool = setup_mempool().0;
+    let txns = add_txns_to_mempool(
+        &mut pool,
+        vec![TestTransaction::new(0, 0, 5), TestTransaction::new(1, 0, 3)],
+    );
+    let (_, last_timeline_id) = pool.read_timeline(0, 10);
+    assert_eq!(
+        pool.get_block(10, HashSet::new()),
+        vec![txns[0].clone(), txns[1].clone()]
+    );
+
+    assert!(pool.update_rank(&TestTransaction::get_address(1), 0, 10, 7));
+    let view = pool
+        .get_transaction(&TestTransaction::get_address(1), 0)
+        .unwrap();
+    assert_eq!((view.ranking_score, view.gas_amount), (10, 7));
+    // the timeline is left as is
+    let (timeline, timeline_id) = pool.read_timeline(0, 10);
+    assert_eq!(timeline, txns);
+    assert_eq!(timeline_id, last_timeline_id);
+
+    assert_eq!(
+        pool.get_block(10, HashSet::new()),
+        vec![txns[1].clone(), txns[0].clone()]
+    );
+    assert!(!pool.update_rank(&TestTransaction::get_address(2), 0, 10, 7));
+}
+
+#[test]
+fn test_max_sequence_gap() {
+    let mut config = NodeConfig::random();
+    config.mempool.max_sequence_gap = 2;
+    let mut pool = CoreMempool::new(&config, None);
+    let account = TestTransaction::get_address(1);
+
+    // within the gap, the transaction is parked
+    add_txn(&mut pool, TestTransaction::new(1, 0, 1)).unwrap();
+    add_txn(&mut pool, TestTransaction::new(1, 2, 1)).unwrap();
+    assert_eq!(pool.get_block(10, HashSet::new()).len(), 1);
+
+    // beyond the gap
+    let txn = TestTransaction::new(1, 3, 1).make_signed_transaction();
+    let status = pool.add_txn(txn, 0, 1, 0, TimelineState::NotReady, false, None);
+    assert_eq!(status.code, MempoolStatusCode::SequenceNumberTooNew);
+    assert!(pool.get_transaction(&account, 3).is_none());
+
+    // once the intervening transactions are committed, the parked one is ready and the gap
+    // moves along
+    add_txn(&mut pool, TestTransaction::new(1, 1, 1)).unwrap();
+    pool.remove_transaction(&account, 0, false);
+    pool.remove_transaction(&account, 1, false);
+    let block = pool.get_block(10, HashSet::new());
+    assert_eq!(block.len(), 1);
+    assert_eq!(block[0].sequence_number(), 2);
+    add_txn(&mut pool, TestTransaction::new(1, 4, 1)).unwrap();
+}
+
+#[test]
+fn test_mempool_full_status_codes() {
+    let mut config = NodeConfig::random();
+    config.mempool.capacity = 3;
+    config.mempool.max_txns_per_account = 2;
+    let mut pool = CoreMempool::new(&config, None);
+    let mut add = |txn: TestTransaction| {
+        pool.add_txn(
+            txn.make_signed_transaction(),
+            0,
+            1,
+            0,
+            TimelineState::NotReady,
+            false,
+            None,
+        )
+    };
+
+    add(TestTransaction::new(0, 0, 1));
+    add(TestTransaction::new(0, 1, 1));
+    // account quota is exhausted while mempool still has space
+    let status = add(TestTransaction::new(0, 2, 1));
+    assert_eq!(status.code, MempoolStatusCode::TooManyTransactions);
+    assert!(status.message.contains("max txns per account"));
+
+    add(TestTransaction::new(1, 0, 1));
+    // mempool capacity is exhausted while account is under its quota
+    let status = add(TestTransaction::new(2, 0, 1));
+    assert_eq!(status.code, MempoolStatusCode::MempoolIsFull);
+    assert!(status.message.contains("mempool is full"));
+}
+
+#[test]
+fn test_min_gas_price() {
+    let (mut pool, mut consensus) = setup_mempool();
+    pool.set_min_gas_price(10);
+    let mut add = |txn: TestTransaction, is_governance_txn: bool| {
+        let txn = txn.make_signed_transaction();
+        pool.add_txn(
+            txn.clone(),
+            0,
+            txn.gas_unit_price(),
+            0,
+            TimelineState::NotReady,
+            is_governance_txn,
+            None,
+        )
+    };
+
+    // accepted at or above the threshold
+    assert_eq!(
+        add(TestTransaction::new(0, 0, 10), false).code,
+        MempoolStatusCode::Accepted
+    );
+    assert_eq!(
+        add(TestTransaction::new(1, 0, 20), false).code,
+        MempoolStatusCode::Accepted
+    );
+
+    // rejected below the threshold
+    let status = add(TestTransaction::new(2, 0, 9), false);
+    assert_eq!(status.code, MempoolStatusCode::GasPriceTooLow);
+    assert!(status.message.contains("min gas price is 10"));
+
+    // governance transactions bypass the threshold
+    assert_eq!(
+        add(TestTransaction::new(3, 0, 1), true).code,
+        MempoolStatusCode::Accepted
+    );
+    assert_eq!(consensus.get_block(&mut pool, 10).len(), 3);
+}
+
+#[test]
+fn test_governance_txn_with_ancestor_not_starved() {
+    let mut config = NodeConfig::random();
+    config.mempool.governance_reserved_fraction = 0.5;
+    let mut pool = CoreMempool::new(&config, None);
+    let mut consensus = ConsensusMock::new();
+
+    // governance txn depends on a low gas txn of the same account
+    let ancestor = TestTransaction::new(0, 0, 1);
+    let mut gov_txn = TestTransaction::new(0, 1, 1);
+    gov_txn.is_governance_txn = true;
+    let spam: Vec<_> = (1..4)
+        .map(|i| TestTransaction::new(i, 0, 100 * (5 - i as u64)))
+        .collect();
+    let mut txns = vec![ancestor.clone(), gov_txn.clone()];
+    txns.extend(spam.clone());
+    add_txns_to_mempool(&mut pool, txns);
+
+    // the governance txn and its ancestor take the reserved half of the block
+    assert_eq!(
+        consensus.get_block(&mut pool, 4),
+        vec![
+            ancestor.make_signed_transaction(),
+            gov_txn.make_signed_transaction(),
+            spam[0].make_signed_transaction(),
+            spam[1].make_signed_transaction(),
+        ]
+    );
+}
+
 #[test]
 fn test_parking_lot_eviction() {
     let mut config = NodeConfig::random();
     config.mempool.capacity = 5;
-    let mut pool = CoreMempool::new(&config);
+    let mut pool = CoreMempool::new(&config, None);
     // add transactions with following sequence numbers to Mempool
     for seq in &[0, 1, 2, 9, 10] {
         add_txn(&mut pool, TestTransaction::new(1, *seq, 1)).unwrap();
@@ -325,11 +622,37 @@ fn test_parking_lot_eviction() {
     assert!(add_txn(&mut pool, TestTransaction::new(0, 2, 1)).is_err());
 }
 
+#[test]
+fn test_gas_price_eviction() {
+    let mut config = NodeConfig::random();
+    config.mempool.capacity = 3;
+    let mut pool = CoreMempool::new(&config, None);
+    add_txn(&mut pool, TestTransaction::new(0, 0, 1)).unwrap();
+    add_txn(&mut pool, TestTransaction::new(0, 1, 5)).unwrap();
+    add_txn(&mut pool, TestTransaction::new(1, 0, 2)).unwrap();
+
+    // the cheapest transaction is required by a resident, so the next cheapest one is evicted
+    add_txn(&mut pool, TestTransaction::new(2, 0, 3)).unwrap();
+    let txns: HashSet<_> = pool
+        .get_block(5, HashSet::new())
+        .iter()
+        .map(|txn| (txn.sender(), txn.sequence_number()))
+        .collect();
+    let expected: HashSet<_> = vec![(0, 0), (0, 1), (2, 0)]
+        .into_iter()
+        .map(|(address, seq)| (TestTransaction::get_address(address), seq))
+        .collect();
+    assert_eq!(txns, expected);
+
+    // transaction that doesn't outrank anyone is rejected
+    assert!(add_txn(&mut pool, TestTransaction::new(3, 0, 1)).is_err());
+}
+
 #[test]
 fn test_parking_lot_evict_only_for_ready_txn_insertion() {
     let mut config = NodeConfig::random();
     config.mempool.capacity = 6;
-    let mut pool = CoreMempool::new(&config);
+    let mut pool = CoreMempool::new(&config, None);
     // add transactions with following sequence numbers to Mempool
     for seq in &[0, 1, 2, 9, 10, 11] {
         add_txn(&mut pool, TestTransaction::new(1, *seq, 1)).unwrap();
@@ -365,7 +688,7 @@ fn test_gc_ready_transaction() {
     // insert in the middle transaction that's going to be expired
     let txn = TestTransaction::new(1, 1, 1)
         .make_signed_transaction_with_expiration_time(Duration::from_secs(0));
-    pool.add_txn(txn, 0, 1, 0, TimelineState::NotReady, false);
+    pool.add_txn(txn, 0, 1, 0, TimelineState::NotReady, false, None);
 
     // insert few transactions after it
     // They supposed to be ready because there's sequential path from 0 to them
@@ -404,12 +727,601 @@ fn test_clean_stuck_transactions() {
         db_sequence_number,
         TimelineState::NotReady,
         false,
+        None,
     );
     let block = pool.get_block(10, HashSet::new());
     assert_eq!(block.len(), 1);
     assert_eq!(block[0].sequence_number(), 10);
 }
 
+#[test]
+fn test_cross_account_dependency() {
+    let mut pool = setup_mempool().0;
+    let add_txn_with_dependency = |pool: &mut CoreMempool, txn: TestTransaction, depends_on| {
+        let txn = txn.make_signed_transaction();
+        let status = pool.add_txn(
+            txn.clone(),
+            0,
+            txn.gas_unit_price(),
+            0,
+            TimelineState::NotReady,
+            false,
+            depends_on,
+        );
+        assert_eq!(status.code, MempoolStatusCode::Accepted);
+        txn
+    };
+    // B's transaction outranks A's one, but depends on it
+    let txn_a = add_txn_with_dependency(&mut pool, TestTransaction::new(0, 0, 1), None);
+    let txn_b = add_txn_with_dependency(
+        &mut pool,
+        TestTransaction::new(1, 0, 10),
+        Some((TestTransaction::get_address(0), 0)),
+    );
+    // transactions depending on each other are never included
+    for (account, dependency) in &[(2, 3), (3, 2)] {
+        add_txn_with_dependency(
+            &mut pool,
+            TestTransaction::new(*account, 0, 5),
+            Some((TestTransaction::get_address(*dependency), 0)),
+        );
+    }
+
+    assert_eq!(
+        pool.get_block(10, HashSet::new()),
+        vec![txn_a.clone(), txn_b.clone()]
+    );
+    // the dependency has been sent to Consensus already
+    let seen: HashSet<_> = vec![(TestTransaction::get_address(0), 0)]
+        .into_iter()
+        .collect();
+    assert_eq!(pool.get_block(10, seen), vec![txn_b.clone()]);
+    // the dependency has been committed
+    pool.remove_transaction(&TestTransaction::get_address(0), 0, false);
+    assert_eq!(pool.get_block(10, HashSet::new()), vec![txn_b]);
+}
+
+#[test]
+fn test_committed_txn_resubmission() {
+    let mut config = NodeConfig::random();
+    config.mempool.committed_cache_ttl_secs = 0;
+    config.mempool.known_txn_hash_ttl_secs = 0;
+    let mut pool = CoreMempool::new(&config, None);
+    let txn = TestTransaction::new(0, 0, 1).make_signed_transaction();
+    add_signed_txn(&mut pool, txn.clone()).unwrap();
+    pool.remove_transaction(&txn.sender(), 0, false);
+
+    // a delayed re-broadcast of the committed transaction is rejected right away
+    let resubmit = |pool: &mut CoreMempool| {
+        pool.add_txn(txn.clone(), 0, 1, 0, TimelineState::NotReady, false, None)
+    };
+    let status = resubmit(&mut pool);
+    assert_eq!(status.code, MempoolStatusCode::InvalidSeqNumber);
+    assert!(status.message.contains("already been committed"));
+
+    // once gc'd it's rejected based on the account's sequence number
+    pool.gc();
+    let status = resubmit(&mut pool);
+    assert_eq!(status.code, MempoolStatusCode::InvalidSeqNumber);
+    assert!(!status.message.contains("already been committed"));
+}
+
+#[test]
+fn test_max_txns_per_account_per_block() {
+    let mut pool = setup_mempool().0;
+    let whale_txns: Vec<_> = (0..10)
+        .map(|seq| TestTransaction::new(0, seq, 10))
+        .collect();
+    let whale_txns = add_txns_to_mempool(&mut pool, whale_txns);
+    let other_txns = add_txns_to_mempool(
+        &mut pool,
+        vec![TestTransaction::new(1, 0, 1), TestTransaction::new(2, 0, 1)],
+    );
+
+    let block = pool.get_block_with_max_txns_per_account(10, HashSet::new(), Some(3));
+    assert_eq!(block.len(), 5);
+    assert_eq!(&block[..3], &whale_txns[..3]);
+    for txn in &other_txns {
+        assert!(block.contains(txn));
+    }
+
+    // the rest of the account's transactions make it into the next blocks
+    let seen = block
+        .iter()
+        .map(|txn| (txn.sender(), txn.sequence_number()))
+        .collect();
+    let block = pool.get_block_with_max_txns_per_account(10, seen, Some(3));
+    assert_eq!(block, whale_txns[3..6].to_vec());
+}
+
+#[test]
+fn test_chain_continuation_across_blocks() {
+    let (mut pool, mut consensus) = setup_mempool();
+    let txns = add_txns_to_mempool(
+        &mut pool,
+        vec![
+            TestTransaction::new(0, 0, 200),
+            TestTransaction::new(0, 1, 1),
+            TestTransaction::new(1, 0, 100),
+        ],
+    );
+    assert_eq!(consensus.get_block(&mut pool, 1), vec![txns[0].clone()]);
+    // the account's chain is continued before higher ranked transactions of other accounts
+    assert_eq!(consensus.get_block(&mut pool, 1), vec![txns[1].clone()]);
+    assert_eq!(consensus.get_block(&mut pool, 1), vec![txns[2].clone()]);
+}
+
+#[test]
+fn test_get_transaction() {
+    let mut pool = setup_mempool().0;
+    let txn = TestTransaction::new(0, 0, 1).make_signed_transaction();
+    add_signed_txn(&mut pool, txn.clone()).unwrap();
+
+    let view = pool.get_transaction(&txn.sender(), 0).unwrap();
+    assert_eq!(view.txn, txn);
+    assert_eq!(view.timeline_state, TimelineState::NotReady);
+    assert_eq!(view.ranking_score, 1);
+    // unknown transactions aren't found
+    assert!(pool.get_transaction(&txn.sender(), 5).is_none());
+
+    // committed transactions are no longer resident
+    pool.remove_transaction(&txn.sender(), 0, false);
+    assert!(pool.get_transaction(&txn.sender(), 0).is_none());
+}
+
+#[test]
+fn test_timeline_read_metrics() {
+    let mut pool = setup_mempool().0;
+    add_txns_to_mempool(
+        &mut pool,
+        vec![TestTransaction::new(0, 0, 1), TestTransaction::new(0, 1, 1)],
+    );
+
+    // metrics are global, other tests may read the timeline concurrently
+    let reads = counters::TIMELINE_READ_COUNT.get();
+    let batches = counters::TIMELINE_READ_BATCH_SIZE.get_sample_count();
+    let batch_sizes = counters::TIMELINE_READ_BATCH_SIZE.get_sample_sum();
+    let (timeline, _) = pool.read_timeline(0, 10);
+    assert_eq!(timeline.len(), 2);
+    assert!(counters::TIMELINE_READ_COUNT.get() > reads);
+    assert!(counters::TIMELINE_READ_BATCH_SIZE.get_sample_count() > batches);
+    assert!(counters::TIMELINE_READ_BATCH_SIZE.get_sample_sum() >= batch_sizes + 2.0);
+}
+
+#[test]
+fn test_remove_account_transactions() {
+    let mut pool = setup_mempool().0;
+    let txns = add_txns_to_mempool(
+        &mut pool,
+        vec![
+            TestTransaction::new(0, 0, 1),
+            TestTransaction::new(0, 1, 1),
+            TestTransaction::new(0, 2, 1),
+            TestTransaction::new(1, 0, 1),
+        ],
+    );
+    let sender = TestTransaction::get_address(0);
+
+    assert_eq!(pool.remove_account_transactions(&sender), 3);
+    for sequence_number in 0..3 {
+        assert!(pool.get_transaction(&sender, sequence_number).is_none());
+    }
+    assert_eq!(pool.read_timeline(0, 10).0, vec![txns[3].clone()]);
+    assert_eq!(pool.get_block(10, HashSet::new()), vec![txns[3].clone()]);
+    assert_eq!(pool.remove_account_transactions(&sender), 0);
+
+    // the account can submit transactions again
+    add_signed_txn(&mut pool, txns[0].clone()).unwrap();
+    assert_eq!(pool.get_block(10, HashSet::new()).len(), 2);
+}
+
+#[test]
+fn test_snapshot_restore() {
+    let mut pool = setup_mempool().0;
+    let txns = add_txns_to_mempool(
+        &mut pool,
+        vec![
+            TestTransaction::new(0, 0, 1),
+            TestTransaction::new(0, 1, 10),
+            TestTransaction::new(1, 0, 5),
+            TestTransaction::new(2, 0, 3),
+            // not ready, its ancestor is missing
+            TestTransaction::new(3, 1, 20),
+        ],
+    );
+    let snapshot = pool.snapshot();
+    assert_eq!(snapshot.transactions.len(), txns.len());
+
+    let mut restored = setup_mempool().0;
+    restored.restore(snapshot.clone(), |_| 0);
+    let block = pool.get_block(10, HashSet::new());
+    assert_eq!(block.len(), 4);
+    assert_eq!(restored.get_block(10, HashSet::new()), block);
+    assert!(restored
+        .get_transaction(&TestTransaction::get_address(3), 1)
+        .is_some());
+
+    // the transactions that aren't ready and the lowest ranked ones are dropped if they don't fit
+    let mut config = NodeConfig::random();
+    config.mempool.capacity = 3;
+    let mut small = CoreMempool::new(&config, None);
+    small.restore(snapshot, |_| 0);
+    assert_eq!(small.get_block(10, HashSet::new()), block[..3].to_vec());
+}
+
+#[test]
+fn test_restore_queries_expired_sequence_numbers() {
+    let config = NodeConfig::random();
+    let clock = FakeClock::new(UNIX_EPOCH + Duration::from_secs(1000));
+    let mut pool = CoreMempool::new_with_clock(&config, None, Box::new(clock.clone()));
+    let txns: Vec<_> = (5..7)
+        .map(|seq| TestTransaction::new(0, seq, 1).make_signed_transaction())
+        .collect();
+    for txn in &txns {
+        let status = pool.add_txn(txn.clone(), 0, 1, 5, TimelineState::NotReady, false, None);
+        assert_eq!(status.code, MempoolStatusCode::Accepted);
+    }
+    // the cached sequence number of the account expires while its transactions are resident
+    clock.advance(Duration::from_secs(101));
+    pool.gc();
+    let snapshot = pool.snapshot();
+    assert!(snapshot.sequence_numbers.is_empty());
+
+    let mut restored = setup_mempool().0;
+    restored.restore(snapshot, |_| 5);
+    assert_eq!(restored.get_block(10, HashSet::new()), txns);
+}
+
+struct DenySender(AccountAddress);
+
+impl AdmissionFilter for DenySender {
+    fn admit(&self, txn: &SignedTransaction) -> Result<(), MempoolStatus> {
+        if txn.sender() == self.0 {
+            Err(MempoolStatus::new(MempoolStatusCode::VmError).with_message("denied".to_string()))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[test]
+fn test_admission_filter() {
+    let denied = TestTransaction::get_address(1);
+    let mut pool = CoreMempool::new(&NodeConfig::random(), Some(Box::new(DenySender(denied))));
+
+    let status = pool.add_txn(
+        TestTransaction::new(1, 0, 1).make_signed_transaction(),
+        0,
+        1,
+        0,
+        TimelineState::NotReady,
+        false,
+        None,
+    );
+    assert_eq!(status.code, MempoolStatusCode::VmError);
+    assert_eq!(status.message, "denied");
+    assert!(pool.get_transaction(&denied, 0).is_none());
+
+    // the batch path is filtered as well
+    let statuses = pool.add_txns(vec![
+        (
+            TestTransaction::new(0, 0, 1).make_signed_transaction(),
+            0,
+            1,
+            0,
+            TimelineState::NotReady,
+            false,
+        ),
+        (
+            TestTransaction::new(1, 1, 1).make_signed_transaction(),
+            0,
+            1,
+            0,
+            TimelineState::NotReady,
+            false,
+        ),
+    ]);
+    assert_eq!(statuses[0].code, MempoolStatusCode::Accepted);
+    assert_eq!(statuses[1].code, MempoolStatusCode::VmError);
+
+    add_txn(&mut pool, TestTransaction::new(2, 0, 1)).unwrap();
+    let mut accepted: Vec<_> = pool
+        .get_block(10, HashSet::new())
+        .iter()
+        .map(|txn| txn.sender())
+        .collect();
+    accepted.sort();
+    let mut expected = vec![
+        TestTransaction::get_address(0),
+        TestTransaction::get_address(2),
+    ];
+    expected.sort();
+    assert_eq!(accepted, expected);
+}
+
+#[test]
+fn test_gas_price_percentiles() {
+    let mut pool = setup_mempool().0;
+    assert_eq!(
+        pool.gas_price_percentiles(&[10.0, 50.0, 90.0]),
+        vec![0, 0, 0]
+    );
+
+    add_txns_to_mempool(
+        &mut pool,
+        vec![
+            TestTransaction::new(0, 0, 7),
+            TestTransaction::new(1, 0, 1),
+            TestTransaction::new(2, 0, 9),
+            TestTransaction::new(3, 0, 3),
+            TestTransaction::new(4, 0, 5),
+            // not ready, its ancestor is missing
+            TestTransaction::new(5, 1, 100),
+        ],
+    );
+    // not broadcastable, received from a peer
+    pool.add_txn(
+        TestTransaction::new(6, 0, 200).make_signed_transaction(),
+        0,
+        200,
+        0,
+        TimelineState::NonQualified,
+        false,
+        None,
+    );
+    assert_eq!(pool.gas_price_percentiles(&[50.0]), vec![5]);
+    assert_eq!(
+        pool.gas_price_percentiles(&[0.0, 10.0, 90.0, 100.0]),
+        vec![1, 1, 9, 9]
+    );
+}
+
+#[test]
+fn test_gc_system_ttl_boundary() {
+    let mut config = NodeConfig::random();
+    config.mempool.system_transaction_timeout_secs = 10;
+    let clock = FakeClock::new(UNIX_EPOCH + Duration::from_secs(1000));
+    let mut pool = CoreMempool::new_with_clock(&config, None, Box::new(clock.clone()));
+    let first = TestTransaction::new(0, 0, 1).make_signed_transaction();
+    let second = TestTransaction::new(1, 0, 1).make_signed_transaction();
+    add_signed_txn(&mut pool, first.clone()).unwrap();
+    clock.advance(Duration::from_secs(5));
+    add_signed_txn(&mut pool, second.clone()).unwrap();
+
+    // a transaction is kept up to its expiration time
+    clock.advance(Duration::from_secs(5));
+    pool.gc();
+    assert!(pool.get_transaction(&first.sender(), 0).is_some());
+
+    // and removed right after it
+    clock.advance(Duration::from_millis(1));
+    pool.gc();
+    assert!(pool.get_transaction(&first.sender(), 0).is_none());
+    assert!(pool.get_transaction(&second.sender(), 0).is_some());
+
+    clock.advance(Duration::from_secs(5));
+    pool.gc();
+    assert!(pool.get_transaction(&second.sender(), 0).is_none());
+}
+
+#[test]
+fn test_sequence_number_cache_follows_clock() {
+    let config = NodeConfig::random();
+    let clock = FakeClock::new(UNIX_EPOCH + Duration::from_secs(1000));
+    let mut pool = CoreMempool::new_with_clock(&config, None, Box::new(clock.clone()));
+    let txn = TestTransaction::new(0, 5, 1).make_signed_transaction();
+    let status = pool.add_txn(txn, 0, 1, 5, TimelineState::NotReady, false, None);
+    assert_eq!(status.code, MempoolStatusCode::Accepted);
+
+    // the cached sequence number of the account expires on the mempool clock, after which
+    // the sequence number from storage is used
+    clock.advance(Duration::from_secs(101));
+    pool.gc();
+    let txn = TestTransaction::new(0, 3, 1).make_signed_transaction();
+    let status = pool.add_txn(txn, 0, 1, 0, TimelineState::NotReady, false, None);
+    assert_eq!(status.code, MempoolStatusCode::Accepted);
+}
+
+#[test]
+fn test_ranking_aging() {
+    let mut config = NodeConfig::random();
+    config.mempool.ranking_aging_coefficient = 1.0;
+    let clock = FakeClock::new(UNIX_EPOCH + Duration::from_secs(1000));
+    let mut pool = CoreMempool::new_with_clock(&config, None, Box::new(clock.clone()));
+    let old = TestTransaction::new(0, 0, 1).make_signed_transaction();
+    add_signed_txn(&mut pool, old.clone()).unwrap();
+
+    // a freshly arrived mid gas transaction still outranks it
+    clock.advance(Duration::from_secs(2));
+    let mid = TestTransaction::new(1, 0, 5).make_signed_transaction();
+    add_signed_txn(&mut pool, mid.clone()).unwrap();
+    assert_eq!(pool.get_block(1, HashSet::new()), vec![mid.clone()]);
+
+    // but not the ones arriving once it has waited long enough
+    clock.advance(Duration::from_secs(8));
+    let newer_mid = TestTransaction::new(2, 0, 5).make_signed_transaction();
+    add_signed_txn(&mut pool, newer_mid.clone()).unwrap();
+    assert_eq!(
+        pool.get_block(3, HashSet::new()),
+        vec![mid, old.clone(), newer_mid.clone()]
+    );
+
+    // without aging the gas price alone decides
+    config.mempool.ranking_aging_coefficient = 0.0;
+    let mut pool = CoreMempool::new_with_clock(&config, None, Box::new(clock.clone()));
+    add_signed_txn(&mut pool, old.clone()).unwrap();
+    clock.advance(Duration::from_secs(10));
+    add_signed_txn(&mut pool, newer_mid.clone()).unwrap();
+    assert_eq!(pool.get_block(1, HashSet::new()), vec![newer_mid]);
+}
+
+#[test]
+fn test_commit_at_max_sequence_number() {
+    let (mut pool, mut consensus) = setup_mempool();
+    let txns: Vec<_> = vec![
+        TestTransaction::new(0, u64::MAX - 1, 1),
+        TestTransaction::new(0, u64::MAX, 1),
+    ]
+    .iter()
+    .map(TestTransaction::make_signed_transaction)
+    .collect();
+    for txn in &txns {
+        let status = pool.add_txn(
+            txn.clone(),
+            0,
+            txn.gas_unit_price(),
+            u64::MAX - 1,
+            TimelineState::NotReady,
+            false,
+            None,
+        );
+        assert_eq!(status.code, MempoolStatusCode::Accepted);
+    }
+    assert_eq!(consensus.get_block(&mut pool, 10), txns);
+    assert!(consensus.get_block(&mut pool, 10).is_empty());
+
+    pool.remove_transaction(&txns[0].sender(), u64::MAX - 1, false);
+    assert_eq!(pool.get_block(10, HashSet::new()), vec![txns[1].clone()]);
+    pool.remove_transaction(&txns[1].sender(), u64::MAX, false);
+    assert!(pool.get_block(10, HashSet::new()).is_empty());
+    assert!(pool.get_transaction(&txns[1].sender(), u64::MAX).is_none());
+}
+
+#[test]
+fn test_ready_accounts() {
+    let mut pool = setup_mempool().0;
+    assert!(pool.ready_accounts().is_empty());
+
+    add_txns_to_mempool(
+        &mut pool,
+        vec![
+            TestTransaction::new(0, 0, 1),
+            TestTransaction::new(0, 1, 1),
+            // the next transaction of the account is missing
+            TestTransaction::new(1, 1, 1),
+            TestTransaction::new(2, 0, 1),
+            TestTransaction::new(2, 2, 1),
+            TestTransaction::new(3, 0, 1),
+        ],
+    );
+    // committed, nothing follows it
+    pool.remove_transaction(&TestTransaction::get_address(3), 0, false);
+
+    let mut expected: Vec<_> = vec![0, 2]
+        .into_iter()
+        .map(TestTransaction::get_address)
+        .collect();
+    expected.sort();
+    assert_eq!(pool.ready_accounts(), expected);
+}
+
+#[test]
+fn test_timeline_state_callback() {
+    let mut pool = setup_mempool().0;
+    let transitions = Arc::new(Mutex::new(vec![]));
+    let recorded = transitions.clone();
+    pool.set_timeline_state_callback(Box::new(move |sender, seq, old_state, new_state| {
+        recorded
+            .lock()
+            .unwrap()
+            .push((sender, seq, old_state, new_state));
+    }));
+
+    // parked, its ancestor is missing
+    add_txn(&mut pool, TestTransaction::new(0, 1, 1)).unwrap();
+    pool.take_timeline_notifications().notify();
+    assert!(transitions.lock().unwrap().is_empty());
+
+    // the ancestor arrives, both become ready
+    add_txn(&mut pool, TestTransaction::new(0, 0, 1)).unwrap();
+    // the transitions are only delivered on notify
+    assert!(transitions.lock().unwrap().is_empty());
+    pool.take_timeline_notifications().notify();
+    let sender = TestTransaction::get_address(0);
+    assert_eq!(
+        *transitions.lock().unwrap(),
+        vec![
+            (sender, 0, TimelineState::NotReady, TimelineState::Ready(1)),
+            (sender, 1, TimelineState::NotReady, TimelineState::Ready(2)),
+        ]
+    );
+    transitions.lock().unwrap().clear();
+
+    // the head of the account expires, the following transaction is parked again
+    let txn = TestTransaction::new(1, 0, 1)
+        .make_signed_transaction_with_expiration_time(Duration::from_secs(0));
+    pool.add_txn(txn, 0, 1, 0, TimelineState::NotReady, false, None);
+    add_txn(&mut pool, TestTransaction::new(1, 1, 1)).unwrap();
+    pool.take_timeline_notifications();
+    pool.gc_by_expiration_time(Duration::from_secs(1));
+    pool.take_timeline_notifications().notify();
+    let sender = TestTransaction::get_address(1);
+    assert_eq!(
+        *transitions.lock().unwrap(),
+        vec![(sender, 1, TimelineState::Ready(4), TimelineState::NotReady)]
+    );
+}
+
+#[test]
+fn test_commit_transactions() {
+    let mut pool = setup_mempool().0;
+    let txns = add_txns_to_mempool(
+        &mut pool,
+        (0..6).map(|seq| TestTransaction::new(0, seq, 1)).collect(),
+    );
+    let sender = TestTransaction::get_address(0);
+
+    pool.commit_transactions(&sender, 4);
+    for seq in 0..5 {
+        assert!(pool.get_transaction(&sender, seq).is_none());
+    }
+    // the account's sequence number is 5 now
+    assert_eq!(pool.get_block(10, HashSet::new()), vec![txns[5].clone()]);
+    assert!(add_txn(&mut pool, TestTransaction::new(0, 4, 1)).is_err());
+}
+
+#[test]
+fn test_stats() {
+    let mut pool = setup_mempool().0;
+    let mut add = |txn: TestTransaction, timeline_state: TimelineState| {
+        let txn = txn.make_signed_transaction();
+        pool.add_txn(
+            txn.clone(),
+            0,
+            txn.gas_unit_price(),
+            0,
+            timeline_state,
+            false,
+            None,
+        )
+        .code
+    };
+    // ready for broadcast
+    assert_eq!(
+        add(TestTransaction::new(0, 0, 1), TimelineState::NotReady),
+        MempoolStatusCode::Accepted
+    );
+    // not ready because of the sequence number gap
+    assert_eq!(
+        add(TestTransaction::new(0, 2, 1), TimelineState::NotReady),
+        MempoolStatusCode::Accepted
+    );
+    assert_eq!(
+        add(TestTransaction::new(1, 0, 1), TimelineState::NonQualified),
+        MempoolStatusCode::Accepted
+    );
+
+    let stats = pool.stats();
+    assert_eq!(stats.total_txns, 3);
+    assert_eq!(stats.ready_txns, 1);
+    assert_eq!(stats.not_ready_txns, 1);
+    assert_eq!(stats.non_qualified_txns, 1);
+    assert_eq!(stats.accounts, 2);
+    assert_eq!(stats.timeline_id, 1);
+    // non-qualified transactions aren't tracked for latency
+    assert_eq!(stats.metrics_cache_size, 2);
+}
+
 #[test]
 fn test_ttl_cache() {
     let mut cache = TtlCache::new(2, Duration::from_secs(1));
@@ -433,3 +1345,432 @@ fn test_ttl_cache() {
         .unwrap());
     assert_eq!(cache.size(), 0);
 }
+
+#[test]
+fn test_count_eligible() {
+    let mut pool = setup_mempool().0;
+    add_txns_to_mempool(
+        &mut pool,
+        vec![
+            TestTransaction::new(0, 0, 1),
+            TestTransaction::new(0, 1, 1),
+            // outranks its ancestors, skipped until they're included
+            TestTransaction::new(0, 2, 5),
+            // the next transaction of the account is missing
+            TestTransaction::new(1, 1, 1),
+            TestTransaction::new(2, 0, 1),
+            TestTransaction::new(2, 1, 1),
+        ],
+    );
+
+    let mut seen = HashSet::new();
+    assert_eq!(pool.count_eligible(&seen), 5);
+    assert_eq!(pool.get_block(100, seen.clone()).len(), 5);
+
+    // the ancestor was already sent to Consensus
+    seen.insert((TestTransaction::get_address(2), 0));
+    assert_eq!(pool.count_eligible(&seen), 4);
+    assert_eq!(pool.get_block(100, seen).len(), 4);
+}
+
+#[test]
+fn test_stuck_accounts() {
+    let mut pool = setup_mempool().0;
+    add_txns_to_mempool(
+        &mut pool,
+        vec![
+            TestTransaction::new(0, 0, 1),
+            TestTransaction::new(0, 1, 1),
+            // the client dropped the transaction with sequence number 0
+            TestTransaction::new(1, 1, 1),
+            TestTransaction::new(1, 2, 1),
+            TestTransaction::new(2, 3, 1),
+        ],
+    );
+    let mut expected = vec![
+        (TestTransaction::get_address(1), 1),
+        (TestTransaction::get_address(2), 3),
+    ];
+    expected.sort();
+    assert_eq!(pool.stuck_accounts(), expected);
+
+    // the missing transaction shows up
+    add_txn(&mut pool, TestTransaction::new(1, 0, 1)).unwrap();
+    assert_eq!(
+        pool.stuck_accounts(),
+        vec![(TestTransaction::get_address(2), 3)]
+    );
+}
+
+#[test]
+fn test_timeline_compaction() {
+    let mut pool = setup_mempool().0;
+    let mut txns = vec![];
+    for account in 0..10 {
+        for seq in 0..5 {
+            txns.push(TestTransaction::new(account, seq, 1));
+        }
+    }
+    add_txns_to_mempool(&mut pool, txns);
+    // commit most of the transactions, leaving the timeline ids mostly unused
+    for account in 0..10 {
+        pool.remove_transaction(&TestTransaction::get_address(account), 2, false);
+    }
+    let view = |txns: Vec<SignedTransaction>| -> Vec<(AccountAddress, u64)> {
+        txns.iter()
+            .map(|txn| (txn.sender(), txn.sequence_number()))
+            .collect()
+    };
+    let (timeline, _) = pool.read_timeline(0, 100);
+    let timeline = view(timeline);
+    assert_eq!(timeline.len(), 20);
+    // a consumer that read part of the timeline
+    let (_, cursor) = pool.read_timeline(0, 7);
+    let (unread, _) = pool.read_timeline(cursor, 100);
+
+    // the low-water mark can't be past the timeline
+    assert!(pool.gc_and_compact_timeline(51).is_err());
+    let remapping = pool.gc_and_compact_timeline(cursor).unwrap().unwrap();
+    let (compacted, last_timeline_id) = pool.read_timeline(0, 100);
+    assert_eq!(view(compacted), timeline);
+    assert_eq!(last_timeline_id, 20);
+    let new_cursor = remapping.remap(cursor).unwrap();
+    assert_eq!(new_cursor, 7);
+    assert_eq!(view(pool.read_timeline(new_cursor, 100).0), view(unread));
+    // the consumers behind the low-water mark can't be translated
+    assert_eq!(remapping.remap(cursor - 1), None);
+    // nothing left to compact
+    assert!(pool.gc_and_compact_timeline(new_cursor).unwrap().is_none());
+
+    // new transactions follow the compacted timeline
+    add_txn(&mut pool, TestTransaction::new(10, 0, 1)).unwrap();
+    let (timeline, last_timeline_id) = pool.read_timeline(20, 100);
+    assert_eq!(view(timeline), vec![(TestTransaction::get_address(10), 0)]);
+    assert_eq!(last_timeline_id, 21);
+}
+
+#[test]
+fn test_timeline_compaction_notifies_renumbering() {
+    let mut pool = setup_mempool().0;
+    let transitions = Arc::new(Mutex::new(vec![]));
+    let recorded = transitions.clone();
+    pool.set_timeline_state_callback(Box::new(move |sender, seq, old_state, new_state| {
+        recorded
+            .lock()
+            .unwrap()
+            .push((sender, seq, old_state, new_state));
+    }));
+    for account in 0..4 {
+        add_txn(&mut pool, TestTransaction::new(account, 0, 1)).unwrap();
+    }
+    for account in 0..3 {
+        pool.remove_transaction(&TestTransaction::get_address(account), 0, false);
+    }
+    pool.take_timeline_notifications();
+
+    assert!(pool.gc_and_compact_timeline(4).unwrap().is_some());
+    pool.take_timeline_notifications().notify();
+    assert_eq!(
+        *transitions.lock().unwrap(),
+        vec![(
+            TestTransaction::get_address(3),
+            0,
+            TimelineState::Ready(4),
+            TimelineState::Ready(1)
+        )]
+    );
+}
+
+#[test]
+fn test_class_capacity() {
+    let mut config = NodeConfig::random();
+    config.mempool.capacity = 10;
+    config.mempool.regular_capacity_fraction = 0.5;
+    let mut pool = CoreMempool::new(&config, None);
+    let mut add = |txn: TestTransaction, is_governance_txn: bool| {
+        let txn = txn.make_signed_transaction();
+        pool.add_txn(
+            txn.clone(),
+            0,
+            txn.gas_unit_price(),
+            0,
+            TimelineState::NotReady,
+            is_governance_txn,
+            None,
+        )
+        .code
+    };
+
+    // fill the quota of regular transactions, the last one is parked
+    for account in 0..4 {
+        assert_eq!(
+            add(TestTransaction::new(account, 0, 1), false),
+            MempoolStatusCode::Accepted
+        );
+    }
+    assert_eq!(
+        add(TestTransaction::new(4, 1, 1), false),
+        MempoolStatusCode::Accepted
+    );
+
+    // the parked transaction is evicted to make room, although it isn't outranked
+    assert_eq!(
+        add(TestTransaction::new(5, 0, 1), false),
+        MempoolStatusCode::Accepted
+    );
+    assert_eq!(
+        add(TestTransaction::new(11, 0, 1), false),
+        MempoolStatusCode::ClassQuotaFull
+    );
+
+    // governance transactions are still admitted
+    for account in 6..9 {
+        assert_eq!(
+            add(TestTransaction::new(account, 0, 1), true),
+            MempoolStatusCode::Accepted
+        );
+    }
+
+    // a higher gas regular transaction only evicts a regular one
+    assert_eq!(
+        add(TestTransaction::new(9, 0, 5), false),
+        MempoolStatusCode::Accepted
+    );
+    assert_eq!(
+        add(TestTransaction::new(10, 0, 1), false),
+        MempoolStatusCode::ClassQuotaFull
+    );
+    assert_eq!(pool.get_block(10, HashSet::new()).len(), 8);
+}
+
+#[test]
+fn test_fair_queuing() {
+    let mut config = NodeConfig::random();
+    config.mempool.fair_queuing = true;
+    let mut pool = CoreMempool::new(&config, None);
+    for (account, weight) in [(0, 1), (1, 2), (2, 3)].iter() {
+        pool.set_account_weight(TestTransaction::get_address(*account), *weight);
+        // the later transactions outrank the earlier ones, but are still included in order
+        let txns = (0..10)
+            .map(|seq| TestTransaction::new(*account, seq, 1 + seq))
+            .collect();
+        add_txns_to_mempool(&mut pool, txns);
+    }
+
+    let block = pool.get_block(12, HashSet::new());
+    assert_eq!(block.len(), 12);
+    for (account, share) in [(0, 2), (1, 4), (2, 6)].iter() {
+        let sequence_numbers: Vec<_> = block
+            .iter()
+            .filter(|txn| txn.sender() == TestTransaction::get_address(*account))
+            .map(|txn| txn.sequence_number())
+            .collect();
+        assert_eq!(sequence_numbers, (0..*share).collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn test_known_txn_hash() {
+    let mut pool = setup_mempool().0;
+    let txn = TestTransaction::new(0, 0, 1).make_signed_transaction();
+    add_signed_txn(&mut pool, txn.clone()).unwrap();
+
+    // the identical transaction gossiped back is recognized without being processed again
+    let status = pool.add_txn(txn.clone(), 0, 1, 0, TimelineState::NotReady, false, None);
+    assert_eq!(status.code, MempoolStatusCode::AlreadyKnown);
+    assert_eq!(pool.read_timeline(0, 10).0, vec![txn.clone()]);
+
+    // once rejected, the transaction can be submitted again
+    pool.remove_transaction(&txn.sender(), 0, true);
+    add_signed_txn(&mut pool, txn.clone()).unwrap();
+
+    // same once it expired
+    let txn = TestTransaction::new(1, 0, 1)
+        .make_signed_transaction_with_expiration_time(Duration::from_secs(0));
+    add_signed_txn(&mut pool, txn.clone()).unwrap();
+    pool.gc_by_expiration_time(Duration::from_secs(1));
+    let status = pool.add_txn(txn, 0, 1, 0, TimelineState::NotReady, false, None);
+    assert_eq!(status.code, MempoolStatusCode::Accepted);
+}
+
+#[test]
+fn test_top_transactions() {
+    let mut pool = setup_mempool().0;
+    let txns = add_txns_to_mempool(
+        &mut pool,
+        vec![
+            TestTransaction::new(0, 0, 5),
+            TestTransaction::new(1, 0, 20),
+            TestTransaction::new(2, 0, 1),
+            TestTransaction::new(3, 0, 10),
+        ],
+    );
+
+    let top = pool.top_transactions(2);
+    assert_eq!(
+        top.iter().map(|view| view.txn.clone()).collect::<Vec<_>>(),
+        vec![txns[1].clone(), txns[3].clone()]
+    );
+    assert_eq!(top[0].ranking_score, 20);
+    // nothing is consumed
+    assert_eq!(pool.top_transactions(2), top);
+    assert_eq!(pool.get_block(10, HashSet::new()).len(), 4);
+}
+
+struct RejectSenders(HashSet<AccountAddress>);
+
+impl TxnRevalidator for RejectSenders {
+    fn is_valid(&self, txn: &SignedTransaction) -> bool {
+        !self.0.contains(&txn.sender())
+    }
+}
+
+#[test]
+fn test_revalidate() {
+    let mut pool = setup_mempool().0;
+    let txns = add_txns_to_mempool(
+        &mut pool,
+        (0..4)
+            .map(|account| TestTransaction::new(account, 0, 1))
+            .collect(),
+    );
+
+    let validator = RejectSenders(
+        [0, 2]
+            .iter()
+            .map(|account| TestTransaction::get_address(*account))
+            .collect(),
+    );
+    assert_eq!(pool.revalidate(&validator), 2);
+    assert_eq!(pool.revalidate(&validator), 0);
+    let block: HashSet<_> = pool.get_block(10, HashSet::new()).into_iter().collect();
+    assert_eq!(
+        block,
+        vec![txns[1].clone(), txns[3].clone()].into_iter().collect()
+    );
+    assert!(pool
+        .get_transaction(&TestTransaction::get_address(0), 0)
+        .is_none());
+}
+
+#[test]
+fn test_max_timeline_read() {
+    let mut config = NodeConfig::random();
+    config.mempool.max_timeline_read = 2;
+    let mut pool = CoreMempool::new(&config, None);
+    let txns = add_txns_to_mempool(
+        &mut pool,
+        (0..5)
+            .map(|account| TestTransaction::new(account, 0, 1))
+            .collect(),
+    );
+
+    // the batch is clamped, reading on from the returned cursor doesn't skip anything
+    let (batch, timeline_id) = pool.read_timeline(0, 10);
+    assert_eq!(batch, txns[..2].to_vec());
+    assert_eq!(timeline_id, 2);
+    let (batch, timeline_id) = pool.read_timeline(timeline_id, 10);
+    assert_eq!(batch, txns[2..4].to_vec());
+    assert_eq!(timeline_id, 4);
+
+    let (batch, timeline_id) = pool.timeline_range(1, 10);
+    assert_eq!(batch, txns[1..3].to_vec());
+    assert_eq!(timeline_id, 3);
+    let (batch, timeline_id) = pool.timeline_range(timeline_id, 10);
+    assert_eq!(batch, txns[3..].to_vec());
+    assert_eq!(timeline_id, 5);
+}
+
+#[test]
+fn test_gas_floor_follows_occupancy() {
+    let mut config = NodeConfig::random();
+    config.mempool.capacity = 10;
+    config.mempool.min_gas_price = 1;
+    config.mempool.max_gas_floor = 101;
+    config.mempool.gas_floor_occupancy_threshold = 0.5;
+    let mut pool = CoreMempool::new(&config, None);
+
+    // up to the threshold the floor stays at the configured min gas price
+    add_txns_to_mempool(
+        &mut pool,
+        (0..5)
+            .map(|seq| TestTransaction::new(0, seq, 200))
+            .collect(),
+    );
+    pool.gc();
+    assert_eq!(pool.min_gas_price(), 1);
+
+    // 80% full: 60% of the way from the min gas price to the max floor
+    add_txns_to_mempool(
+        &mut pool,
+        (0..3)
+            .map(|seq| TestTransaction::new(1, seq, 200))
+            .collect(),
+    );
+    pool.gc();
+    assert_eq!(pool.min_gas_price(), 61);
+    assert!(add_txn(&mut pool, TestTransaction::new(2, 0, 60)).is_err());
+    add_txn(&mut pool, TestTransaction::new(2, 0, 61)).unwrap();
+
+    // the floor falls as Mempool drains
+    pool.commit_transactions(&TestTransaction::get_address(0), 4);
+    pool.gc();
+    assert_eq!(pool.min_gas_price(), 1);
+    add_txn(&mut pool, TestTransaction::new(3, 0, 1)).unwrap();
+}
+
+#[test]
+fn test_duplicate_commit_notification() {
+    let config = NodeConfig::random();
+    let clock = FakeClock::new(UNIX_EPOCH + Duration::from_secs(1000));
+    let mut pool = CoreMempool::new_with_clock(&config, None, Box::new(clock.clone()));
+    let txns = add_txns_to_mempool(
+        &mut pool,
+        vec![TestTransaction::new(0, 0, 1), TestTransaction::new(0, 2, 1)],
+    );
+    let sender = TestTransaction::get_address(0);
+
+    let duplicates = counters::DUPLICATE_REMOVE_TRANSACTION_COUNT.get();
+    pool.remove_transaction(&sender, 0, false);
+    // the account's cached sequence number is 1, short of the resident transaction
+    assert_eq!(pool.stuck_accounts(), vec![(sender, 1)]);
+
+    // the retried notification is ignored, it doesn't refresh the cached sequence number
+    clock.advance(Duration::from_secs(60));
+    pool.remove_transaction(&sender, 0, false);
+    assert!(counters::DUPLICATE_REMOVE_TRANSACTION_COUNT.get() > duplicates);
+    clock.advance(Duration::from_secs(60));
+    pool.gc();
+    assert!(pool.stuck_accounts().is_empty());
+    assert_eq!(pool.get_transaction(&sender, 2).unwrap().txn, txns[1]);
+}
+
+#[test]
+fn test_get_block_paged() {
+    let mut pool = setup_mempool().0;
+    add_txns_to_mempool(
+        &mut pool,
+        vec![
+            TestTransaction::new(0, 0, 6),
+            TestTransaction::new(0, 1, 10),
+            TestTransaction::new(1, 0, 5),
+            TestTransaction::new(1, 1, 4),
+            TestTransaction::new(2, 0, 3),
+            TestTransaction::new(3, 0, 8),
+            TestTransaction::new(4, 0, 7),
+        ],
+    );
+    let block = pool.get_block(6, HashSet::new());
+
+    // the first page ends right before the skipped transaction of account 0 it unblocked
+    let (first_page, cursor) = pool.get_block_paged(3, HashSet::new(), None);
+    assert_eq!(first_page, block[..3].to_vec());
+    let (second_page, cursor) = pool.get_block_paged(3, HashSet::new(), cursor);
+    assert_eq!(second_page, block[3..].to_vec());
+
+    // the last page is the rest of the queue
+    let (last_page, cursor) = pool.get_block_paged(3, HashSet::new(), cursor);
+    assert_eq!(last_page.len(), 1);
+    assert_eq!(last_page[0].sender(), TestTransaction::get_address(2));
+    assert!(cursor.is_none());
+}
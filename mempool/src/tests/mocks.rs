@@ -55,7 +55,7 @@ impl MockSharedMempool {
         config.validator_network = Some(NetworkConfig::network_with_id(NetworkId::Validator));
         let peer_id = config.validator_network.as_ref().unwrap().peer_id();
 
-        let mempool = Arc::new(Mutex::new(CoreMempool::new(&config)));
+        let mempool = Arc::new(Mutex::new(CoreMempool::new(&config, None)));
         let (network_reqs_tx, _network_reqs_rx) =
             libra_channel::new(QueueStyle::FIFO, NonZeroUsize::new(8).unwrap(), None);
         let (connection_reqs_tx, _) =
@@ -120,6 +120,7 @@ impl MockSharedMempool {
                         0,
                         TimelineState::NotReady,
                         false,
+                        None,
                     )
                     .code
                     != MempoolStatusCode::Accepted
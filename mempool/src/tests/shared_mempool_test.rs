@@ -9,6 +9,7 @@ use crate::{
     tests::common::{batch_add_signed_txn, TestTransaction},
     CommitNotification, CommittedTransaction, ConsensusRequest,
 };
+use anyhow::Result;
 use channel::{self, libra_channel, message_queues::QueueStyle};
 use futures::{
     channel::{
@@ -25,7 +26,13 @@ use libra_config::{
     network_id::NetworkId,
 };
 use libra_network_address::NetworkAddress;
-use libra_types::{transaction::SignedTransaction, PeerId};
+use libra_types::{
+    account_address::AccountAddress,
+    on_chain_config::OnChainConfigPayload,
+    transaction::{SignedTransaction, VMValidatorResult},
+    vm_error::{StatusCode, VMStatus},
+    PeerId,
+};
 use network::{
     peer_manager::{
         conn_notifs_channel, ConnectionNotification, ConnectionRequestSender,
@@ -41,7 +48,9 @@ use std::{
 };
 use storage_interface::mock::MockDbReader;
 use tokio::runtime::{Builder, Runtime};
-use vm_validator::mocks::mock_vm_validator::MockVMValidator;
+use vm_validator::{
+    mocks::mock_vm_validator::MockVMValidator, vm_validator::TransactionValidation,
+};
 
 #[derive(Default)]
 struct SharedMempoolNetwork {
@@ -51,6 +60,7 @@ struct SharedMempoolNetwork {
     network_notifs_txs:
         HashMap<PeerId, libra_channel::Sender<(PeerId, ProtocolId), PeerManagerNotification>>,
     network_conn_event_notifs_txs: HashMap<PeerId, conn_notifs_channel::Sender>,
+    reconfig_event_txs: HashMap<PeerId, libra_channel::Sender<(), OnChainConfigPayload>>,
     runtimes: HashMap<PeerId, Runtime>,
     subscribers: HashMap<PeerId, UnboundedReceiver<SharedMempoolNotification>>,
     peer_ids: HashMap<PeerId, PeerId>,
@@ -59,7 +69,19 @@ struct SharedMempoolNetwork {
 // start a shared mempool for a node `peer_id` with config `config`
 // and add it to `smp` network
 fn init_single_shared_mempool(smp: &mut SharedMempoolNetwork, peer_id: PeerId, config: NodeConfig) {
-    let mempool = Arc::new(Mutex::new(CoreMempool::new(&config)));
+    init_single_shared_mempool_with_validator(smp, peer_id, config, MockVMValidator);
+}
+
+// same as `init_single_shared_mempool`, with the VM validator `validator`
+fn init_single_shared_mempool_with_validator<V>(
+    smp: &mut SharedMempoolNetwork,
+    peer_id: PeerId,
+    config: NodeConfig,
+    validator: V,
+) where
+    V: TransactionValidation + 'static,
+{
+    let mempool = Arc::new(Mutex::new(CoreMempool::new(&config, None)));
     let (network_reqs_tx, network_reqs_rx) =
         libra_channel::new(QueueStyle::FIFO, NonZeroUsize::new(8).unwrap(), None);
     let (connection_reqs_tx, _) =
@@ -77,7 +99,7 @@ fn init_single_shared_mempool(smp: &mut SharedMempoolNetwork, peer_id: PeerId, c
     let network_handles = vec![(peer_id, network_sender, network_events)];
     let (_consensus_sender, consensus_events) = mpsc::channel(1_024);
     let (_state_sync_sender, state_sync_events) = mpsc::channel(1_024);
-    let (_reconfig_events, reconfig_events_receiver) =
+    let (reconfig_events, reconfig_events_receiver) =
         libra_channel::new(QueueStyle::LIFO, NonZeroUsize::new(1).unwrap(), None);
 
     let runtime = Builder::new()
@@ -96,7 +118,7 @@ fn init_single_shared_mempool(smp: &mut SharedMempoolNetwork, peer_id: PeerId, c
         state_sync_events,
         reconfig_events_receiver,
         Arc::new(MockDbReader),
-        Arc::new(RwLock::new(MockVMValidator)),
+        Arc::new(RwLock::new(validator)),
         vec![sender],
     );
 
@@ -105,6 +127,7 @@ fn init_single_shared_mempool(smp: &mut SharedMempoolNetwork, peer_id: PeerId, c
     smp.network_notifs_txs.insert(peer_id, network_notifs_tx);
     smp.network_conn_event_notifs_txs
         .insert(peer_id, conn_status_tx);
+    smp.reconfig_event_txs.insert(peer_id, reconfig_events);
     smp.subscribers.insert(peer_id, subscriber);
     smp.runtimes.insert(peer_id, runtime);
 }
@@ -115,7 +138,7 @@ fn init_smp_multiple_networks(
     network_ids: Vec<PeerId>,
     config: NodeConfig,
 ) {
-    let mempool = Arc::new(Mutex::new(CoreMempool::new(&config)));
+    let mempool = Arc::new(Mutex::new(CoreMempool::new(&config, None)));
 
     let mut network_handles = vec![];
     for peer_id in network_ids.iter() {
@@ -242,6 +265,7 @@ impl SharedMempoolNetwork {
                 0,
                 TimelineState::NotReady,
                 false,
+                None,
             );
         }
     }
@@ -881,3 +905,68 @@ fn test_k_policy_broadcast_not_enough_fallbacks() {
     // check that no messages have been sent to fallback upstream peer
     smp.assert_no_message_sent(&fn_0_fallback_network_id);
 }
+
+// accepts every transaction until restarted, then rejects the ones of `rejected_sender`
+#[derive(Clone)]
+struct RejectSenderOnRestart {
+    rejected_sender: AccountAddress,
+    restarted: bool,
+}
+
+impl TransactionValidation for RejectSenderOnRestart {
+    type ValidationInstance = MockVMValidator;
+
+    fn validate_transaction(&self, txn: SignedTransaction) -> Result<VMValidatorResult> {
+        let status = if self.restarted && txn.sender() == self.rejected_sender {
+            Some(VMStatus::new(StatusCode::UNKNOWN_SCRIPT))
+        } else {
+            None
+        };
+        Ok(VMValidatorResult::new(status, 0, false))
+    }
+
+    fn restart(&mut self, _config: OnChainConfigPayload) -> Result<()> {
+        self.restarted = true;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_reconfig_revalidates_transactions() {
+    let mut smp = SharedMempoolNetwork::default();
+    let peer_id = PeerId::random();
+    let validator = RejectSenderOnRestart {
+        rejected_sender: TestTransaction::get_address(1),
+        restarted: false,
+    };
+    init_single_shared_mempool_with_validator(&mut smp, peer_id, NodeConfig::random(), validator);
+    smp.add_txns(
+        &peer_id,
+        vec![
+            TestTransaction::new(0, 0, 1),
+            TestTransaction::new(1, 0, 1),
+            TestTransaction::new(0, 1, 1),
+            TestTransaction::new(1, 1, 1),
+        ],
+    );
+
+    smp.reconfig_event_txs
+        .get_mut(&peer_id)
+        .unwrap()
+        .push((), OnChainConfigPayload::new(1, Arc::new(HashMap::new())))
+        .unwrap();
+    smp.wait_for_event(&peer_id, SharedMempoolNotification::Reconfig);
+
+    // only the transactions of the accepted sender remain
+    let block = smp
+        .mempools
+        .get(&peer_id)
+        .unwrap()
+        .lock()
+        .unwrap()
+        .get_block(100, HashSet::new());
+    assert_eq!(block.len(), 2);
+    assert!(block
+        .iter()
+        .all(|txn| txn.sender() == TestTransaction::get_address(0)));
+}
@@ -53,6 +53,14 @@ pub enum MempoolStatusCode {
     // transaction didn't pass vm_validation
     VmError = 5,
     UnknownStatus = 6,
+    // Gas price is below the current Mempool admission threshold
+    GasPriceTooLow = 7,
+    // Sequence number is too far ahead of the account's current sequence number
+    SequenceNumberTooNew = 8,
+    // The identical transaction has been seen recently, it wasn't processed again
+    AlreadyKnown = 9,
+    // The quota of the transaction's class (governance or regular transactions) is full
+    ClassQuotaFull = 10,
 }
 
 impl TryFrom<u64> for MempoolStatusCode {
@@ -67,6 +75,10 @@ impl TryFrom<u64> for MempoolStatusCode {
             4 => Ok(MempoolStatusCode::InvalidUpdate),
             5 => Ok(MempoolStatusCode::VmError),
             6 => Ok(MempoolStatusCode::UnknownStatus),
+            7 => Ok(MempoolStatusCode::GasPriceTooLow),
+            8 => Ok(MempoolStatusCode::SequenceNumberTooNew),
+            9 => Ok(MempoolStatusCode::AlreadyKnown),
+            10 => Ok(MempoolStatusCode::ClassQuotaFull),
             _ => Err("invalid StatusCode"),
         }
     }